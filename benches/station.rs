@@ -0,0 +1,87 @@
+// How long a 500x500 station takes to run, and to get ready to draw
+// Run with `cargo bench --bench station`
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use space_station_god_game_proto_rust::minimap;
+use space_station_god_game_proto_rust::seed::GameSeed;
+use space_station_god_game_proto_rust::simulation::Simulation;
+use space_station_god_game_proto_rust::station::generator::Bsp;
+use space_station_god_game_proto_rust::station::station::Station;
+
+use std::time::Duration;
+
+type Point2 = glam::Vec2;
+
+const SIZE: usize = 500;
+
+// A second of game time, as the game runs it
+const TICKS: usize = 60;
+const TICK: Duration = Duration::from_nanos(1_000_000_000 / TICKS as u64);
+
+// A station full of rooms, the biggest a player is likely to make, with a crew to live in it
+fn simulation() -> Simulation {
+    let generator = Bsp {
+        width: SIZE,
+        height: SIZE,
+        max_depth: 12,
+        ..Bsp::default()
+    };
+    Simulation::new(GameSeed::new(1), Point2::zero(), &generator, 20)
+}
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("second");
+    group.sample_size(10);
+    let mut sim = simulation();
+    group.bench_function(BenchmarkId::new("Station::update", SIZE), |b| {
+        b.iter(|| {
+            for _ in 0..TICKS {
+                sim.station.update(TICK);
+            }
+        })
+    });
+    let mut sim = simulation();
+    group.bench_function(BenchmarkId::new("Simulation::tick", SIZE), |b| {
+        b.iter(|| {
+            for _ in 0..TICKS {
+                sim.tick(TICK);
+            }
+            black_box(sim.ticks())
+        })
+    });
+    group.finish();
+}
+
+// Everything `Station::build_mesh` works out from scratch, short of handing it to the graphics card
+// That needs a window, so the meshes, sprites and minimap pictures are only got ready here
+fn build_mesh(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_mesh");
+    group.sample_size(10);
+    let sim = simulation();
+    let station = &sim.station;
+    group.bench_function(BenchmarkId::new("Mesh", SIZE), |b| {
+        b.iter(|| {
+            for (_chunk_pos, chunk) in station.get_chunks() {
+                black_box(Station::chunk_mesh(chunk).unwrap());
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::new("Sprites", SIZE), |b| {
+        b.iter(|| {
+            for (_chunk_pos, chunk) in station.get_chunks() {
+                black_box(Station::chunk_sprites(chunk));
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::new("minimap", SIZE), |b| {
+        let bounds = station.get_bounds().unwrap();
+        b.iter(|| {
+            for deck in station.get_decks() {
+                black_box(minimap::layer_pixels(station, deck, bounds));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, update, build_mesh);
+criterion_main!(benches);
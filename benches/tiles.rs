@@ -0,0 +1,143 @@
+// Compares the chunked tile storage with the single ordered map every tile used to go in, on a 500x500 station
+// Run with `cargo bench --bench tiles`
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use oorandom::Rand32;
+use space_station_god_game_proto_rust::station::chunk::TileMap;
+use space_station_god_game_proto_rust::station::gridposition::GridPosition;
+use space_station_god_game_proto_rust::station::tile::{Tile, TileType, WallDirection};
+
+use std::collections::BTreeMap;
+
+const SIZE: i32 = 500;
+
+// A big square of floor with a wall round the edge
+fn tiles() -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for x in 0..SIZE {
+        for y in 0..SIZE {
+            let edge = x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1;
+            let kind = if edge {
+                TileType::Wall(WallDirection::Full)
+            } else {
+                TileType::Floor
+            };
+            tiles.push(Tile::new(GridPosition::new(x, y), kind));
+        }
+    }
+    tiles
+}
+
+fn btree_map() -> BTreeMap<GridPosition, Tile> {
+    tiles().into_iter().map(|tile| (tile.pos, tile)).collect()
+}
+
+fn tile_map() -> TileMap {
+    TileMap::from(tiles())
+}
+
+// Somewhere to look, with a few misses off the edge
+fn lookups() -> Vec<GridPosition> {
+    let mut rng = Rand32::new(1);
+    (0..10_000)
+        .map(|_| {
+            let x = rng.rand_range(0..SIZE as u32 + 10) as i32 - 5;
+            let y = rng.rand_range(0..SIZE as u32 + 10) as i32 - 5;
+            GridPosition::new(x, y)
+        })
+        .collect()
+}
+
+fn get(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get");
+    let positions = lookups();
+    let old = btree_map();
+    let new = tile_map();
+    group.bench_function(BenchmarkId::new("BTreeMap", SIZE), |b| {
+        b.iter(|| positions.iter().filter(|pos| old.contains_key(pos)).count())
+    });
+    group.bench_function(BenchmarkId::new("TileMap", SIZE), |b| {
+        b.iter(|| positions.iter().filter(|pos| new.contains_key(pos)).count())
+    });
+    group.finish();
+}
+
+fn iterate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    let old = btree_map();
+    let new = tile_map();
+    group.bench_function(BenchmarkId::new("BTreeMap", SIZE), |b| {
+        b.iter(|| {
+            old.values()
+                .filter(|tile| tile.kind == TileType::Floor)
+                .count()
+        })
+    });
+    group.bench_function(BenchmarkId::new("TileMap", SIZE), |b| {
+        b.iter(|| {
+            new.values()
+                .filter(|tile| tile.kind == TileType::Floor)
+                .count()
+        })
+    });
+    group.finish();
+}
+
+// Picking a random floor tile, the way `Station::get_random_tile` did it before and does it now
+fn random_tile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("random_tile");
+    let old = btree_map();
+    let new = tile_map();
+    group.bench_function(BenchmarkId::new("BTreeMap", SIZE), |b| {
+        let mut rng = Rand32::new(1);
+        b.iter(|| {
+            let options: Vec<&Tile> = old
+                .values()
+                .filter(|tile| tile.kind == TileType::Floor)
+                .collect();
+            let index = rng.rand_range(0..options.len() as u32) as usize;
+            black_box(options[index].pos)
+        })
+    });
+    group.bench_function(BenchmarkId::new("TileMap", SIZE), |b| {
+        let mut rng = Rand32::new(1);
+        b.iter(|| {
+            let count = new
+                .values()
+                .filter(|tile| tile.kind == TileType::Floor)
+                .count();
+            let index = rng.rand_range(0..count as u32) as usize;
+            black_box(
+                new.values()
+                    .filter(|tile| tile.kind == TileType::Floor)
+                    .nth(index)
+                    .unwrap()
+                    .pos,
+            )
+        })
+    });
+    group.finish();
+}
+
+// Just the tiles on one square of the station, like drawing what's on screen
+fn corner(c: &mut Criterion) {
+    let mut group = c.benchmark_group("corner");
+    let old = btree_map();
+    let new = tile_map();
+    let wanted = |pos: &GridPosition| pos.x < 32 && pos.y < 32;
+    group.bench_function(BenchmarkId::new("BTreeMap", SIZE), |b| {
+        b.iter(|| old.keys().filter(|pos| wanted(pos)).count())
+    });
+    group.bench_function(BenchmarkId::new("TileMap", SIZE), |b| {
+        b.iter(|| {
+            new.chunks()
+                .filter(|(chunk_pos, _chunk)| chunk_pos.x < 2 && chunk_pos.y < 2)
+                .flat_map(|(_chunk_pos, chunk)| chunk.tiles())
+                .filter(|tile| wanted(&tile.pos))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, get, iterate, random_tile, corner);
+criterion_main!(benches);
@@ -0,0 +1,134 @@
+// Runs the simulation with no window, as fast as it will go, and reports what happened
+// Usage: batch [--seed 1] [--runs 1] [--generator cellular] [--width 21] [--height 13] [--crew 3] [--hours 1]
+//              [--map resources/stations/outpost.json] [--csv out.csv]
+// With more than one run, each run uses the next seed along
+// A Tiled map replaces the generator, and brings its own crew
+use space_station_god_game_proto_rust::seed::GameSeed;
+use space_station_god_game_proto_rust::simulation::{Simulation, TICKS_PER_SECOND, TICK_DURATION};
+use space_station_god_game_proto_rust::station::generator::GeneratorType;
+use space_station_god_game_proto_rust::stats::{RunStats, CSV_HEADER};
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::{env, fs, mem};
+
+type Point2 = glam::Vec2;
+
+// How often, in ticks, the crew are sampled for the time series (once an in-game minute)
+const SAMPLE_INTERVAL: u64 = TICKS_PER_SECOND as u64 * 60;
+
+// Read `--name <value>` from the command line, or use the default if it isn't there
+fn arg<T: FromStr>(name: &str, default: T) -> T {
+    match env::args().skip_while(|arg| arg != name).nth(1) {
+        Some(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("Invalid {} value: {}", name, value)),
+        None => default,
+    }
+}
+
+// Simulate for a number of in-game hours, collecting stats as it goes
+fn run(mut simulation: Simulation, hours: u64) -> RunStats {
+    let stats = Rc::new(RefCell::new(RunStats::new()));
+    let recorder = stats.clone();
+    simulation
+        .events
+        .subscribe(move |event| recorder.borrow_mut().record(event));
+
+    // Events from generating the station are still queued up, so count those too
+    simulation.events.dispatch();
+    stats.borrow_mut().sample(&simulation);
+
+    let ticks = hours * 60 * SAMPLE_INTERVAL;
+    for _ in 0..ticks {
+        simulation.tick(TICK_DURATION);
+        if simulation.ticks().is_multiple_of(SAMPLE_INTERVAL) {
+            stats.borrow_mut().sample(&simulation);
+        }
+    }
+
+    // Drop the subscriber so the stats are ours alone
+    mem::drop(simulation);
+    Rc::try_unwrap(stats).ok().unwrap().into_inner()
+}
+
+// Print a human-readable summary of some stats
+fn print_summary(title: &str, stats: &RunStats) {
+    println!("{}", title);
+    println!("  Deaths: {}", stats.total_deaths());
+    for (cause, count) in &stats.deaths {
+        println!("    {:?}: {}", cause, count);
+    }
+    println!("  Average hunger: {:.2}", stats.avg_hunger());
+    println!("  Average thirst: {:.2}", stats.avg_thirst());
+    println!(
+        "  Items consumed: {}",
+        stats.items_consumed.values().sum::<u32>()
+    );
+    for (item, count) in &stats.items_consumed {
+        println!("    {:?}: {}", item, count);
+    }
+    println!("  Path failures: {}", stats.path_failures);
+}
+
+fn main() -> io::Result<()> {
+    let seed: u64 = arg("--seed", 1);
+    let runs: u64 = arg("--runs", 1);
+    let generator_type: GeneratorType = arg("--generator", GeneratorType::Cellular);
+    let width: usize = arg("--width", 21);
+    let height: usize = arg("--height", 13);
+    let crew: usize = arg("--crew", 3);
+    let hours: u64 = arg("--hours", 1);
+    let map_path: Option<String> = env::args().skip_while(|arg| arg != "--map").nth(1);
+    let csv_path: Option<String> = env::args().skip_while(|arg| arg != "--csv").nth(1);
+
+    let mut csv = match &csv_path {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            writeln!(writer, "{}", CSV_HEADER)?;
+            Some(writer)
+        }
+        None => None,
+    };
+
+    let map = match &map_path {
+        Some(path) => Some(fs::read_to_string(path)?),
+        None => None,
+    };
+    let generator = generator_type.generator(width, height);
+    let mut totals = RunStats::new();
+    for seed in seed..seed + runs {
+        let seed = GameSeed::new(seed);
+        let simulation = match &map {
+            Some(json) => Simulation::from_tiled(seed, Point2::zero(), json)
+                .unwrap_or_else(|error| panic!("Invalid map: {}", error)),
+            None => Simulation::new(seed, Point2::zero(), generator.as_ref(), crew),
+        };
+        let stats = run(simulation, hours);
+
+        print_summary(&format!("Seed {}:", seed), &stats);
+        if let Some(writer) = &mut csv {
+            stats.write_csv(writer, &seed.to_string())?;
+        }
+        totals.merge(&stats);
+    }
+
+    if runs > 1 {
+        let station = match &map_path {
+            Some(path) => path.clone(),
+            None => format!("{} crew, {} {}x{}", crew, generator_type, width, height),
+        };
+        print_summary(
+            &format!("All {} runs ({}, {} hours each):", runs, station, hours),
+            &totals,
+        );
+    }
+    if let Some(path) = csv_path {
+        println!("Wrote time series to {}", path);
+    }
+
+    Ok(())
+}
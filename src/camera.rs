@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use std::collections::BTreeSet;
+
 // Alias some types to making reading/writing code easier and also in case math libraries change again
 type Point2 = glam::Vec2;
 
@@ -10,6 +12,7 @@ const ZOOM_AMOUNT: f32 = 2.0; // TODO: Tweak this multiple
 pub struct Camera {
     pub pos: Point2,
     pub zoom: Point2,
+    pub deck: i32, // Which of the station's decks we're looking at
 }
 
 impl Camera {
@@ -18,6 +21,7 @@ impl Camera {
         Camera {
             pos: Point2::zero(),
             zoom: Point2::one(),
+            deck: 0,
         }
     }
 
@@ -50,9 +54,77 @@ impl Camera {
         }
     }
 
+    // Look at the next deck up or down, if there is one. `decks` is every deck there is, lowest first
+    pub fn change_deck(&mut self, decks: &BTreeSet<i32>, up: bool) {
+        let next = if up {
+            decks.range(self.deck + 1..).next()
+        } else {
+            decks.range(..self.deck).next_back()
+        };
+        if let Some(deck) = next {
+            self.deck = *deck;
+        }
+    }
+
+    // Move straight to looking at a position in the station, with it in the middle of a screen this size
+    // Everything's drawn at the station's position, offset by the camera's, then zoomed, so this undoes that for the middle
+    pub fn look_at(&mut self, target: Point2, station_pos: Point2, screen_size: Point2) {
+        self.pos = target - (screen_size / 2.0 - station_pos) / self.zoom;
+    }
+
+    // The part of the station that's on a screen this size, as (top-left, size) in the station's own coordinates
+    pub fn viewport(&self, station_pos: Point2, screen_size: Point2) -> (Point2, Point2) {
+        (self.pos - station_pos / self.zoom, screen_size / self.zoom)
+    }
+
+    // The world position under a point on the screen, undoing the camera's offset and zoom
+    pub fn to_world(&self, screen_pos: Point2, station_pos: Point2) -> Point2 {
+        station_pos + (screen_pos - station_pos) / self.zoom + self.pos
+    }
+
     // Resets the camera to the default center and zoom positions
     pub fn reset(&mut self) {
         self.pos = Point2::zero();
         self.zoom = Point2::one();
     }
 }
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Camera;
+
+    type Point2 = glam::Vec2;
+
+    #[test]
+    fn look_at() {
+        let station_pos = Point2::new(100.0, 50.0);
+        let screen_size = Point2::new(800.0, 600.0);
+        let target = Point2::new(300.0, -20.0);
+        for zoom in [0.0, 1.0, 3.0] {
+            let mut camera = Camera::new();
+            camera.zoom(zoom);
+            camera.look_at(target, station_pos, screen_size);
+
+            // Where the target ends up on screen, the same way the station is drawn
+            let on_screen = station_pos + (target - camera.pos) * camera.zoom;
+            assert!(
+                (on_screen - screen_size / 2.0).length() < 0.001,
+                "In the middle at {}x",
+                camera.zoom.x
+            );
+
+            let (top_left, size) = camera.viewport(station_pos, screen_size);
+            assert!((top_left + size / 2.0 - target).length() < 0.001);
+
+            // And clicking there finds it again
+            let world = camera.to_world(screen_size / 2.0, station_pos);
+            assert!((world - (station_pos + target)).length() < 0.001);
+        }
+    }
+}
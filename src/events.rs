@@ -0,0 +1,271 @@
+use crate::inhabitant::InhabitantType;
+use crate::item::ItemType;
+use crate::scenes::scene::SceneType;
+use crate::station::build::BuildTool;
+use crate::station::fire::FireCause;
+use crate::station::gridposition::GridPosition;
+
+use uuid::Uuid;
+
+use std::fmt;
+
+// Something that happened in the game that other parts of it might care about
+#[derive(Clone, PartialEq, Debug)]
+pub enum SimEvent {
+    InhabitantAdded {
+        inhabitant: Uuid,
+        kind: InhabitantType,
+        pos: GridPosition,
+    },
+    NeedCritical {
+        inhabitant: Uuid,
+        need: Need,
+    },
+    Died {
+        inhabitant: Uuid,
+        cause: DamageCause,
+    },
+    Searching {
+        inhabitant: Uuid,
+        items: Vec<ItemType>,
+    },
+    PathStarted {
+        inhabitant: Uuid,
+        from: GridPosition,
+        to: GridPosition,
+    },
+    PathFailed {
+        inhabitant: Uuid,
+        from: GridPosition,
+        to: GridPosition,
+    },
+    Arrived {
+        inhabitant: Uuid,
+        pos: GridPosition,
+    },
+    ItemConsumed {
+        inhabitant: Uuid,
+        item: ItemType,
+        from: ItemSource,
+    },
+    ItemPlaced {
+        item: ItemType,
+        pos: GridPosition,
+    },
+    JobPosted {
+        pos: GridPosition,
+        tool: BuildTool,
+    },
+    JobCancelled {
+        pos: GridPosition,
+        tool: BuildTool,
+        reason: &'static str,
+    },
+    Built {
+        pos: GridPosition,
+        tool: BuildTool,
+    },
+    FireStarted {
+        pos: GridPosition,
+        cause: FireCause,
+    },
+    FireOut {
+        pos: GridPosition,
+    },
+    MeteorStrike {
+        pos: GridPosition,
+        damage: u8,
+    },
+    HullBreached {
+        pos: GridPosition,
+    },
+    SceneChanged {
+        from: Option<SceneType>,
+        to: SceneType,
+    },
+    ReplayDiverged {
+        tick: u64,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+// The things an inhabitant needs to stay alive
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Need {
+    Food,
+    Drink,
+    Air,
+    Warmth,
+    Cooling,
+}
+
+// What hurt an inhabitant
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DamageCause {
+    Starvation,
+    Dehydration,
+    Suffocation,
+    Hypothermia,
+    Heatstroke,
+    Burns,
+}
+
+// Where an inhabitant got something from
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ItemSource {
+    Inventory,
+    Tile,
+}
+
+impl fmt::Display for SimEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimEvent::InhabitantAdded {
+                inhabitant,
+                kind,
+                pos,
+            } => write!(f, "[{}] {:?} inhabitant added at {}", inhabitant, kind, pos),
+            SimEvent::NeedCritical { inhabitant, need } => match need {
+                Need::Food => write!(f, "[{}] Starving! Taking damage.", inhabitant),
+                Need::Drink => write!(f, "[{}] Parched! Taking damage.", inhabitant),
+                Need::Air => write!(f, "[{}] Can't breathe! Taking damage.", inhabitant),
+                Need::Warmth => write!(f, "[{}] Freezing! Taking damage.", inhabitant),
+                Need::Cooling => write!(f, "[{}] Overheating! Taking damage.", inhabitant),
+            },
+            SimEvent::Died { inhabitant, cause } => {
+                write!(f, "[{}] I die. I am dead. ({:?})", inhabitant, cause)
+            }
+            SimEvent::Searching { inhabitant, items } => {
+                write!(f, "[{}] Searching for {:?}", inhabitant, items)
+            }
+            SimEvent::PathStarted {
+                inhabitant,
+                from,
+                to,
+            } => write!(f, "[{}] Pathing from {} to {}", inhabitant, from, to),
+            SimEvent::PathFailed {
+                inhabitant,
+                from,
+                to,
+            } => write!(f, "[{}] No path from {} to {}", inhabitant, from, to),
+            SimEvent::Arrived { inhabitant, pos } => {
+                write!(f, "[{}] Arrived at {}", inhabitant, pos)
+            }
+            SimEvent::ItemConsumed {
+                inhabitant,
+                item,
+                from,
+            } => write!(f, "[{}] Consumed {:?} from {:?}", inhabitant, item, from),
+            SimEvent::ItemPlaced { item, pos } => write!(f, "Placed {:?} at {}", item, pos),
+            SimEvent::JobPosted { pos, tool } => write!(f, "Planned {} at {}", tool, pos),
+            SimEvent::JobCancelled { pos, tool, reason } => {
+                write!(f, "Gave up on {} at {}: {}", tool, pos, reason)
+            }
+            SimEvent::Built { pos, tool } => write!(f, "Built {} at {}", tool, pos),
+            SimEvent::FireStarted { pos, cause } => {
+                write!(f, "Fire at {}! ({:?})", pos, cause)
+            }
+            SimEvent::FireOut { pos } => write!(f, "Fire at {} is out", pos),
+            SimEvent::MeteorStrike { pos, damage } => {
+                write!(
+                    f,
+                    "Micrometeorite hit the hull at {} ({} damage)",
+                    pos, damage
+                )
+            }
+            SimEvent::HullBreached { pos } => write!(f, "Breach! Hull open to space at {}", pos),
+            SimEvent::SceneChanged { from, to } => match from {
+                Some(from) => write!(f, "Scene changed from {:?} to {:?}", from, to),
+                None => write!(f, "Scene changed to {:?}", to),
+            },
+            SimEvent::ReplayDiverged {
+                tick,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Replay diverged at tick {}: expected checksum {:016x}, got {:016x}",
+                tick, expected, actual
+            ),
+        }
+    }
+}
+
+type Subscriber = Box<dyn FnMut(&SimEvent)>;
+
+// Events are published onto the bus as they happen, and handed to every subscriber when the bus is dispatched
+// Queueing them means subscribers never run in the middle of a simulation update
+#[derive(Default)]
+pub struct EventBus {
+    queue: Vec<SimEvent>,
+    subscribers: Vec<Subscriber>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus::default()
+    }
+
+    // Register a callback for every future event
+    pub fn subscribe<F>(&mut self, subscriber: F)
+    where
+        F: FnMut(&SimEvent) + 'static,
+    {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    // Queue up an event for the next dispatch
+    pub fn publish(&mut self, event: SimEvent) {
+        self.queue.push(event);
+    }
+
+    // Hand all queued events to the subscribers, in the order they happened
+    pub fn dispatch(&mut self) {
+        for event in self.queue.drain(..) {
+            for subscriber in self.subscribers.iter_mut() {
+                subscriber(&event);
+            }
+        }
+    }
+}
+
+// A subscriber that prints events to stdout, like the game always has
+pub fn print(event: &SimEvent) {
+    println!("{}", event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventBus, SimEvent};
+    use crate::scenes::scene::SceneType;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn dispatch_in_order() {
+        let mut bus = EventBus::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_by_subscriber = seen.clone();
+        bus.subscribe(move |event| seen_by_subscriber.borrow_mut().push(event.clone()));
+
+        let first = SimEvent::SceneChanged {
+            from: None,
+            to: SceneType::Title,
+        };
+        let second = SimEvent::SceneChanged {
+            from: Some(SceneType::Title),
+            to: SceneType::Game,
+        };
+        bus.publish(first.clone());
+        bus.publish(second.clone());
+        assert!(seen.borrow().is_empty(), "Nothing is seen until dispatch");
+
+        bus.dispatch();
+        assert_eq!(vec![first, second], *seen.borrow());
+
+        bus.dispatch();
+        assert_eq!(2, seen.borrow().len(), "Events are only dispatched once");
+    }
+}
@@ -1,29 +1,57 @@
 use crate::camera::Camera;
+use crate::events::*;
 use crate::item::*;
+use crate::jobs::{Job, Jobs};
+use crate::seed::new_id;
+use crate::station::fire::*;
 use crate::station::gridposition::*;
+use crate::station::heat::SPACE_TEMPERATURE;
+use crate::station::room::*;
 use crate::station::station::*;
 use crate::station::tile::*;
 
 use ggez::graphics::{Color, DrawMode, DrawParam, Mesh};
-use ggez::{graphics, timer, Context, GameResult};
+use ggez::{graphics, Context, GameResult};
 
 use keyframe::{ease, functions::EaseInOut};
 use oorandom::Rand32;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use std::collections::BTreeSet;
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
 use std::{fmt, time};
 
 // Alias some types to making reading/writing code easier and also in case math libraries change again
 type Point2 = glam::Vec2;
 
+// How long it takes to walk from one tile to the next
+const SECONDS_PER_TILE: f64 = 2.0;
+
+// How many moles of oxygen get breathed in, and breathed back out as CO2, every second
+// Lots more than a real person, so a sealed room goes stale in hours rather than days
+const BREATH_RATE: f32 = 0.01;
+
+// Below this much oxygen, in kPa, there isn't enough to breathe, and it hurts once a second
+const MIN_O2_PRESSURE: f32 = 16.0;
+
+// The temperatures, in kelvin, most people can stand. Outside of them it hurts once a second
+const COMFORT_RANGE: RangeInclusive<f32> = 278.15..=308.15;
+const SOLDIER_COMFORT_RANGE: RangeInclusive<f32> = 268.15..=318.15; // Trained for worse
+
+// How much standing in a fire at full intensity hurts, every second
+const BURN_DAMAGE: f32 = 5.0;
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 enum Behavior {
     Wander,
     Search(Vec<ItemType>),
+    GoTo(GridPosition),
     Eat,
     Drink,
-    Work,
+    Work(GridPosition),       // Building whatever the job on this tile is
+    Extinguish(GridPosition), // Putting out the fire on this tile
 }
 
 // An Inhabitant of the Station
@@ -31,12 +59,14 @@ enum Behavior {
 pub struct Inhabitant {
     // These are world positions (since they can go outside the station)
     pub pos: Point2,          // Current position
+    pub deck: i32, // Which deck we're on, since world positions are the same on all of them
     pub dest: Option<Point2>, // Pathfinding destination to reach
 
     // Pathfinding status
     path: Vec<GridPosition>,
     current_waypoint: usize,
-    move_elapsed: f64, // Seconds we've been moving from source to dest
+    move_from: Point2, // Where we started moving towards the current waypoint
+    move_elapsed: f64, // Seconds we've been moving towards the current waypoint
 
     behaviors: Vec<Behavior>,
 
@@ -45,13 +75,17 @@ pub struct Inhabitant {
     hunger: u8,
     thirst: u8,
     age: time::Duration,
+    out_of_breath: f64,           // Seconds since the last breath of decent air
+    comfort: RangeInclusive<f32>, // Temperatures we can stand
+    exposed: f64,                 // Seconds we've been too hot or too cold for
+    burning: f64,                 // Seconds we've been standing in a fire for
 
     items: Vec<Item>,
 
-    id: uuid::Uuid,
+    id: Uuid,
 }
 
-#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Debug, Hash, Serialize, Deserialize)]
 pub enum InhabitantType {
     Pilot,
     Engineer,
@@ -80,32 +114,85 @@ impl fmt::Display for Inhabitant {
 }
 
 impl Inhabitant {
-    pub fn new(pos: Point2, kind: InhabitantType) -> Inhabitant {
+    pub fn new(pos: Point2, kind: InhabitantType, rng: &mut Rand32) -> Inhabitant {
+        let id = new_id(rng);
         let items = match kind {
             InhabitantType::Ghost => vec![],
             _ => vec![
-                Item::new(GridPosition::new(0, 0), ItemType::Food(FoodType::EnergyBar)),
-                Item::new(GridPosition::new(1, 0), ItemType::Drink(DrinkType::Water)),
+                Item::new(
+                    GridPosition::new(0, 0),
+                    ItemType::Food(FoodType::EnergyBar),
+                    rng,
+                ),
+                Item::new(
+                    GridPosition::new(1, 0),
+                    ItemType::Drink(DrinkType::Water),
+                    rng,
+                ),
             ],
         };
 
         Inhabitant {
-            id: Uuid::new_v4(),
+            id,
             pos,
+            deck: 0,
             dest: None,
             path: Vec::new(),
             current_waypoint: 0,
+            move_from: pos,
             move_elapsed: 0.0,
             kind,
             health: 100,
             hunger: 0,
             thirst: 0,
             age: time::Duration::from_micros(0),
+            out_of_breath: 0.0,
+            comfort: match kind {
+                InhabitantType::Soldier => SOLDIER_COMFORT_RANGE,
+                _ => COMFORT_RANGE,
+            },
+            exposed: 0.0,
+            burning: 0.0,
             items,
             behaviors: Vec::with_capacity(7),
         }
     }
 
+    pub fn get_id(&self) -> Uuid {
+        self.id
+    }
+
+    // Feed where we are, how we're doing and what we're carrying into a checksum
+    pub fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.kind.hash(state);
+        self.pos.x.to_bits().hash(state);
+        self.pos.y.to_bits().hash(state);
+        self.deck.hash(state);
+        self.path.hash(state);
+        self.current_waypoint.hash(state);
+        self.health.hash(state);
+        self.hunger.hash(state);
+        self.thirst.hash(state);
+        self.age.hash(state);
+        self.items.len().hash(state);
+        for item in self.items.iter() {
+            item.hash_state(state);
+        }
+    }
+
+    pub fn get_kind(&self) -> InhabitantType {
+        self.kind
+    }
+
+    pub fn get_hunger(&self) -> u8 {
+        self.hunger
+    }
+
+    pub fn get_thirst(&self) -> u8 {
+        self.thirst
+    }
+
     // Whether we can move to a type of tile
     // Doesn't check whether we can _get_ there, but only if we can be there
     pub fn can_move_to(&self, tile: Option<&Tile>) -> bool {
@@ -115,29 +202,38 @@ impl Inhabitant {
 
             // Everyone else needs to test the type of tile
             _ => match tile {
-                Some(t) => match t.kind {
-                    TileType::Wall(_) => false,
-                    TileType::Door(_) => true, // TODO: Check if we can open it?
-                    TileType::Floor => true,
-                },
+                Some(t) => t.is_passable(self.kind),
                 None => false,
             },
         }
     }
 
+    // Advance the inhabitant by `dt` of simulated time
     pub fn update(
         &mut self,
-        ctx: &mut Context,
-        station: &Station,
+        dt: time::Duration,
+        station: &mut Station,
+        jobs: &mut Jobs,
         rng: &mut Rand32,
-    ) -> GameResult<()> {
-        let dt = timer::delta(ctx); // Time since last frame
-
+        events: &mut EventBus,
+    ) {
         // Look, we're growing!
         self.age += dt;
 
-        // Where are we?
-        let current_tile = station.get_tile_from_world(self.pos).unwrap();
+        // Where are we? Even with the floor taken out from under us, we still feel it
+        let current_pos = station.grid_position_from_world(self.pos, self.deck);
+        self.breathe(dt, station, current_pos, events);
+        self.feel_temperature(dt, station, current_pos, events);
+        self.feel_fire(dt, station, current_pos, events);
+
+        // But there's nowhere to walk to from nothing, so stay put
+        if !station.has_tile(current_pos) {
+            self.stop();
+            return;
+        }
+
+        // A fire needs putting out more than anything else needs doing
+        self.respond_to_fire(station, jobs, current_pos);
 
         // Perform next behavior
         let next = self.behaviors.last();
@@ -146,89 +242,130 @@ impl Inhabitant {
                 // Move
                 match self.dest {
                     Some(_) => {
-                        self.keep_moving(dt, station);
+                        self.keep_moving(dt, station, events);
                     }
                     None => {
                         let tile = station.get_random_tile(TileType::Floor, rng);
 
                         if self.can_move_to(tile) {
-                            let dest = tile.unwrap().to_world_position(station);
-                            self.set_destination(station, dest);
+                            let dest = tile.unwrap().pos;
+                            self.set_destination(station, dest, events);
                         }
                     }
                 }
             }
             Some(Behavior::Eat) => {
-                if self.has_item(get_food_types()) {
+                if let Some(food) = self.take_item(get_food_types()) {
                     // If we have food on our person, eat it
-                    println!("{} Eating from inventory", self);
-                    self.eat(&Item::new(
-                        current_tile.pos,
-                        ItemType::Food(FoodType::EnergyBar),
-                    )); // TODO: Actually consume the food from inventory
+                    self.eat(&food);
+                    events.publish(SimEvent::ItemConsumed {
+                        inhabitant: self.id,
+                        item: food.get_type(),
+                        from: ItemSource::Inventory,
+                    });
                     self.behaviors.pop();
-                } else if current_tile.has_item(get_food_types()) {
+                } else if let Some(food) = station.take_item(current_pos, get_food_types()) {
                     // If there's food here on this tile, eat it
-                    println!("{} Eating from tile", self);
-                    self.eat(&Item::new(
-                        current_tile.pos,
-                        ItemType::Food(FoodType::EnergyBar),
-                    )); // TODO: Actually consume the food from the tile
+                    self.eat(&food);
+                    events.publish(SimEvent::ItemConsumed {
+                        inhabitant: self.id,
+                        item: food.get_type(),
+                        from: ItemSource::Tile,
+                    });
                     self.behaviors.pop();
+
+                    // Meals in the galley get heated up, and every so often that goes wrong
+                    let in_galley = station
+                        .get_room_at(current_pos)
+                        .is_some_and(|room| room.kind == RoomType::Galley);
+                    if in_galley && rng.rand_float() < COOKING_ACCIDENT_CHANCE {
+                        station.ignite(current_pos, FireCause::Cooking, events);
+                    }
                 } else {
                     // Otherwise, search for it
-                    println!("{} Searching for food", self);
+                    events.publish(SimEvent::Searching {
+                        inhabitant: self.id,
+                        items: get_food_types(),
+                    });
                     self.behaviors.push(Behavior::Search(get_food_types()));
                 }
             }
             Some(Behavior::Drink) => {
-                if self.has_item(get_drink_types()) {
+                if let Some(drink) = self.take_item(get_drink_types()) {
                     // If we have drink on our person, drink it
-                    println!("{} Drinking from inventory", self);
-                    self.drink(&Item::new(
-                        current_tile.pos,
-                        ItemType::Drink(DrinkType::Water),
-                    )); // TODO: Actually consume the drink from inventory
+                    self.drink(&drink);
+                    events.publish(SimEvent::ItemConsumed {
+                        inhabitant: self.id,
+                        item: drink.get_type(),
+                        from: ItemSource::Inventory,
+                    });
                     self.behaviors.pop();
-                } else if current_tile.has_item(get_drink_types()) {
+                } else if let Some(drink) = station.take_item(current_pos, get_drink_types()) {
                     // If there's drink here on this tile, drink it
-                    println!("{} Drinking from tile", self);
-                    self.drink(&Item::new(
-                        current_tile.pos,
-                        ItemType::Drink(DrinkType::Water),
-                    )); // TODO: Actually consume the drink from the tile
+                    self.drink(&drink);
+                    events.publish(SimEvent::ItemConsumed {
+                        inhabitant: self.id,
+                        item: drink.get_type(),
+                        from: ItemSource::Tile,
+                    });
                     self.behaviors.pop();
                 } else {
                     // Otherwise, search for it
-                    println!("{} Searching for drink", self);
+                    events.publish(SimEvent::Searching {
+                        inhabitant: self.id,
+                        items: get_drink_types(),
+                    });
                     self.behaviors.push(Behavior::Search(get_drink_types()));
                 }
             }
             Some(Behavior::Search(item_types)) => match self.dest {
                 Some(_) => {
-                    self.keep_moving(dt, station);
+                    self.keep_moving(dt, station, events);
                 }
                 None => {
-                    let mut best_path: Vec<GridPosition> = Vec::new();
-                    let found = station.find_items(item_types.to_vec());
-                    for pos in found.iter() {
-                        let path = station.path_to(current_tile.pos, **pos);
-                        if best_path.is_empty() || path.len() < best_path.len() {
-                            best_path = path;
-                        }
+                    // Look in the galley first, if there is one, and then everywhere else
+                    let found = station.find_items(item_types.to_vec(), current_pos);
+                    let (in_galley, elsewhere): (BTreeSet<GridPosition>, BTreeSet<GridPosition>) =
+                        found.into_iter().partition(|pos| {
+                            station
+                                .get_room_at(*pos)
+                                .is_some_and(|room| room.kind == RoomType::Galley)
+                        });
+
+                    let mut best_path = station.path_to_any(current_pos, &in_galley, self.kind);
+                    if best_path.is_empty() {
+                        best_path = station.path_to_any(current_pos, &elsewhere, self.kind);
                     }
 
                     if !best_path.is_empty() {
                         let dest = best_path.pop().unwrap();
-                        self.set_destination(
-                            station,
-                            station.get_tile(dest).unwrap().to_world_position(station),
-                        );
+                        self.set_destination(station, dest, events);
                     }
                 }
             },
-            Some(Behavior::Work) => {
-                // TODO
+            Some(Behavior::GoTo(pos)) => match self.dest {
+                Some(_) => {
+                    self.keep_moving(dt, station, events);
+                }
+                None => {
+                    let pos = *pos;
+                    if pos != current_pos {
+                        self.set_destination(station, pos, events);
+                    }
+
+                    // Already there, or can't get there
+                    if self.dest.is_none() {
+                        self.behaviors.pop();
+                    }
+                }
+            },
+            Some(Behavior::Work(pos)) => {
+                let pos = *pos;
+                self.work(pos, current_pos, dt, station, jobs);
+            }
+            Some(Behavior::Extinguish(pos)) => {
+                let pos = *pos;
+                self.extinguish(pos, current_pos, dt, station, jobs, events);
             }
             None => {
                 // Decide what to do
@@ -236,16 +373,242 @@ impl Inhabitant {
                     self.behaviors.push(Behavior::Eat);
                 } else if self.wants_drink() >= 0.5 {
                     self.behaviors.push(Behavior::Drink);
+                } else if let Some(pos) = self.find_job(station, jobs, current_pos) {
+                    jobs.get_mut(pos).unwrap().assigned = Some(self.id);
+                    self.behaviors.push(Behavior::Work(pos));
                 } else {
                     self.behaviors.push(Behavior::Wander);
                 }
             }
         }
+    }
 
-        Ok(())
+    // Pick a job to go and work on, if we're the sort of person who builds things
+    fn find_job(&self, station: &Station, jobs: &Jobs, from: GridPosition) -> Option<GridPosition> {
+        if self.kind != InhabitantType::Engineer {
+            return None;
+        }
+
+        // No point taking on a job we can't get the materials for
+        let has_materials =
+            self.has_item(get_material_types()) || station.count_items(get_material_types()) > 0;
+
+        let mut candidates: Vec<&Job> = jobs
+            .unassigned()
+            .filter(|job| has_materials || !job.needs_materials())
+            .collect();
+        candidates.sort_by_key(|job| job.pos.distance(from));
+        candidates
+            .into_iter()
+            .find(|job| self.work_spot(station, job.pos, from).is_some())
+            .map(|job| job.pos)
+    }
+
+    // Drop everything for a fire, if there's one we should be fighting
+    // Engineers and Soldiers go straight away. Everyone else leaves it to them unless nobody's come for a while
+    fn respond_to_fire(&mut self, station: &Station, jobs: &mut Jobs, from: GridPosition) {
+        if self.kind == InhabitantType::Ghost
+            || self
+                .behaviors
+                .iter()
+                .any(|behavior| matches!(behavior, Behavior::Extinguish(_)))
+        {
+            return;
+        }
+
+        let first_responder = matches!(
+            self.kind,
+            InhabitantType::Engineer | InhabitantType::Soldier
+        );
+        let mut candidates: Vec<GridPosition> = jobs
+            .unassigned_fires()
+            .filter(|fire| first_responder || fire.waiting >= FIRE_RESPONSE_DELAY)
+            .map(|fire| fire.pos)
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+
+        // Nothing to fight it with, so stay out of the way
+        if !self.has_item(get_tool_types()) && station.count_items(get_tool_types()) == 0 {
+            return;
+        }
+
+        candidates.sort_by_key(|pos| pos.distance(from));
+        let pos = match candidates
+            .into_iter()
+            .find(|pos| self.work_spot(station, *pos, from).is_some())
+        {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        jobs.release(self.id);
+        jobs.get_fire_mut(pos).unwrap().assigned = Some(self.id);
+        self.behaviors.clear();
+        self.dest = None;
+        self.behaviors.push(Behavior::Extinguish(pos));
+    }
+
+    // Where to stand to work on a job: next to it, as close as we can get
+    fn work_spot(
+        &self,
+        station: &Station,
+        job_pos: GridPosition,
+        from: GridPosition,
+    ) -> Option<GridPosition> {
+        let spots: Vec<GridPosition> = station
+            .get_neighbors(job_pos)
+            .values()
+            .filter(|tile| tile.is_passable(self.kind))
+            .map(|tile| tile.pos)
+            .collect();
+        if spots.contains(&from) {
+            return Some(from);
+        }
+
+        spots
+            .into_iter()
+            .map(|spot| (station.path_to(from, spot, self.kind).len(), spot))
+            .filter(|(length, _spot)| *length > 0)
+            .min()
+            .map(|(_length, spot)| spot)
+    }
+
+    // Fetch materials for a job, carry them over, then build it
+    fn work(
+        &mut self,
+        pos: GridPosition,
+        current_pos: GridPosition,
+        dt: time::Duration,
+        station: &mut Station,
+        jobs: &mut Jobs,
+    ) {
+        // Is it still ours to do, and are we in any state to do it?
+        let still_needed = match jobs.get(pos) {
+            Some(job) if job.assigned == Some(self.id) => {
+                job.materials_needed - job.materials_delivered
+            }
+            _ => {
+                self.behaviors.pop();
+                return;
+            }
+        };
+        if self.kind == InhabitantType::Ghost
+            || self.wants_food() >= 0.5
+            || self.wants_drink() >= 0.5
+        {
+            jobs.release(self.id);
+            self.behaviors.pop();
+            return;
+        }
+
+        if still_needed > 0 && !self.has_item(get_material_types()) {
+            // Pick up as much as the job needs if it's here, or go and find some
+            let mut carried = false;
+            for _ in 0..still_needed {
+                match station.take_item(current_pos, get_material_types()) {
+                    Some(material) => self.items.push(material),
+                    None => break,
+                }
+                carried = true;
+            }
+            if carried {
+                return;
+            }
+
+            let path = station.path_to_item(current_pos, get_material_types(), self.kind);
+            match path.last() {
+                Some(found) => self.behaviors.push(Behavior::GoTo(*found)),
+                None => {
+                    // Nothing to build with, so leave it for now
+                    jobs.release(self.id);
+                    self.behaviors.pop();
+                }
+            }
+            return;
+        }
+
+        match self.work_spot(station, pos, current_pos) {
+            Some(spot) if spot == current_pos => {
+                let job = jobs.get_mut(pos).unwrap();
+                if still_needed > 0 {
+                    // Hand over everything we brought
+                    while job.needs_materials() && self.take_item(get_material_types()).is_some() {
+                        job.materials_delivered += 1;
+                    }
+                } else {
+                    job.work(dt);
+                }
+            }
+            Some(spot) => self.behaviors.push(Behavior::GoTo(spot)),
+            None => {
+                jobs.release(self.id);
+                self.behaviors.pop();
+            }
+        }
+    }
+
+    // Fetch an extinguisher, get next to the fire, and spray it until it's out
+    fn extinguish(
+        &mut self,
+        pos: GridPosition,
+        current_pos: GridPosition,
+        dt: time::Duration,
+        station: &mut Station,
+        jobs: &mut Jobs,
+        events: &mut EventBus,
+    ) {
+        // Still burning, and still ours?
+        match jobs.get_fire(pos) {
+            Some(fire) if fire.assigned == Some(self.id) => (),
+            _ => {
+                self.behaviors.pop();
+                return;
+            }
+        }
+        if self.kind == InhabitantType::Ghost {
+            jobs.release(self.id);
+            self.behaviors.pop();
+            return;
+        }
+
+        if !self.has_item(get_tool_types()) {
+            // Pick one up if it's here, or go and find one
+            if let Some(extinguisher) = station.take_item(current_pos, get_tool_types()) {
+                self.items.push(extinguisher);
+                return;
+            }
+
+            let path = station.path_to_item(current_pos, get_tool_types(), self.kind);
+            match path.last() {
+                Some(found) => self.behaviors.push(Behavior::GoTo(*found)),
+                None => {
+                    jobs.release(self.id);
+                    self.behaviors.pop();
+                }
+            }
+            return;
+        }
+
+        match self.work_spot(station, pos, current_pos) {
+            Some(spot) if spot == current_pos => {
+                station.extinguish(pos, EXTINGUISH_RATE * dt.as_secs_f32(), events);
+            }
+            Some(spot) => self.behaviors.push(Behavior::GoTo(spot)),
+            None => {
+                jobs.release(self.id);
+                self.behaviors.pop();
+            }
+        }
     }
 
     pub fn draw(&self, ctx: &mut Context, camera: &Camera) -> GameResult<()> {
+        // Only the people on the deck we're looking at
+        if self.deck != camera.deck {
+            return Ok(());
+        }
+
         let color = match self.kind {
             InhabitantType::Ghost => Color::new(0.8, 0.8, 0.8, 0.8),
             _ => Color::WHITE,
@@ -289,62 +652,110 @@ impl Inhabitant {
         Ok(())
     }
 
-    pub fn set_destination(&mut self, station: &Station, dest: Point2) {
-        if dest != self.pos {
-            println!("{} Pathing from {} to {}", self, self.pos, dest);
-            // TODO: All this position unit translation is annoying. Cleanup?
-            let path = station.path_to(
-                station.get_tile_from_world(self.pos).unwrap().pos,
-                station.get_tile_from_world(dest).unwrap().pos,
-            );
+    // Set off for a tile, which may be on another deck
+    pub fn set_destination(&mut self, station: &Station, to: GridPosition, events: &mut EventBus) {
+        let from = station
+            .get_tile_from_world(self.pos, self.deck)
+            .unwrap()
+            .pos;
+        if to != from {
+            events.publish(SimEvent::PathStarted {
+                inhabitant: self.id,
+                from,
+                to,
+            });
+            let path = station.path_to(from, to, self.kind);
 
             if !path.is_empty() {
+                self.move_from = self.pos;
                 self.move_elapsed = 0.0;
                 self.path = path;
                 self.current_waypoint = 0;
-                self.dest = Some(dest);
+                self.dest = Some(station.get_tile(to).unwrap().to_world_position(station));
             } else {
-                println!("{} No path", self);
+                events.publish(SimEvent::PathFailed {
+                    inhabitant: self.id,
+                    from,
+                    to,
+                });
             }
         }
     }
 
-    fn keep_moving(&mut self, dt: time::Duration, station: &Station) {
-        if self.dest == None {
+    fn keep_moving(&mut self, dt: time::Duration, station: &Station, events: &mut EventBus) {
+        if self.dest.is_none() {
             return;
         }
+        self.move_elapsed += dt.as_secs_f64();
+
+        // Keep going until we get there, carrying any time left over from one waypoint to the next
+        // so that big steps (like running the game faster) cover the same ground as small ones
+        loop {
+            // If the way's been built over or taken away since we set off, work out a new one
+            let next_waypoint = match station.get_tile(self.path[self.current_waypoint]) {
+                Some(tile) if self.can_move_to(Some(tile)) => tile.to_world_position(station),
+                _ => {
+                    self.stop();
+                    return;
+                }
+            };
+
+            if self.move_elapsed < SECONDS_PER_TILE {
+                // The ease functions want mint types
+                let source: mint::Point2<f32> = self.move_from.into();
+                let next: mint::Point2<f32> = next_waypoint.into();
+
+                // Ease in from where we started towards the waypoint
+                self.pos = ease(
+                    EaseInOut,
+                    source,
+                    next,
+                    self.move_elapsed / SECONDS_PER_TILE,
+                )
+                .into();
+                return;
+            }
 
-        // Keep going until we get there
-        let next_waypoint = station
-            .get_tile(self.path[self.current_waypoint])
-            .unwrap()
-            .to_world_position(station);
-        if self.pos == next_waypoint {
+            // Made it to the waypoint, which might be up or down a deck
+            self.pos = next_waypoint;
+            self.deck = self.path[self.current_waypoint].z;
+            self.move_from = next_waypoint;
+            self.move_elapsed -= SECONDS_PER_TILE;
             self.current_waypoint += 1;
-            self.move_elapsed = 0.0;
 
             // Moving takes work!
-            self.add_hunger(1);
-            self.add_thirst(1);
+            self.add_hunger(1, events);
+            self.add_thirst(1, events);
+
+            // We there?
+            if self.current_waypoint == self.path.len() {
+                events.publish(SimEvent::Arrived {
+                    inhabitant: self.id,
+                    pos: self.path[self.path.len() - 1],
+                });
+                self.dest = None;
+                self.move_elapsed = 0.0;
+                self.behaviors.pop(); // TODO: Is it safe to assume that arriving somewhere means the current behavior is "done"?
+                return;
+            }
         }
-        self.move_elapsed += timer::duration_to_f64(dt);
-
-        // The ease functions want mint types
-        let source: mint::Point2<f32> = self.pos.into();
-        let next: mint::Point2<f32> = next_waypoint.into();
+    }
 
-        // Ease in over 2 seconds per square
-        self.pos = ease(EaseInOut, source, next, self.move_elapsed / 2.0).into();
+    // Is this somewhere we've still to walk through on the way to where we're going?
+    pub fn is_headed_through(&self, pos: GridPosition) -> bool {
+        self.dest.is_some() && self.path[self.current_waypoint..].contains(&pos)
+    }
 
-        // We there?
-        if self.pos == self.dest.unwrap() {
-            println!("{} Arrived at {}", self, self.pos);
-            self.dest = None;
-            self.behaviors.pop(); // TODO: Is it safe to assume that arriving somewhere means the current behavior is "done"?
-        }
+    // Forget where we were going, so the way there gets worked out again from wherever we've got to
+    pub fn stop(&mut self) {
+        self.dest = None;
+        self.path.clear();
+        self.current_waypoint = 0;
+        self.move_from = self.pos;
+        self.move_elapsed = 0.0;
     }
 
-    pub fn add_hunger(&mut self, value: u8) {
+    pub fn add_hunger(&mut self, value: u8, events: &mut EventBus) {
         if self.kind == InhabitantType::Ghost {
             return;
         }
@@ -352,12 +763,15 @@ impl Inhabitant {
         self.hunger += value;
         if self.hunger >= 100 {
             self.hunger = 100;
-            self.take_damage(1);
-            println!("{} Starving! Taking damage.", self);
+            events.publish(SimEvent::NeedCritical {
+                inhabitant: self.id,
+                need: Need::Food,
+            });
+            self.take_damage(1, DamageCause::Starvation, events);
         }
     }
 
-    pub fn add_thirst(&mut self, value: u8) {
+    pub fn add_thirst(&mut self, value: u8, events: &mut EventBus) {
         if self.kind == InhabitantType::Ghost {
             return;
         }
@@ -365,8 +779,109 @@ impl Inhabitant {
         self.thirst += value;
         if self.thirst >= 100 {
             self.thirst = 100;
-            self.take_damage(1);
-            println!("{} Parched! Taking damage.", self);
+            events.publish(SimEvent::NeedCritical {
+                inhabitant: self.id,
+                need: Need::Drink,
+            });
+            self.take_damage(1, DamageCause::Dehydration, events);
+        }
+    }
+
+    // Swap some oxygen for CO2 in the air around us, and suffocate if there wasn't enough of it
+    fn breathe(
+        &mut self,
+        dt: time::Duration,
+        station: &mut Station,
+        pos: GridPosition,
+        events: &mut EventBus,
+    ) {
+        if self.kind == InhabitantType::Ghost {
+            return;
+        }
+
+        let o2_pressure = match station.get_gas_mut(pos) {
+            Some(gas) => {
+                let breath = (BREATH_RATE * dt.as_secs_f32()).min(gas.o2);
+                gas.o2 -= breath;
+                gas.co2 += breath;
+                gas.o2_pressure()
+            }
+            None => 0.0,
+        };
+        if o2_pressure >= MIN_O2_PRESSURE {
+            self.out_of_breath = 0.0;
+            return;
+        }
+
+        self.out_of_breath += dt.as_secs_f64();
+        while self.out_of_breath >= 1.0 && self.kind != InhabitantType::Ghost {
+            self.out_of_breath -= 1.0;
+            events.publish(SimEvent::NeedCritical {
+                inhabitant: self.id,
+                need: Need::Air,
+            });
+            self.take_damage(1, DamageCause::Suffocation, events);
+        }
+    }
+
+    // Get hurt if it's too hot or too cold where we're standing
+    fn feel_temperature(
+        &mut self,
+        dt: time::Duration,
+        station: &Station,
+        pos: GridPosition,
+        events: &mut EventBus,
+    ) {
+        if self.kind == InhabitantType::Ghost {
+            return;
+        }
+
+        let temperature = station.get_temperature(pos).unwrap_or(SPACE_TEMPERATURE);
+        if self.comfort.contains(&temperature) {
+            self.exposed = 0.0;
+            return;
+        }
+
+        let (need, cause) = if temperature < *self.comfort.start() {
+            (Need::Warmth, DamageCause::Hypothermia)
+        } else {
+            (Need::Cooling, DamageCause::Heatstroke)
+        };
+        self.exposed += dt.as_secs_f64();
+        while self.exposed >= 1.0 && self.kind != InhabitantType::Ghost {
+            self.exposed -= 1.0;
+            events.publish(SimEvent::NeedCritical {
+                inhabitant: self.id,
+                need,
+            });
+            self.take_damage(1, cause, events);
+        }
+    }
+
+    // Get burnt if we're standing in a fire
+    fn feel_fire(
+        &mut self,
+        dt: time::Duration,
+        station: &Station,
+        pos: GridPosition,
+        events: &mut EventBus,
+    ) {
+        if self.kind == InhabitantType::Ghost {
+            return;
+        }
+
+        let intensity = match station.get_fire(pos) {
+            Some(fire) => fire.intensity,
+            None => {
+                self.burning = 0.0;
+                return;
+            }
+        };
+        self.burning += dt.as_secs_f64();
+        while self.burning >= 1.0 && self.kind != InhabitantType::Ghost {
+            self.burning -= 1.0;
+            let damage = (BURN_DAMAGE * intensity).ceil() as u8;
+            self.take_damage(damage, DamageCause::Burns, events);
         }
     }
 
@@ -380,14 +895,17 @@ impl Inhabitant {
         self.thirst = self.thirst.saturating_sub(item.get_hydration());
     }
 
-    pub fn take_damage(&mut self, amount: u8) {
+    pub fn take_damage(&mut self, amount: u8, cause: DamageCause, events: &mut EventBus) {
         if self.kind == InhabitantType::Ghost {
             return;
         }
 
         self.health = self.health.saturating_sub(amount);
         if self.health == 0 {
-            println!("{} I die. I am dead.", self);
+            events.publish(SimEvent::Died {
+                inhabitant: self.id,
+                cause,
+            });
             self.die();
         }
     }
@@ -436,6 +954,15 @@ impl Inhabitant {
         false
     }
 
+    // Take the first item of one of these types out of our inventory, if we have one
+    fn take_item(&mut self, item_types: Vec<ItemType>) -> Option<Item> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item_types.contains(&item.get_type()))?;
+        Some(self.items.remove(index))
+    }
+
     // Given an item uuid, removes it from our inventory
     pub fn remove_item(&mut self, id: uuid::Uuid) {
         self.items.retain(|item| item.get_id() != id)
@@ -444,17 +971,39 @@ impl Inhabitant {
 
 #[cfg(test)]
 mod tests {
-    use super::{Inhabitant, InhabitantType, Point2};
+    use super::{Behavior, Inhabitant, InhabitantType, Point2};
+    use crate::events::*;
+    use crate::item::{get_food_types, get_tool_types, ContainerType, FoodType, Item, ItemType};
+    use crate::jobs::Jobs;
+    use crate::station::atmosphere::Gas;
+    use crate::station::fire::{FireCause, FIRE_RESPONSE_DELAY};
+    use crate::station::generator::CellularAutomaton;
     use crate::station::gridposition::*;
+    use crate::station::station::Station;
     use crate::station::tile::*;
+    use oorandom::Rand32;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
 
     #[test]
     fn inhabitant_can_move_to() {
-        let inhabitant = Inhabitant::new(Point2::new(1.0, 1.0), InhabitantType::Engineer);
+        let inhabitant = Inhabitant::new(
+            Point2::new(1.0, 1.0),
+            InhabitantType::Engineer,
+            &mut Rand32::new(1),
+        );
 
         let floor_tile = Tile::new(GridPosition::new(1, 1), TileType::Floor);
         let wall_tile = Tile::new(GridPosition::new(1, 2), TileType::Wall(WallDirection::Full));
-        let door_tile = Tile::new(GridPosition::new(1, 3), TileType::Door(WallDirection::Full));
+        let door_tile = Tile::new(
+            GridPosition::new(1, 3),
+            TileType::Door(Door::new(WallDirection::Full)),
+        );
+        let mut locked_door = Door::new(WallDirection::Full);
+        locked_door.state = DoorState::Locked;
+        let locked_door_tile = Tile::new(GridPosition::new(1, 4), TileType::Door(locked_door));
 
         assert!(
             inhabitant.can_move_to(Some(&floor_tile)),
@@ -468,12 +1017,20 @@ mod tests {
             !inhabitant.can_move_to(Some(&wall_tile)),
             "Inhabitants cannot move to walls"
         );
+        assert!(
+            !inhabitant.can_move_to(Some(&locked_door_tile)),
+            "Inhabitants cannot move to locked doors"
+        );
         assert!(
             !inhabitant.can_move_to(None),
             "Inhabitants cannot move to empty tiles"
         );
 
-        let ghost = Inhabitant::new(Point2::new(1.0, 2.0), InhabitantType::Ghost);
+        let ghost = Inhabitant::new(
+            Point2::new(1.0, 2.0),
+            InhabitantType::Ghost,
+            &mut Rand32::new(1),
+        );
         assert!(
             ghost.can_move_to(Some(&floor_tile)),
             "Ghosts can move to floors"
@@ -491,7 +1048,11 @@ mod tests {
 
     #[test]
     fn inhabitant_wants_food() {
-        let mut inhabitant = Inhabitant::new(Point2::new(1.0, 1.0), InhabitantType::Engineer);
+        let mut inhabitant = Inhabitant::new(
+            Point2::new(1.0, 1.0),
+            InhabitantType::Engineer,
+            &mut Rand32::new(1),
+        );
         assert_eq!(
             0.0,
             inhabitant.wants_food(),
@@ -510,7 +1071,11 @@ mod tests {
 
     #[test]
     fn inhabitant_wants_drink() {
-        let mut inhabitant = Inhabitant::new(Point2::new(1.0, 1.0), InhabitantType::Engineer);
+        let mut inhabitant = Inhabitant::new(
+            Point2::new(1.0, 1.0),
+            InhabitantType::Engineer,
+            &mut Rand32::new(1),
+        );
         assert_eq!(
             0.0,
             inhabitant.wants_drink(),
@@ -526,4 +1091,325 @@ mod tests {
         inhabitant.kind = InhabitantType::Ghost;
         assert_eq!(0.0, inhabitant.wants_drink(), "Ghosts aren't thirsty");
     }
+
+    #[test]
+    fn eating_uses_food_up() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut jobs = Jobs::new();
+        let mut station = Station::from_ascii(Point2::zero(), "###\n#.#\n###", &mut rng).unwrap();
+        let pos = GridPosition::new(1, 1);
+        let bar = ItemType::Food(FoodType::EnergyBar);
+        station.add_item(pos, Item::new(pos, bar, &mut rng));
+        let tile = station.get_tile(pos).unwrap().to_world_position(&station);
+        let mut inhabitant = Inhabitant::new(tile, InhabitantType::Pilot, &mut rng);
+        let step = Duration::from_secs(1) / 60;
+
+        // The packed lunch goes first
+        inhabitant.hunger = 60;
+        inhabitant.behaviors.push(Behavior::Eat);
+        inhabitant.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert!(!inhabitant.has_item(get_food_types()), "Lunch is gone");
+        assert!(inhabitant.hunger < 60);
+        assert_eq!(1, station.count_items(get_food_types()));
+
+        // Then whatever's lying around
+        inhabitant.hunger = 60;
+        inhabitant.behaviors.push(Behavior::Eat);
+        inhabitant.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert!(inhabitant.hunger < 60);
+        assert_eq!(0, station.count_items(get_food_types()), "So is the bar");
+    }
+
+    #[test]
+    fn inhabitant_has_item() {
+        let mut rng = Rand32::new(1);
+        let mut inhabitant = Inhabitant::new(Point2::new(1.0, 1.0), InhabitantType::Cook, &mut rng);
+        assert!(inhabitant.has_item(get_food_types()), "Packed lunch");
+        inhabitant.items.clear();
+        assert!(!inhabitant.has_item(get_food_types()));
+
+        // Fridges come stocked, and what's in them counts as on us
+        let pos = GridPosition::new(1, 1);
+        let fridge = Item::new(pos, ItemType::Container(ContainerType::Fridge), &mut rng);
+        inhabitant.items.push(fridge);
+        assert!(inhabitant.has_item(get_food_types()), "Food in the fridge");
+        assert!(!inhabitant.has_item(get_tool_types()));
+    }
+
+    #[test]
+    fn inhabitant_starves() {
+        let mut events = EventBus::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_by_subscriber = seen.clone();
+        events.subscribe(move |event| seen_by_subscriber.borrow_mut().push(event.clone()));
+
+        let mut inhabitant = Inhabitant::new(
+            Point2::new(1.0, 1.0),
+            InhabitantType::Engineer,
+            &mut Rand32::new(1),
+        );
+        inhabitant.hunger = 99;
+        inhabitant.health = 1;
+        inhabitant.add_hunger(1, &mut events);
+        events.dispatch();
+
+        let id = inhabitant.get_id();
+        assert_eq!(
+            vec![
+                SimEvent::NeedCritical {
+                    inhabitant: id,
+                    need: Need::Food
+                },
+                SimEvent::Died {
+                    inhabitant: id,
+                    cause: DamageCause::Starvation
+                },
+            ],
+            *seen.borrow()
+        );
+        assert_eq!(
+            InhabitantType::Ghost,
+            inhabitant.kind,
+            "Dead inhabitants are ghosts"
+        );
+    }
+
+    #[test]
+    fn way_taken_away() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut jobs = Jobs::new();
+        let mut station =
+            Station::from_ascii(Point2::zero(), "#######\n#.....#\n#######", &mut rng).unwrap();
+        let start = station
+            .get_tile(GridPosition::new(1, 1))
+            .unwrap()
+            .to_world_position(&station);
+        let mut inhabitant = Inhabitant::new(start, InhabitantType::Pilot, &mut rng);
+        inhabitant
+            .behaviors
+            .push(Behavior::GoTo(GridPosition::new(5, 1)));
+        let step = Duration::from_secs(1) / 60;
+        inhabitant.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert!(inhabitant.dest.is_some(), "On the way");
+
+        // A hole in the floor ahead means finding another way, and there isn't one
+        station.remove_tile(GridPosition::new(3, 1));
+        for _ in 0..60 * 5 {
+            inhabitant.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+            let here = station.grid_position_from_world(inhabitant.pos, 0);
+            assert!(here.x < 3, "Stopped short of the hole");
+        }
+
+        // And with the floor gone from under us, we stay put
+        let here = station.grid_position_from_world(inhabitant.pos, 0);
+        station.remove_tile(here);
+        inhabitant.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert_eq!(None, inhabitant.dest);
+    }
+
+    #[test]
+    fn movement_does_not_depend_on_step_size() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let station = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut rng,
+            &mut events,
+        );
+
+        // Find somewhere a few tiles away to walk to
+        let (from, to) = loop {
+            let from = station.get_random_tile(TileType::Floor, &mut rng).unwrap();
+            let to = station.get_random_tile(TileType::Floor, &mut rng).unwrap();
+            if station
+                .path_to(from.pos, to.pos, InhabitantType::Engineer)
+                .len()
+                >= 3
+            {
+                break (from.to_world_position(&station), to);
+            }
+        };
+
+        let mut slow = Inhabitant::new(from, InhabitantType::Engineer, &mut rng);
+        slow.set_destination(&station, to.pos, &mut events);
+        let to = to.to_world_position(&station);
+        assert_eq!(Some(to), slow.dest, "Has somewhere to go");
+        let mut fast = slow.clone();
+
+        // One walks in small steps, the other in steps eight times as big, over the same amount of time
+        let step = Duration::from_secs(1) / 60;
+        while slow.dest.is_some() {
+            for _ in 0..8 {
+                slow.keep_moving(step, &station, &mut events);
+            }
+            fast.keep_moving(step * 8, &station, &mut events);
+
+            assert!(
+                (slow.pos - fast.pos).length() < 0.01,
+                "Both are in the same place: {} vs {}",
+                slow.pos,
+                fast.pos
+            );
+        }
+
+        assert_eq!(None, fast.dest, "Both arrive at the same time");
+        assert_eq!(to, slow.pos);
+        assert_eq!(slow.hunger, fast.hunger, "Both worked as hard");
+    }
+
+    #[test]
+    fn breathing() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut station = Station::from_ascii(Point2::zero(), "###\n#.#\n###", &mut rng).unwrap();
+        let pos = GridPosition::new(1, 1);
+        let mut inhabitant = Inhabitant::new(
+            station.get_tile(pos).unwrap().to_world_position(&station),
+            InhabitantType::Engineer,
+            &mut rng,
+        );
+
+        let step = Duration::from_secs(1);
+        inhabitant.breathe(step, &mut station, pos, &mut events);
+        let gas = station.get_gas(pos).unwrap();
+        assert!(gas.o2 < Gas::air().o2, "Breathed in oxygen");
+        assert!(gas.co2 > Gas::air().co2, "Breathed out CO2");
+        assert_eq!(100, inhabitant.health);
+
+        // Nothing to breathe in a vacuum
+        *station.get_gas_mut(pos).unwrap() = Gas::vacuum();
+        for _ in 0..10 {
+            inhabitant.breathe(step, &mut station, pos, &mut events);
+        }
+        assert_eq!(90, inhabitant.health, "Hurts once a second");
+        for _ in 0..90 {
+            inhabitant.breathe(step, &mut station, pos, &mut events);
+        }
+        assert_eq!(InhabitantType::Ghost, inhabitant.kind, "Suffocated");
+    }
+
+    #[test]
+    fn feel_temperature() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut station = Station::from_ascii(Point2::zero(), "###\n#.#\n###", &mut rng).unwrap();
+        let pos = GridPosition::new(1, 1);
+        let tile = station.get_tile(pos).unwrap().to_world_position(&station);
+        let mut engineer = Inhabitant::new(tile, InhabitantType::Engineer, &mut rng);
+        let mut soldier = Inhabitant::new(tile, InhabitantType::Soldier, &mut rng);
+
+        let step = Duration::from_secs(1);
+        for _ in 0..10 {
+            engineer.feel_temperature(step, &station, pos, &mut events);
+        }
+        assert_eq!(100, engineer.health, "Room temperature is fine");
+
+        station.set_temperature(pos, 273.15);
+        for _ in 0..10 {
+            engineer.feel_temperature(step, &station, pos, &mut events);
+            soldier.feel_temperature(step, &station, pos, &mut events);
+        }
+        assert_eq!(90, engineer.health, "Freezing hurts once a second");
+        assert_eq!(100, soldier.health, "Soldiers can take it");
+
+        station.set_temperature(pos, 373.15);
+        soldier.feel_temperature(step, &station, pos, &mut events);
+        assert_eq!(99, soldier.health, "Not boiling though");
+    }
+
+    #[test]
+    fn fight_fires() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut jobs = Jobs::new();
+        let mut station =
+            Station::from_ascii(Point2::zero(), "#######\n#EE...#\n#######", &mut rng).unwrap();
+        let fire = GridPosition::new(5, 1);
+        let start = station
+            .get_tile(GridPosition::new(3, 1))
+            .unwrap()
+            .to_world_position(&station);
+        let mut engineer = Inhabitant::new(start, InhabitantType::Engineer, &mut rng);
+        let mut scientist = Inhabitant::new(start, InhabitantType::Scientist, &mut rng);
+
+        let step = Duration::from_secs(1);
+        station.ignite(fire, FireCause::Electrical, &mut events);
+        jobs.update_fires(station.get_fires(), step);
+        scientist.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert_ne!(
+            Some(&Behavior::Extinguish(fire)),
+            scientist.behaviors.first(),
+            "Leaves it to the Engineer"
+        );
+        engineer.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert_eq!(
+            Some(&Behavior::Extinguish(fire)),
+            engineer.behaviors.first()
+        );
+        assert_eq!(Some(engineer.id), jobs.get_fire(fire).unwrap().assigned);
+
+        for _ in 0..30 {
+            engineer.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+            jobs.update_fires(station.get_fires(), step);
+        }
+        assert!(station.get_fire(fire).is_none(), "Put it out");
+        assert!(engineer.has_item(get_tool_types()), "With the extinguisher");
+
+        // Once a fire's been left long enough, anyone will go
+        station.ignite(fire, FireCause::Electrical, &mut events);
+        jobs.update_fires(station.get_fires(), step);
+        jobs.get_fire_mut(fire).unwrap().waiting = FIRE_RESPONSE_DELAY;
+        scientist.update(step, &mut station, &mut jobs, &mut rng, &mut events);
+        assert_eq!(
+            Some(&Behavior::Extinguish(fire)),
+            scientist.behaviors.first()
+        );
+    }
+
+    #[test]
+    fn feel_fire() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut station = Station::from_ascii(Point2::zero(), "###\n#.#\n###", &mut rng).unwrap();
+        let pos = GridPosition::new(1, 1);
+        let tile = station.get_tile(pos).unwrap().to_world_position(&station);
+        let mut inhabitant = Inhabitant::new(tile, InhabitantType::Soldier, &mut rng);
+
+        let step = Duration::from_secs(1);
+        inhabitant.feel_fire(step, &station, pos, &mut events);
+        assert_eq!(100, inhabitant.health);
+
+        station.ignite(pos, FireCause::Cooking, &mut events);
+        for _ in 0..10 {
+            inhabitant.feel_fire(step, &station, pos, &mut events);
+        }
+        assert!(inhabitant.health < 100, "Burnt");
+    }
+
+    #[test]
+    fn climb_ladders() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let station = Station::from_ascii(
+            Point2::zero(),
+            "####\n#.|#\n####\n---\n####\n#.|#\n####",
+            &mut rng,
+        )
+        .unwrap();
+        let start = station
+            .get_tile(GridPosition::new(1, 1))
+            .unwrap()
+            .to_world_position(&station);
+        let mut inhabitant = Inhabitant::new(start, InhabitantType::Engineer, &mut rng);
+
+        inhabitant.set_destination(&station, GridPosition::on_deck(1, 1, 1), &mut events);
+        while inhabitant.dest.is_some() {
+            inhabitant.keep_moving(Duration::from_secs(1), &station, &mut events);
+        }
+        assert_eq!(1, inhabitant.deck, "Went up a deck");
+        assert_eq!(start, inhabitant.pos, "To the same spot on it");
+    }
 }
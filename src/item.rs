@@ -1,9 +1,13 @@
 use crate::camera::Camera;
+use crate::seed::new_id;
 use crate::station::gridposition::*;
+use crate::station::heat::*;
+use crate::station::tileset::{Sprite, SpritePart};
 
 use ggez::graphics::{Color, DrawMode, DrawParam, Mesh};
 use ggez::{graphics, Context, GameError, GameResult};
 
+use oorandom::Rand32;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,35 +15,82 @@ use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use core::fmt;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
 
 // Alias some types to making reading/writing code easier and also in case math libraries change again
 type Point2 = glam::Vec2;
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, Serialize, Deserialize)]
 pub enum ItemType {
     Food(FoodType),
     Drink(DrinkType),
     Container(ContainerType),
+    Material(MaterialType),
+    Generator(PowerGeneratorType),
+    Fixture(FixtureType),
+    Tool(ToolType),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, EnumIter, Serialize, Deserialize)]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
 pub enum FoodType {
     EnergyBar,
     MealReadyToEat, // Because MRE is not a valid Enum name
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, EnumIter, Serialize, Deserialize)]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
 pub enum DrinkType {
     Water,
     Coffee,
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, EnumIter, Serialize, Deserialize)]
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
 pub enum ContainerType {
     Fridge,
     Locker,
 }
 
+// What things get built out of
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum MaterialType {
+    Steel,
+}
+
+// What puts power into the station's wiring
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum PowerGeneratorType {
+    SolarPanel,
+    Reactor,
+}
+
+// Things fixed in place that need power to work
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum FixtureType {
+    Light,
+    Heater,
+    Cooler,
+}
+
+// Things the crew carry around to do a job with
+#[derive(
+    Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Hash, EnumIter, Serialize, Deserialize,
+)]
+pub enum ToolType {
+    Extinguisher,
+}
+
 // Helper functions to return all possible subtypes of a given item type
 pub fn get_food_types() -> Vec<ItemType> {
     let mut types = vec![];
@@ -65,14 +116,40 @@ pub fn get_container_types() -> Vec<ItemType> {
     types
 }
 
+pub fn get_generator_types() -> Vec<ItemType> {
+    let mut types = vec![];
+    for kind in PowerGeneratorType::iter() {
+        types.push(ItemType::Generator(kind));
+    }
+    types
+}
+
+pub fn get_material_types() -> Vec<ItemType> {
+    let mut types = vec![];
+    for kind in MaterialType::iter() {
+        types.push(ItemType::Material(kind));
+    }
+    types
+}
+
+pub fn get_tool_types() -> Vec<ItemType> {
+    let mut types = vec![];
+    for kind in ToolType::iter() {
+        types.push(ItemType::Tool(kind));
+    }
+    types
+}
+
 // An item is the base of objects that live inside the station on tiles and inhabitants can interact
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Item {
     id: uuid::Uuid,
     kind: ItemType,
-    pub pos: super::GridPosition,
+    pub pos: GridPosition,
     items: Vec<Item>,
     capacity: usize,
+    age: Duration,    // How long it's been going off for, which only matters for food
+    temperature: f32, // In kelvin
 }
 
 impl fmt::Debug for Item {
@@ -82,7 +159,7 @@ impl fmt::Debug for Item {
 }
 
 impl Item {
-    pub fn new(pos: super::GridPosition, kind: ItemType) -> Item {
+    pub fn new(pos: GridPosition, kind: ItemType, rng: &mut Rand32) -> Item {
         // Containers have a capacity, other stuff doesn't
         let capacity = match kind {
             ItemType::Container(_) => 10,
@@ -91,69 +168,90 @@ impl Item {
 
         // Create the item itself
         let mut i = Item {
-            id: Uuid::new_v4(),
+            id: new_id(rng),
             kind,
             pos,
             items: Vec::with_capacity(capacity),
             capacity,
+            age: Duration::from_secs(0),
+            temperature: ROOM_TEMPERATURE,
         };
 
         // Some item types modify after creation
-        match kind {
-            // Fill the fridge with energy bars and water
-            ItemType::Container(ContainerType::Fridge) => {
-                i.add_item(Item::new(
-                    GridPosition::new(0, 0),
-                    ItemType::Food(FoodType::EnergyBar),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(1, 0),
-                    ItemType::Drink(DrinkType::Water),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(2, 0),
-                    ItemType::Food(FoodType::EnergyBar),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(3, 0),
-                    ItemType::Drink(DrinkType::Water),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(4, 0),
-                    ItemType::Food(FoodType::EnergyBar),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(5, 0),
-                    ItemType::Drink(DrinkType::Water),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(6, 0),
-                    ItemType::Food(FoodType::EnergyBar),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(7, 0),
-                    ItemType::Drink(DrinkType::Water),
-                ))
-                .unwrap();
-                i.add_item(Item::new(
-                    GridPosition::new(8, 0),
-                    ItemType::Food(FoodType::EnergyBar),
-                ))
-                .unwrap();
+        // Fill the fridge with energy bars and water
+        if let ItemType::Container(ContainerType::Fridge) = kind {
+            i.add_item(Item::new(
+                GridPosition::new(0, 0),
+                ItemType::Food(FoodType::EnergyBar),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(1, 0),
+                ItemType::Drink(DrinkType::Water),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(2, 0),
+                ItemType::Food(FoodType::EnergyBar),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(3, 0),
+                ItemType::Drink(DrinkType::Water),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(4, 0),
+                ItemType::Food(FoodType::EnergyBar),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(5, 0),
+                ItemType::Drink(DrinkType::Water),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(6, 0),
+                ItemType::Food(FoodType::EnergyBar),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(7, 0),
+                ItemType::Drink(DrinkType::Water),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(8, 0),
+                ItemType::Food(FoodType::EnergyBar),
+                rng,
+            ))
+            .unwrap();
+            i.add_item(Item::new(
+                GridPosition::new(9, 0),
+                ItemType::Drink(DrinkType::Water),
+                rng,
+            ))
+            .unwrap();
+        }
+
+        // Fill lockers up with building materials
+        if let ItemType::Container(ContainerType::Locker) = kind {
+            for x in 0..capacity {
                 i.add_item(Item::new(
-                    GridPosition::new(9, 0),
-                    ItemType::Drink(DrinkType::Water),
+                    GridPosition::new(x as i32, 0),
+                    ItemType::Material(MaterialType::Steel),
+                    rng,
                 ))
                 .unwrap();
             }
-            _ => (),
         }
 
         // Return it
@@ -166,7 +264,24 @@ impl Item {
         self.id
     }
 
+    // Feed everything about the item, and whatever's in it, into a checksum
+    pub fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.kind.hash(state);
+        self.pos.hash(state);
+        self.age.hash(state);
+        self.temperature.to_bits().hash(state);
+        self.items.len().hash(state);
+        for item in self.items.iter() {
+            item.hash_state(state);
+        }
+    }
+
     pub fn get_name(&self) -> String {
+        if self.is_spoiled() {
+            return format!("{:?}, gone off. Restores nothing", self.kind);
+        }
+
         match self.kind {
             ItemType::Food(food_type) => match food_type {
                 FoodType::EnergyBar => format!(
@@ -196,6 +311,64 @@ impl Item {
                     format!("Storage container. Has {} items.", self.items.len())
                 }
             },
+            ItemType::Material(material_type) => match material_type {
+                MaterialType::Steel => "A sheet of steel, for building with".to_string(),
+            },
+            ItemType::Generator(generator_type) => match generator_type {
+                PowerGeneratorType::SolarPanel => {
+                    format!("Turns starlight into {} W", self.get_power_output())
+                }
+                PowerGeneratorType::Reactor => format!(
+                    "A small fusion reactor. Puts out {} W",
+                    self.get_power_output()
+                ),
+            },
+            ItemType::Fixture(fixture_type) => match fixture_type {
+                FixtureType::Light => format!("A ceiling light. Draws {} W", self.get_power_draw()),
+                FixtureType::Heater => format!(
+                    "Keeps the air around it above {:.0} K. Draws {} W",
+                    HEATER_SETPOINT,
+                    self.get_power_draw()
+                ),
+                FixtureType::Cooler => format!(
+                    "Keeps the air around it below {:.0} K. Draws {} W",
+                    COOLER_SETPOINT,
+                    self.get_power_draw()
+                ),
+            },
+            ItemType::Tool(tool_type) => match tool_type {
+                ToolType::Extinguisher => "A fire extinguisher, for putting out fires".to_string(),
+            },
+        }
+    }
+
+    // How the item looks drawn from the tileset, if there's a sprite for it. Anything else is drawn with `draw`
+    pub fn sprite(&self) -> Option<SpritePart> {
+        let third = 1.0 / 3.0;
+        match self.kind {
+            ItemType::Container(ContainerType::Fridge) => Some(
+                SpritePart::new(Sprite::Panel)
+                    .covering(third, third, third, third)
+                    .tinted(Color::new(0.8, 0.9, 1.0, 1.0)),
+            ),
+            ItemType::Container(ContainerType::Locker) => {
+                Some(SpritePart::new(Sprite::Panel).covering(third, third, third, third))
+            }
+            ItemType::Material(_) => {
+                Some(SpritePart::new(Sprite::Panel).covering(0.4, 0.4, 0.2, 0.2))
+            }
+            ItemType::Generator(generator) => {
+                let sprite = match generator {
+                    PowerGeneratorType::SolarPanel => Sprite::Grating,
+                    PowerGeneratorType::Reactor => Sprite::Panel,
+                };
+                Some(
+                    SpritePart::new(sprite)
+                        .covering(0.25, 0.25, 0.5, 0.5)
+                        .tinted(Color::new(0.2, 0.4, 0.9, 1.0)),
+                )
+            }
+            _ => None,
         }
     }
 
@@ -227,6 +400,27 @@ impl Item {
                 graphics::Rect::new(pos.x + 10.0, pos.y + 10.0, 10.0, 10.0),
                 Color::new(0.5, 0.5, 0.5, 1.0),
             )?,
+            ItemType::Material(_) => Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                graphics::Rect::new(pos.x + 12.0, pos.y + 12.0, 6.0, 6.0),
+                Color::new(0.7, 0.7, 0.8, 1.0),
+            )?,
+            ItemType::Generator(_) => Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                graphics::Rect::new(pos.x + 8.0, pos.y + 8.0, 14.0, 14.0),
+                Color::new(0.2, 0.4, 0.9, 1.0),
+            )?,
+            ItemType::Fixture(_) => {
+                Mesh::new_circle(ctx, DrawMode::fill(), pos, 4.0, 0.1, Color::WHITE)?
+            }
+            ItemType::Tool(_) => Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                graphics::Rect::new(pos.x + 12.0, pos.y + 8.0, 6.0, 14.0),
+                Color::new(0.9, 0.1, 0.1, 1.0),
+            )?,
         };
         graphics::draw(
             ctx,
@@ -238,13 +432,50 @@ impl Item {
         )
     }
 
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        // Update all the contents
+    // Items warm up or cool down to the `ambient` temperature around them, and food goes off while it's warm
+    pub fn update(&mut self, dt: Duration, powered: bool, ambient: f32) {
+        self.temperature = settle(self.temperature, ambient, ITEM_HEAT_RATE, dt.as_secs_f32());
+        if let ItemType::Food(_) = self.kind {
+            if self.temperature > SPOIL_TEMPERATURE {
+                self.age += dt;
+            }
+        }
+
+        // A fridge with power keeps its contents cold, otherwise they're at whatever the container is
+        let inside = if powered && self.kind == ItemType::Container(ContainerType::Fridge) {
+            FRIDGE_TEMPERATURE
+        } else {
+            self.temperature
+        };
+
+        // Update all the contents, which aren't plugged into anything themselves
         for item in self.items.iter_mut() {
-            item.update(ctx)?;
+            item.update(dt, false, inside);
         }
+    }
 
-        Ok(())
+    pub fn get_temperature(&self) -> f32 {
+        self.temperature
+    }
+
+    // How long food lasts once it's warm
+    fn shelf_life(&self) -> Option<Duration> {
+        match self.kind {
+            ItemType::Food(food_type) => match food_type {
+                FoodType::EnergyBar => Some(Duration::from_secs(8 * 60 * 60)),
+                FoodType::MealReadyToEat => Some(Duration::from_secs(2 * 60 * 60)),
+            },
+            _ => None,
+        }
+    }
+
+    // Whether it burns, giving a fire on its tile more to go on and more chance of spreading there
+    pub fn is_flammable(&self) -> bool {
+        matches!(self.kind, ItemType::Food(_) | ItemType::Container(_))
+    }
+
+    pub fn is_spoiled(&self) -> bool {
+        self.shelf_life().is_some_and(|life| self.age >= life)
     }
 
     pub fn get_type(&self) -> ItemType {
@@ -271,7 +502,20 @@ impl Item {
         self.items.retain(|item| item.id != id)
     }
 
+    // Take the first item of one of these types out of the container, if there is one
+    pub fn take_item(&mut self, item_types: &[ItemType]) -> Option<Item> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item_types.contains(&item.get_type()))?;
+        Some(self.items.remove(index))
+    }
+
     pub fn get_energy(&self) -> u8 {
+        if self.is_spoiled() {
+            return 0;
+        }
+
         match self.kind {
             ItemType::Food(food_type) => match food_type {
                 FoodType::EnergyBar => 10,
@@ -285,6 +529,28 @@ impl Item {
         }
     }
 
+    // Watts put into the station's wiring
+    pub fn get_power_output(&self) -> u32 {
+        match self.kind {
+            ItemType::Generator(generator_type) => match generator_type {
+                PowerGeneratorType::SolarPanel => 500,
+                PowerGeneratorType::Reactor => 5000,
+            },
+            _ => 0,
+        }
+    }
+
+    // Watts taken out of the station's wiring
+    pub fn get_power_draw(&self) -> u32 {
+        match self.kind {
+            ItemType::Container(ContainerType::Fridge) => 200,
+            ItemType::Fixture(FixtureType::Light) => 20,
+            ItemType::Fixture(FixtureType::Heater) => 1000,
+            ItemType::Fixture(FixtureType::Cooler) => 1000,
+            _ => 0,
+        }
+    }
+
     pub fn get_hydration(&self) -> u8 {
         match self.kind {
             ItemType::Drink(drink_type) => match drink_type {
@@ -300,54 +566,111 @@ impl Item {
 mod tests {
     use super::{ContainerType, FoodType, Item, ItemType};
     use crate::station::gridposition::*;
+    use crate::station::heat::{FRIDGE_TEMPERATURE, ROOM_TEMPERATURE};
+    use oorandom::Rand32;
+
+    use std::time::Duration;
 
     #[test]
     fn new_fridge_contains_items() {
+        let mut rng = Rand32::new(1);
         let fridge = Item::new(
             GridPosition::new(1, 1),
             ItemType::Container(ContainerType::Fridge),
+            &mut rng,
         );
         assert_eq!(fridge.capacity, fridge.items.len()); // Fridges come full
     }
 
     #[test]
     fn fridge_add_item() {
+        let mut rng = Rand32::new(1);
         let mut fridge = Item::new(
             GridPosition::new(1, 1),
             ItemType::Container(ContainerType::Fridge),
+            &mut rng,
         );
         fridge.items.clear(); // Empty the fridge
         assert!(fridge
-            .add_item(Item::new(fridge.pos, ItemType::Food(FoodType::EnergyBar)))
+            .add_item(Item::new(
+                fridge.pos,
+                ItemType::Food(FoodType::EnergyBar),
+                &mut rng
+            ))
             .is_ok());
         assert_eq!(1, fridge.items.len());
     }
 
     #[test]
     fn fridge_max_items() {
+        let mut rng = Rand32::new(1);
         let mut fridge = Item::new(
             GridPosition::new(1, 1),
             ItemType::Container(ContainerType::Fridge),
+            &mut rng,
         );
         while fridge.items.len() < fridge.capacity {
             assert!(fridge
-                .add_item(Item::new(fridge.pos, ItemType::Food(FoodType::EnergyBar)))
+                .add_item(Item::new(
+                    fridge.pos,
+                    ItemType::Food(FoodType::EnergyBar),
+                    &mut rng
+                ))
                 .is_ok());
         }
 
-        let result = fridge.add_item(Item::new(fridge.pos, ItemType::Food(FoodType::EnergyBar)));
+        let result = fridge.add_item(Item::new(
+            fridge.pos,
+            ItemType::Food(FoodType::EnergyBar),
+            &mut rng,
+        ));
         assert!(result.is_err());
     }
 
     #[test]
     fn fridge_remove_item() {
+        let mut rng = Rand32::new(1);
         let mut fridge = Item::new(
             GridPosition::new(1, 1),
             ItemType::Container(ContainerType::Fridge),
+            &mut rng,
         );
 
         let id = fridge.get_items().last().unwrap().get_id();
         fridge.remove_item(id);
         assert_eq!(9, fridge.items.len()); // Fridges come full, so we should be down one item
     }
+
+    #[test]
+    fn food_spoils_without_power() {
+        let mut rng = Rand32::new(1);
+        let mut fridge = Item::new(
+            GridPosition::new(1, 1),
+            ItemType::Container(ContainerType::Fridge),
+            &mut rng,
+        );
+        let mut bar = Item::new(
+            GridPosition::new(1, 1),
+            ItemType::Food(FoodType::EnergyBar),
+            &mut rng,
+        );
+        let day = Duration::from_secs(24 * 60 * 60);
+
+        fridge.update(day, true, ROOM_TEMPERATURE);
+        assert!(
+            fridge.items.iter().all(|item| !item.is_spoiled()),
+            "A working fridge keeps food fresh"
+        );
+        assert!((fridge.items[0].temperature - FRIDGE_TEMPERATURE).abs() < 0.1);
+
+        bar.update(day, false, ROOM_TEMPERATURE);
+        assert!(bar.is_spoiled(), "Food left out goes off");
+        assert_eq!(0, bar.get_energy());
+
+        fridge.update(day, false, ROOM_TEMPERATURE);
+        assert!(
+            fridge.items.iter().any(|item| item.is_spoiled()),
+            "And so does food in a fridge with no power"
+        );
+    }
 }
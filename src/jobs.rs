@@ -0,0 +1,255 @@
+use crate::item::{ContainerType, PowerGeneratorType};
+use crate::station::build::BuildTool;
+use crate::station::gridposition::GridPosition;
+use crate::station::tile::LiftType;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+// A blueprint for something to be built, waiting for an Engineer to bring the materials and do the work
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Job {
+    pub pos: GridPosition,
+    pub tool: BuildTool,
+    pub materials_needed: u32,
+    pub materials_delivered: u32,
+    pub work_left: f64, // Seconds of work still to do, once all the materials are here
+    pub assigned: Option<Uuid>, // Whoever is working on it
+}
+
+impl Job {
+    pub fn new(pos: GridPosition, tool: BuildTool) -> Job {
+        Job {
+            pos,
+            tool,
+            materials_needed: material_cost(tool),
+            materials_delivered: 0,
+            work_left: work_time(tool),
+            assigned: None,
+        }
+    }
+
+    pub fn needs_materials(&self) -> bool {
+        self.materials_delivered < self.materials_needed
+    }
+
+    // Work can only start once everything's been delivered
+    pub fn work(&mut self, dt: Duration) {
+        if !self.needs_materials() {
+            self.work_left -= dt.as_secs_f64();
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        !self.needs_materials() && self.work_left <= 0.0
+    }
+}
+
+// How many sheets of material it takes to build something
+pub fn material_cost(tool: BuildTool) -> u32 {
+    match tool {
+        BuildTool::Floor => 1,
+        BuildTool::Wall => 2,
+        BuildTool::Door => 3,
+        BuildTool::Furniture(ContainerType::Fridge) => 4,
+        BuildTool::Furniture(ContainerType::Locker) => 2,
+        BuildTool::Generator(PowerGeneratorType::SolarPanel) => 4,
+        BuildTool::Generator(PowerGeneratorType::Reactor) => 8,
+        BuildTool::Conduit => 1,
+        BuildTool::Lift(LiftType::Ladder) => 3,
+        BuildTool::Lift(LiftType::Elevator) => 6,
+        BuildTool::Repair => 1,
+        BuildTool::Remove => 0,
+    }
+}
+
+// How many seconds of work it takes to build something, once the materials are there
+pub fn work_time(tool: BuildTool) -> f64 {
+    match tool {
+        BuildTool::Floor => 5.0,
+        BuildTool::Wall => 10.0,
+        BuildTool::Door => 15.0,
+        BuildTool::Furniture(_) => 10.0,
+        BuildTool::Generator(_) => 20.0,
+        BuildTool::Conduit => 5.0,
+        BuildTool::Lift(_) => 20.0,
+        BuildTool::Repair => 10.0,
+        BuildTool::Remove => 5.0,
+    }
+}
+
+// A fire that needs putting out, waiting for someone to come with an extinguisher
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FireJob {
+    pub pos: GridPosition,
+    pub waiting: f64,           // Seconds it's been burning with nobody on it
+    pub assigned: Option<Uuid>, // Whoever is putting it out
+}
+
+// All the construction waiting to be done, at most one job for each tile, and all the fires to fight
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Jobs {
+    jobs: BTreeMap<GridPosition, Job>,
+    fires: BTreeMap<GridPosition, FireJob>,
+}
+
+impl Jobs {
+    pub fn new() -> Jobs {
+        Jobs::default()
+    }
+
+    // Add a job, unless there's one on that tile already
+    pub fn add(&mut self, job: Job) -> bool {
+        if self.jobs.contains_key(&job.pos) {
+            return false;
+        }
+        self.jobs.insert(job.pos, job);
+        true
+    }
+
+    pub fn get(&self, pos: GridPosition) -> Option<&Job> {
+        self.jobs.get(&pos)
+    }
+
+    pub fn get_mut(&mut self, pos: GridPosition) -> Option<&mut Job> {
+        self.jobs.get_mut(&pos)
+    }
+
+    pub fn remove(&mut self, pos: GridPosition) -> Option<Job> {
+        self.jobs.remove(&pos)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    // Jobs nobody has taken on yet, in a reproducible order
+    pub fn unassigned(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.values().filter(|job| job.assigned.is_none())
+    }
+
+    // Let go of whatever this inhabitant was working on, so someone else can pick it up
+    pub fn release(&mut self, id: Uuid) {
+        for job in self.jobs.values_mut() {
+            if job.assigned == Some(id) {
+                job.assigned = None;
+            }
+        }
+        for fire in self.fires.values_mut() {
+            if fire.assigned == Some(id) {
+                fire.assigned = None;
+            }
+        }
+    }
+
+    // Keep a fire job for every tile that's burning, and count how long each has gone without anyone on it
+    pub fn update_fires<I>(&mut self, burning: I, dt: Duration)
+    where
+        I: IntoIterator<Item = GridPosition>,
+    {
+        let burning: BTreeSet<GridPosition> = burning.into_iter().collect();
+        self.fires.retain(|pos, _fire| burning.contains(pos));
+        for pos in burning {
+            let fire = self.fires.entry(pos).or_insert(FireJob {
+                pos,
+                waiting: 0.0,
+                assigned: None,
+            });
+            if fire.assigned.is_none() {
+                fire.waiting += dt.as_secs_f64();
+            }
+        }
+    }
+
+    pub fn get_fire(&self, pos: GridPosition) -> Option<&FireJob> {
+        self.fires.get(&pos)
+    }
+
+    pub fn get_fire_mut(&mut self, pos: GridPosition) -> Option<&mut FireJob> {
+        self.fires.get_mut(&pos)
+    }
+
+    // Fires nobody is fighting yet, in a reproducible order
+    pub fn unassigned_fires(&self) -> impl Iterator<Item = &FireJob> {
+        self.fires.values().filter(|fire| fire.assigned.is_none())
+    }
+
+    // Everything finished, ready to be built
+    pub fn done(&self) -> Vec<GridPosition> {
+        self.jobs
+            .values()
+            .filter(|job| job.is_done())
+            .map(|job| job.pos)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Job, Jobs};
+    use crate::station::build::BuildTool;
+    use crate::station::gridposition::GridPosition;
+
+    use std::time::Duration;
+
+    #[test]
+    fn job_needs_materials_before_work() {
+        let mut job = Job::new(GridPosition::new(1, 1), BuildTool::Wall);
+        assert!(job.needs_materials());
+
+        job.work(Duration::from_secs(60));
+        assert!(!job.is_done(), "No work without materials");
+
+        job.materials_delivered = job.materials_needed;
+        job.work(Duration::from_secs(5));
+        assert!(!job.is_done(), "Not done yet");
+        job.work(Duration::from_secs(5));
+        assert!(job.is_done());
+    }
+
+    #[test]
+    fn one_job_per_tile() {
+        let mut jobs = Jobs::new();
+        let pos = GridPosition::new(1, 1);
+        assert!(jobs.add(Job::new(pos, BuildTool::Wall)));
+        assert!(!jobs.add(Job::new(pos, BuildTool::Floor)));
+        assert_eq!(1, jobs.len());
+        assert_eq!(BuildTool::Wall, jobs.get(pos).unwrap().tool);
+    }
+
+    #[test]
+    fn fire_jobs_follow_fires() {
+        let mut jobs = Jobs::new();
+        let here = GridPosition::new(1, 1);
+        let there = GridPosition::new(2, 1);
+        let step = Duration::from_secs(1);
+        jobs.update_fires(vec![here, there], step);
+        assert_eq!(2, jobs.unassigned_fires().count());
+
+        jobs.get_fire_mut(here).unwrap().assigned = Some(uuid::Uuid::nil());
+        jobs.update_fires(vec![here, there], step);
+        assert_eq!(
+            1.0,
+            jobs.get_fire(here).unwrap().waiting,
+            "Stops waiting once taken"
+        );
+        assert_eq!(2.0, jobs.get_fire(there).unwrap().waiting);
+
+        jobs.release(uuid::Uuid::nil());
+        assert_eq!(2, jobs.unassigned_fires().count());
+
+        jobs.update_fires(vec![there], step);
+        assert!(jobs.get_fire(here).is_none(), "Gone once the fire's out");
+    }
+}
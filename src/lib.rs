@@ -0,0 +1,18 @@
+pub mod camera;
+pub mod events;
+pub mod inhabitant;
+pub mod item;
+pub mod jobs;
+pub mod minimap;
+pub mod music;
+pub mod preferences;
+pub mod replay;
+pub mod savegame;
+pub mod scenes;
+pub mod seed;
+pub mod simulation;
+pub mod starfield;
+pub mod station;
+pub mod stats;
+
+pub const TILE_WIDTH: f32 = 30.0;
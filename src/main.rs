@@ -1,46 +1,41 @@
 // https://github.com/ggez/ggez/blob/master/docs/FAQ.md#i-get-a-console-window-when-i-launch-my-executable-on-windows
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod camera;
-mod inhabitant;
-mod item;
-mod music;
-mod scenes;
-mod starfield;
-mod station;
-
-use crate::scenes::*;
-use crate::station::gridposition::*;
-use ggez::event::{self, EventHandler, KeyCode, KeyMods};
+use ggez::event::{self, EventHandler, KeyCode, KeyMods, MouseButton};
 use ggez::{conf, graphics, timer, Context, ContextBuilder, GameResult};
-use music::Music;
-use starfield::Starfield;
+use space_station_god_game_proto_rust::events::{self, EventBus, SimEvent};
+use space_station_god_game_proto_rust::music::Music;
+use space_station_god_game_proto_rust::scenes::{self, scene};
+use space_station_god_game_proto_rust::seed::{GameSeed, RngStream};
+use space_station_god_game_proto_rust::simulation;
+use space_station_god_game_proto_rust::starfield::Starfield;
 
 use std::{env, path};
 
-const TILE_WIDTH: f32 = 30.0;
-
 // Main game state object. Holds positions, scores, etc
 struct GameState {
     is_fullscreen: bool,
     starfield: Starfield,
     music: Music,
     scenes: Vec<Box<dyn scene::Scene>>,
+    events: EventBus,
 }
 
 impl GameState {
     // Load/create resources such as images here and otherwise initialize state
-    pub fn new(ctx: &mut Context) -> GameResult<GameState> {
+    pub fn new(ctx: &mut Context, seed: GameSeed) -> GameResult<GameState> {
         // Create game state and return it
         let mut state = GameState {
             is_fullscreen: false, // TODO: Is it possible to know this on startup from context?
-            starfield: Starfield::new(ctx),
-            music: Music::new(ctx),
+            starfield: Starfield::new(ctx, seed.rng(RngStream::Starfield)),
+            music: Music::new(ctx, seed.rng(RngStream::Music)),
             scenes: Vec::with_capacity(5),
+            events: EventBus::new(),
         };
+        state.events.subscribe(events::print);
 
         // Add the initial title scene
-        state.push_scene(Box::new(scenes::title::Title {}));
+        state.push_scene(Box::new(scenes::title::Title::new(seed)));
 
         // Return the initial game state
         Ok(state)
@@ -56,30 +51,48 @@ impl GameState {
         match self.scenes.last_mut() {
             Some(current_scene) => {
                 if current_scene.get_type() != scene.get_type() {
-                    println!(
-                        "Pushing {:?} from {:?}",
-                        scene.get_type(),
-                        current_scene.get_type()
-                    );
+                    self.events.publish(SimEvent::SceneChanged {
+                        from: Some(current_scene.get_type()),
+                        to: scene.get_type(),
+                    });
                     current_scene.to_scene(scene.get_type());
                     self.scenes.push(scene);
                 }
             }
             None => {
-                println!("Pushing {:?}", scene.get_type());
+                self.events.publish(SimEvent::SceneChanged {
+                    from: None,
+                    to: scene.get_type(),
+                });
                 self.scenes.push(scene);
             }
         }
+        self.events.dispatch();
     }
 
     // Pop the top of the scene stack
     pub fn pop_scene(&mut self) {
         if let Some(last) = self.scenes.pop() {
-            if let Some(current) = self.get_current_scene() {
-                println!("Popping {:?} to {:?}", last.get_type(), current.get_type());
+            if let Some(current) = self.scenes.last_mut() {
+                self.events.publish(SimEvent::SceneChanged {
+                    from: Some(last.get_type()),
+                    to: current.get_type(),
+                });
                 current.from_scene(last.get_type());
-            } else {
-                println!("Popping {:?}", last.get_type());
+            }
+            self.events.dispatch();
+        }
+    }
+
+    // Perform the scene transition a scene asked for
+    fn handle_action(&mut self, action: scene::SceneAction) {
+        match action {
+            scene::SceneAction::None | scene::SceneAction::Forward => (),
+            scene::SceneAction::Pop => self.pop_scene(),
+            scene::SceneAction::Push(scene) => self.push_scene(scene),
+            scene::SceneAction::PopAndPush(scene) => {
+                self.pop_scene();
+                self.push_scene(scene);
             }
         }
     }
@@ -94,9 +107,8 @@ impl EventHandler<ggez::GameError> for GameState {
         // Check music
         self.music.update(ctx)?;
 
-        // Update at 60fps
-        const DESIRED_FPS: u32 = 60;
-        while timer::check_update_time(ctx, DESIRED_FPS) {
+        // Update at the simulation's fixed tick rate
+        while timer::check_update_time(ctx, simulation::TICKS_PER_SECOND) {
             // Always update the starfield
             self.starfield.update(ctx)?;
 
@@ -161,33 +173,61 @@ impl EventHandler<ggez::GameError> for GameState {
             _ => (),
         }
 
-        // Inform current scene
-        if let Some(scene) = self.get_current_scene() {
-            match scene.key_down_event(ctx, keycode, keymods, repeat) {
-                scene::SceneAction::None => (),
-                scene::SceneAction::Pop => self.pop_scene(),
-                scene::SceneAction::Push(scene) => self.push_scene(scene),
-                scene::SceneAction::PopAndPush(scene) => {
-                    self.pop_scene();
-                    self.push_scene(scene);
-                }
+        // Inform scenes, starting with the current one, until one of them handles it
+        for i in (0..self.scenes.len()).rev() {
+            match self.scenes[i].key_down_event(ctx, keycode, keymods, repeat) {
+                scene::SceneAction::Forward => continue,
+                action => self.handle_action(action),
             }
+            break;
         }
     }
 
     // The mousewheel/trackpad was moved
     fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
-        // Inform current scene
-        if let Some(scene) = self.get_current_scene() {
-            match scene.mouse_wheel_event(ctx, x, y) {
-                scene::SceneAction::None => (),
-                scene::SceneAction::Pop => self.pop_scene(),
-                scene::SceneAction::Push(scene) => self.push_scene(scene),
-                scene::SceneAction::PopAndPush(scene) => {
-                    self.pop_scene();
-                    self.push_scene(scene);
-                }
+        // Inform scenes, starting with the current one, until one of them handles it
+        for i in (0..self.scenes.len()).rev() {
+            match self.scenes[i].mouse_wheel_event(ctx, x, y) {
+                scene::SceneAction::Forward => continue,
+                action => self.handle_action(action),
+            }
+            break;
+        }
+    }
+
+    // A mouse button was pressed
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        // Inform scenes, starting with the current one, until one of them handles it
+        for i in (0..self.scenes.len()).rev() {
+            match self.scenes[i].mouse_button_down_event(ctx, button, x, y) {
+                scene::SceneAction::Forward => continue,
+                action => self.handle_action(action),
             }
+            break;
+        }
+    }
+
+    // A mouse button was let go
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        // Inform scenes, starting with the current one, until one of them handles it
+        for i in (0..self.scenes.len()).rev() {
+            match self.scenes[i].mouse_button_up_event(ctx, button, x, y) {
+                scene::SceneAction::Forward => continue,
+                action => self.handle_action(action),
+            }
+            break;
+        }
+    }
+
+    // The mouse moved
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        // Inform scenes, starting with the current one, until one of them handles it
+        for i in (0..self.scenes.len()).rev() {
+            match self.scenes[i].mouse_motion_event(ctx, x, y, dx, dy) {
+                scene::SceneAction::Forward => continue,
+                action => self.handle_action(action),
+            }
+            break;
         }
     }
 
@@ -200,15 +240,8 @@ impl EventHandler<ggez::GameError> for GameState {
 
         // Inform current scene and handle actions
         if let Some(scene) = self.get_current_scene() {
-            match scene.resize_event(ctx, width, height) {
-                scene::SceneAction::None => (),
-                scene::SceneAction::Pop => self.pop_scene(),
-                scene::SceneAction::Push(scene) => self.push_scene(scene),
-                scene::SceneAction::PopAndPush(scene) => {
-                    self.pop_scene();
-                    self.push_scene(scene);
-                }
-            }
+            let action = scene.resize_event(ctx, width, height);
+            self.handle_action(action);
         }
     }
 }
@@ -225,6 +258,13 @@ fn main() -> GameResult {
         path::PathBuf::from("./resources")
     };
 
+    // Pick the seed for this run, either from the command line (`--seed 1234`) or at random
+    let seed = match env::args().skip_while(|arg| arg != "--seed").nth(1) {
+        Some(value) => value.parse().expect("Invalid --seed value"),
+        None => GameSeed::random(),
+    };
+    println!("Seed: {}", seed);
+
     // Make a Context. This is passed to the game loop
     let (mut ctx, event_loop) = ContextBuilder::new("space_station_god_game", "Myles Grant")
         .add_resource_path(resource_dir)
@@ -243,7 +283,7 @@ fn main() -> GameResult {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let state = GameState::new(&mut ctx)?;
+    let state = GameState::new(&mut ctx, seed)?;
 
     // Run!
     event::run(ctx, event_loop, state)
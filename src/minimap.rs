@@ -0,0 +1,265 @@
+use crate::camera::Camera;
+use crate::inhabitant::{Inhabitant, InhabitantType};
+use crate::station::gridposition::GridPosition;
+use crate::station::station::Station;
+use crate::station::tile::TileType;
+
+use ggez::graphics::{Color, DrawMode, DrawParam, FilterMode, Image, MeshBuilder, Rect};
+use ggez::{graphics, Context, GameResult};
+
+type Point2 = glam::Vec2;
+
+// The most room the minimap takes up on screen, and how far in from the corner it sits
+const MINIMAP_SIZE: f32 = 200.0;
+const MINIMAP_MARGIN: f32 = 10.0;
+
+const BACKGROUND_COLOR: Color = Color::new(0.0, 0.0, 0.0, 0.7);
+const BORDER_COLOR: Color = Color::new(0.5, 0.5, 0.5, 1.0);
+const FLOOR_COLOR: Color = Color::new(0.3, 0.3, 0.3, 1.0);
+const WALL_COLOR: Color = Color::new(0.7, 0.7, 0.7, 1.0);
+const DOOR_COLOR: Color = Color::new(0.2, 0.6, 1.0, 1.0);
+const VIEWPORT_COLOR: Color = Color::new(1.0, 1.0, 0.0, 1.0);
+const INHABITANT_RADIUS: f32 = 2.0;
+
+// A little map of the whole station in the bottom-right corner of the screen, for getting around big stations quickly
+// It's worked out from the station as it is, so it always fits however the station grows
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Minimap {
+    rect: Rect,     // Where the map is on screen
+    origin: Point2, // The point in the station at the map's top-left corner, in the coordinates tiles are drawn at
+    scale: f32,     // How many pixels of map there are to each one of station
+}
+
+impl Minimap {
+    // Fit the station into the corner of a screen this size, if there's any station to fit
+    pub fn new(station: &Station, screen_size: Point2) -> Option<Minimap> {
+        let (top_left, bottom_right) = station.get_bounds()?;
+        let origin = Point2::new(top_left.x as f32, top_left.y as f32) * crate::TILE_WIDTH
+            - Point2::one() * crate::TILE_WIDTH / 2.0;
+        let size = Point2::new(
+            (bottom_right.x - top_left.x + 1) as f32,
+            (bottom_right.y - top_left.y + 1) as f32,
+        ) * crate::TILE_WIDTH;
+
+        // Keep the station's shape, as big as it'll go
+        let scale = (MINIMAP_SIZE / size.x).min(MINIMAP_SIZE / size.y);
+        let size = size * scale;
+        let rect = Rect::new(
+            screen_size.x - MINIMAP_MARGIN - size.x,
+            screen_size.y - MINIMAP_MARGIN - size.y,
+            size.x,
+            size.y,
+        );
+
+        Some(Minimap {
+            rect,
+            origin,
+            scale,
+        })
+    }
+
+    // Is a point on the screen over the map?
+    pub fn contains(&self, screen_pos: Point2) -> bool {
+        self.rect.contains(screen_pos)
+    }
+
+    // The point in the station shown at a point on the map
+    pub fn to_station(&self, screen_pos: Point2) -> Point2 {
+        self.origin + (screen_pos - Point2::new(self.rect.x, self.rect.y)) / self.scale
+    }
+
+    // Where on the map a point in the station is shown
+    pub fn to_screen(&self, station_pos: Point2) -> Point2 {
+        Point2::new(self.rect.x, self.rect.y) + (station_pos - self.origin) * self.scale
+    }
+
+    // Draw the deck the camera's on, who's on it, and the part of it the camera can see
+    // The deck itself comes from the station, which keeps a picture of it until its tiles change
+    pub fn draw(
+        &self,
+        ctx: &mut Context,
+        station: &Station,
+        inhabitants: &[Inhabitant],
+        camera: &Camera,
+    ) -> GameResult<()> {
+        let background =
+            graphics::Mesh::new_rectangle(ctx, DrawMode::fill(), self.rect, BACKGROUND_COLOR)?;
+        graphics::draw(ctx, &background, DrawParam::default())?;
+
+        // A pixel for every tile, stretched out to fill the map
+        if let Some(layer) = station.get_minimap(camera.deck) {
+            let tile_size = crate::TILE_WIDTH * self.scale;
+            graphics::draw(
+                ctx,
+                layer,
+                DrawParam::default()
+                    .dest(Point2::new(self.rect.x, self.rect.y))
+                    .scale(Point2::one() * tile_size),
+            )?;
+        }
+
+        // Everyone on this deck, coloured by what they do
+        let mb = &mut MeshBuilder::new();
+        for inhabitant in inhabitants.iter().filter(|i| i.deck == camera.deck) {
+            let pos = self.to_screen(inhabitant.pos - station.pos);
+            if self.contains(pos) {
+                mb.circle(
+                    DrawMode::fill(),
+                    pos,
+                    INHABITANT_RADIUS,
+                    0.1,
+                    inhabitant_color(inhabitant.get_kind()),
+                )?;
+            }
+        }
+
+        // What the camera can see, cut down to fit inside the map
+        let (screen_width, screen_height) = graphics::drawable_size(ctx);
+        let (view_pos, view_size) =
+            camera.viewport(station.pos, Point2::new(screen_width, screen_height));
+        let top_left = self
+            .to_screen(view_pos)
+            .max(Point2::new(self.rect.x, self.rect.y));
+        let bottom_right = self
+            .to_screen(view_pos + view_size)
+            .min(Point2::new(self.rect.right(), self.rect.bottom()));
+        if top_left.x < bottom_right.x && top_left.y < bottom_right.y {
+            let view = Rect::new(
+                top_left.x,
+                top_left.y,
+                bottom_right.x - top_left.x,
+                bottom_right.y - top_left.y,
+            );
+            mb.rectangle(DrawMode::stroke(1.0), view, VIEWPORT_COLOR)?;
+        }
+
+        mb.rectangle(DrawMode::stroke(1.0), self.rect, BORDER_COLOR)?;
+
+        let mesh = mb.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::default())
+    }
+}
+
+// The picture of a deck the minimap draws, a pixel for each tile from one corner of the bounds to the other
+pub fn layer(
+    ctx: &mut Context,
+    station: &Station,
+    deck: i32,
+    bounds: (GridPosition, GridPosition),
+) -> GameResult<Image> {
+    let (min, max) = bounds;
+    let width = (max.x - min.x + 1) as u16;
+    let height = (max.y - min.y + 1) as u16;
+    let mut image = Image::from_rgba8(ctx, width, height, &layer_pixels(station, deck, bounds))?;
+
+    // Keep the tiles square, rather than blurred together
+    image.set_filter(FilterMode::Nearest);
+    Ok(image)
+}
+
+// The RGBA of every pixel in a deck's picture, row by row. Anything that isn't a tile is left see-through
+pub fn layer_pixels(station: &Station, deck: i32, bounds: (GridPosition, GridPosition)) -> Vec<u8> {
+    let (min, max) = bounds;
+    let width = (max.x - min.x + 1) as usize;
+    let height = (max.y - min.y + 1) as usize;
+    let mut pixels = vec![0; width * height * 4];
+    for tile in station.get_tiles_on_deck(deck) {
+        let color = match tile.kind {
+            _ if tile.is_breached() => continue,
+            TileType::Floor => FLOOR_COLOR,
+            TileType::Wall(_) => WALL_COLOR,
+            TileType::Door(_) => DOOR_COLOR,
+        };
+        let (r, g, b, a) = color.to_rgba();
+        let i = ((tile.pos.y - min.y) as usize * width + (tile.pos.x - min.x) as usize) * 4;
+        pixels[i..i + 4].copy_from_slice(&[r, g, b, a]);
+    }
+    pixels
+}
+
+// So everyone can be told apart at a glance
+fn inhabitant_color(kind: InhabitantType) -> Color {
+    match kind {
+        InhabitantType::Pilot => Color::new(0.3, 0.6, 1.0, 1.0),
+        InhabitantType::Engineer => Color::new(1.0, 0.6, 0.1, 1.0),
+        InhabitantType::Scientist => Color::new(0.7, 0.4, 1.0, 1.0),
+        InhabitantType::Medic => Color::new(0.2, 0.9, 0.3, 1.0),
+        InhabitantType::Soldier => Color::new(0.9, 0.1, 0.1, 1.0),
+        InhabitantType::Miner => Color::new(0.6, 0.4, 0.2, 1.0),
+        InhabitantType::Cook => Color::new(1.0, 1.0, 0.3, 1.0),
+        InhabitantType::Ghost => Color::new(0.8, 0.8, 0.8, 0.6),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{layer_pixels, Minimap, FLOOR_COLOR, MINIMAP_MARGIN, MINIMAP_SIZE, WALL_COLOR};
+    use crate::station::station::Station;
+    use oorandom::Rand32;
+
+    type Point2 = glam::Vec2;
+
+    #[test]
+    fn fits_in_the_corner() {
+        let screen_size = Point2::new(800.0, 600.0);
+        let empty = Station::empty(Point2::zero());
+        assert!(
+            Minimap::new(&empty, screen_size).is_none(),
+            "Nothing to map"
+        );
+
+        // Twice as wide as it is tall
+        let station = Station::from_ascii(
+            Point2::new(50.0, 50.0),
+            &["########", "#......#", "#......#", "########"].join("\n"),
+            &mut Rand32::new(1),
+        )
+        .unwrap();
+        let minimap = Minimap::new(&station, screen_size).unwrap();
+        assert_eq!(MINIMAP_SIZE, minimap.rect.w, "As big as it'll go");
+        assert_eq!(MINIMAP_SIZE / 2.0, minimap.rect.h, "Keeping its shape");
+        assert_eq!(screen_size.x - MINIMAP_MARGIN, minimap.rect.right());
+        assert_eq!(screen_size.y - MINIMAP_MARGIN, minimap.rect.bottom());
+
+        // The middle of the map is the middle of the station, between the two middle tiles
+        let middle = Point2::new(
+            minimap.rect.x + minimap.rect.w / 2.0,
+            minimap.rect.y + minimap.rect.h / 2.0,
+        );
+        assert!(minimap.contains(middle));
+        assert!(!minimap.contains(Point2::zero()));
+        let expected = Point2::new(3.5, 1.5) * crate::TILE_WIDTH;
+        assert!((minimap.to_station(middle) - expected).length() < 0.001);
+        assert!((minimap.to_screen(expected) - middle).length() < 0.001);
+    }
+
+    #[test]
+    fn layer_has_every_tile() {
+        let station = Station::from_ascii(
+            Point2::zero(),
+            &["###", "#.#", "###", "---", "#"].join("\n"),
+            &mut Rand32::new(1),
+        )
+        .unwrap();
+        let bounds = station.get_bounds().unwrap();
+        let pixel = |pixels: &[u8], x: usize, y: usize| {
+            let i = (y * 3 + x) * 4;
+            [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+        };
+        let rgba = |color: ggez::graphics::Color| {
+            let (r, g, b, a) = color.to_rgba();
+            [r, g, b, a]
+        };
+
+        let ground = layer_pixels(&station, 0, bounds);
+        assert_eq!(3 * 3 * 4, ground.len(), "A pixel for each tile");
+        assert_eq!(rgba(WALL_COLOR), pixel(&ground, 0, 0));
+        assert_eq!(rgba(FLOOR_COLOR), pixel(&ground, 1, 1));
+
+        // Decks share the bounds, so a smaller deck above is mostly see-through
+        let above = layer_pixels(&station, 1, bounds);
+        assert_eq!(ground.len(), above.len());
+        assert_eq!(rgba(WALL_COLOR), pixel(&above, 0, 0));
+        assert_eq!(0, pixel(&above, 1, 1)[3]);
+    }
+}
@@ -5,20 +5,22 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use rand::seq::SliceRandom;
+use oorandom::Rand32;
 
 pub struct Music {
     sound: Option<audio::Source>,
     paths: Vec<PathBuf>,
+    rng: Rand32,
 }
 
 impl Music {
-    pub fn new(ctx: &mut Context) -> Music {
+    pub fn new(ctx: &mut Context, rng: Rand32) -> Music {
         let dir_contents: Vec<PathBuf> = filesystem::read_dir(ctx, "/music").unwrap().collect();
 
         Music {
             sound: None,
             paths: dir_contents,
+            rng,
         }
     }
 
@@ -26,7 +28,7 @@ impl Music {
         match &mut self.sound {
             Some(sound) => {
                 if sound.stopped() {
-                    let path = choose_music(&self.paths);
+                    let path = choose_music(&self.paths, &mut self.rng);
                     println!("Choosing new music: {}", path.display());
                     let mut sound = audio::Source::new(ctx, path).unwrap();
                     sound.set_fade_in(Duration::from_millis(5000));
@@ -36,7 +38,7 @@ impl Music {
                 }
             }
             None => {
-                let path = choose_music(&self.paths);
+                let path = choose_music(&self.paths, &mut self.rng);
                 println!("Starting music: {}", path.display());
                 let mut sound = audio::Source::new(ctx, path).unwrap();
                 sound.set_fade_in(Duration::from_millis(5000));
@@ -50,9 +52,9 @@ impl Music {
     }
 }
 
-fn choose_music(paths: &[PathBuf]) -> &Path {
-    let mut rng = rand::thread_rng();
-    paths.choose(&mut rng).unwrap()
+fn choose_music<'a>(paths: &'a [PathBuf], rng: &mut Rand32) -> &'a Path {
+    let index = rng.rand_range(0..paths.len() as u32) as usize;
+    &paths[index]
 }
 
 impl fmt::Display for Music {
@@ -0,0 +1,38 @@
+use crate::station::tileset::Renderer;
+
+use ggez::{filesystem, Context, GameError, GameResult};
+
+use serde::{Deserialize, Serialize};
+
+use std::path;
+
+// Where preferences are kept, alongside the saves
+const PREFERENCES_FILE: &str = "/preferences.cbor";
+
+// How the player likes things, kept between games rather than in any one of them
+// Anything missing from the file, like a setting added since it was written, gets its default
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+#[serde(default)]
+pub struct Preferences {
+    pub renderer: Renderer, // How the station is drawn
+}
+
+impl Preferences {
+    // Read the preferences in, or the defaults if there aren't any yet or they can't be read
+    pub fn load(ctx: &mut Context) -> Preferences {
+        let file = match filesystem::open(ctx, path::Path::new(PREFERENCES_FILE)) {
+            Ok(file) => file,
+            Err(_) => return Preferences::default(),
+        };
+        serde_cbor::from_reader(file).unwrap_or_else(|e| {
+            println!("Couldn't read preferences, using the defaults: {}", e);
+            Preferences::default()
+        })
+    }
+
+    // Write the preferences out, overwriting what was there
+    pub fn save(&self, ctx: &mut Context) -> GameResult<()> {
+        let file = filesystem::create(ctx, path::Path::new(PREFERENCES_FILE))?;
+        serde_cbor::to_writer(file, self).map_err(|e| GameError::FilesystemError(e.to_string()))
+    }
+}
@@ -0,0 +1,264 @@
+use crate::savegame::SavedGame;
+use crate::simulation::Simulation;
+use crate::station::build::BuildTool;
+use crate::station::gridposition::GridPosition;
+use crate::station::room::{RoomId, RoomType};
+use crate::station::tile::{DoorAccess, DoorState};
+
+use ggez::{filesystem, Context, GameError, GameResult};
+
+use serde::{Deserialize, Serialize};
+
+use std::path;
+
+type Point2 = glam::Vec2;
+
+// How often, in ticks, a checksum of the simulation is recorded
+pub const CHECKSUM_INTERVAL: u64 = 60;
+
+// Replay files live next to the saves, with their own extension
+pub const REPLAY_EXTENSION: &str = "replay";
+
+// Every player action that changes the state of a game
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Command {
+    AddInhabitant,
+    DesignateRoom(RoomId, RoomType),
+    SetDoorState(GridPosition, DoorState),
+    SetDoorAccess(GridPosition, DoorAccess),
+    Build(GridPosition, BuildTool),
+    Save(String),         // Name of the save file
+    Load(Box<SavedGame>), // The whole save, so replays don't depend on files that might change
+    CameraUp,
+    CameraDown,
+    CameraLeft,
+    CameraRight,
+    CameraReset,
+    CameraZoom(f32),
+    CameraJump(Point2), // Where the camera ended up, after clicking on the minimap
+    DeckUp,
+    DeckDown,
+}
+
+// A command, and the tick (counted from the start of the recording) it happened before
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecordedCommand {
+    pub tick: u64,
+    pub command: Command,
+}
+
+// What the simulation looked like at a tick, to compare against when playing back
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Checksum {
+    pub tick: u64,
+    pub value: u64,
+}
+
+// A recording of a game: where it started, and everything the player did since
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub start: SavedGame,
+    commands: Vec<RecordedCommand>,
+    checksums: Vec<Checksum>,
+    ticks: u64, // How many ticks have been recorded
+}
+
+impl Replay {
+    // Start a new recording from a snapshot
+    pub fn new(start: SavedGame) -> Replay {
+        Replay {
+            start,
+            commands: Vec::new(),
+            checksums: Vec::new(),
+            ticks: 0,
+        }
+    }
+
+    // Record a command as happening before the next tick
+    pub fn record(&mut self, command: Command) {
+        self.commands.push(RecordedCommand {
+            tick: self.ticks,
+            command,
+        });
+    }
+
+    // Call after every simulation tick, so we know when things happened and what they looked like
+    pub fn tick(&mut self, simulation: &Simulation) {
+        self.ticks += 1;
+        if self.ticks.is_multiple_of(CHECKSUM_INTERVAL) {
+            self.checksums.push(Checksum {
+                tick: self.ticks,
+                value: simulation.checksum(),
+            });
+        }
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    // Write the replay out next to the saves, overwriting if it exists
+    pub fn save(&self, ctx: &mut Context, name: &str) -> GameResult<()> {
+        filesystem::create_dir(ctx, path::Path::new("/saves"))?;
+
+        let filename = format!("/saves/{}.{}", name, REPLAY_EXTENSION);
+        println!("Saving replay to {}", filename);
+        let file = filesystem::create(ctx, path::Path::new(&filename))?;
+        serde_cbor::to_writer(file, self).unwrap();
+
+        Ok(())
+    }
+
+    // Read a replay back in from a file
+    pub fn load(ctx: &mut Context, filename: &path::Path) -> GameResult<Replay> {
+        let file = filesystem::open(ctx, filename)?;
+        serde_cbor::from_reader(file).map_err(|e| GameError::ResourceLoadError(e.to_string()))
+    }
+}
+
+// List replay files, oldest first
+pub fn list_replays(ctx: &mut Context) -> GameResult<Vec<path::PathBuf>> {
+    let mut replays: Vec<path::PathBuf> = filesystem::read_dir(ctx, "/saves")?
+        .filter(|path| path.extension().is_some_and(|ext| ext == REPLAY_EXTENSION))
+        .collect();
+    replays.sort();
+    Ok(replays)
+}
+
+// Plays a recording back tick by tick, checking that the simulation does what it did the first time
+pub struct Playback {
+    replay: Replay,
+    tick: u64,
+    next_command: usize,
+    next_checksum: usize,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback {
+            replay,
+            tick: 0,
+            next_command: 0,
+            next_checksum: 0,
+        }
+    }
+
+    // Where the recording started from
+    pub fn start(&self) -> &SavedGame {
+        &self.replay.start
+    }
+
+    // The commands that need applying before the next tick
+    pub fn due_commands(&mut self) -> Vec<Command> {
+        let mut due = Vec::new();
+        while let Some(recorded) = self.replay.commands.get(self.next_command) {
+            if recorded.tick > self.tick {
+                break;
+            }
+            due.push(recorded.command.clone());
+            self.next_command += 1;
+        }
+        due
+    }
+
+    // Call after every simulation tick. Returns the recorded checksum if the simulation no longer matches it
+    pub fn tick(&mut self, simulation: &Simulation) -> Option<Checksum> {
+        self.tick += 1;
+
+        if let Some(expected) = self.replay.checksums.get(self.next_checksum) {
+            if expected.tick == self.tick {
+                self.next_checksum += 1;
+                if expected.value != simulation.checksum() {
+                    return Some(*expected);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.tick
+    }
+
+    // Have we played back everything that was recorded?
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Command, Playback, Replay, CHECKSUM_INTERVAL};
+    use crate::camera::Camera;
+    use crate::savegame::SavedGame;
+    use crate::seed::GameSeed;
+    use crate::simulation::{Simulation, TICK_DURATION};
+    use crate::station::generator::CellularAutomaton;
+
+    type Point2 = glam::Vec2;
+
+    // Record a short game where someone gets added partway through
+    fn test_recording() -> (Simulation, Replay) {
+        let mut sim = Simulation::new(
+            GameSeed::new(7),
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            2,
+        );
+        let mut replay = Replay::new(SavedGame::new(&sim, Camera::new()));
+
+        for tick in 0..(CHECKSUM_INTERVAL * 5) {
+            if tick == 100 {
+                replay.record(Command::AddInhabitant);
+                sim.add_random_inhabitant();
+            }
+            sim.tick(TICK_DURATION);
+            replay.tick(&sim);
+        }
+
+        (sim, replay)
+    }
+
+    #[test]
+    fn play_back_matches() {
+        let (recorded, replay) = test_recording();
+        let mut playback = Playback::new(replay);
+        let (mut sim, _camera) = playback.start().clone().restore();
+
+        while !playback.is_finished() {
+            for command in playback.due_commands() {
+                if let Command::AddInhabitant = command {
+                    sim.add_random_inhabitant();
+                }
+            }
+            sim.tick(TICK_DURATION);
+            assert_eq!(None, playback.tick(&sim), "Playback does not diverge");
+        }
+
+        assert_eq!(3, sim.inhabitants.len());
+        assert_eq!(recorded.checksum(), sim.checksum());
+    }
+
+    #[test]
+    fn play_back_diverges() {
+        let (_recorded, replay) = test_recording();
+        let mut playback = Playback::new(replay);
+        let (mut sim, _camera) = playback.start().clone().restore();
+
+        // Ignore the commands, so we end up somewhere else
+        let mut diverged = None;
+        while !playback.is_finished() && diverged.is_none() {
+            playback.due_commands();
+            sim.tick(TICK_DURATION);
+            diverged = playback.tick(&sim);
+        }
+
+        let checksum = diverged.expect("Playback notices the missing inhabitant");
+        assert_eq!(
+            CHECKSUM_INTERVAL * 2,
+            checksum.tick,
+            "First checksum after tick 100"
+        );
+    }
+}
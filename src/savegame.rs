@@ -0,0 +1,55 @@
+use crate::camera::Camera;
+use crate::inhabitant::Inhabitant;
+use crate::jobs::Jobs;
+use crate::seed::GameSeed;
+use crate::simulation::Simulation;
+use crate::station::station::{Problem, Station};
+
+use serde::{Deserialize, Serialize};
+
+// Save game serialize/deserialize object
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedGame {
+    seed: GameSeed,
+    rng_state: (u64, u64),
+    camera: Camera,
+    station: Station,
+    inhabitants: Vec<Inhabitant>,
+    jobs: Jobs,
+}
+
+impl SavedGame {
+    // Snapshot the current state of a game
+    pub fn new(simulation: &Simulation, camera: Camera) -> SavedGame {
+        SavedGame {
+            seed: simulation.seed(),
+            rng_state: simulation.rng_state(),
+            camera,
+            inhabitants: simulation.inhabitants.clone(),
+            station: simulation.station.clone(),
+            jobs: simulation.jobs.clone(),
+        }
+    }
+
+    // Turn the snapshot back into a running game
+    // The station has no mesh yet, so it needs `build_mesh` before being drawn
+    pub fn restore(self) -> (Simulation, Camera) {
+        let simulation = Simulation::from_saved(
+            self.seed,
+            self.station,
+            self.inhabitants,
+            self.jobs,
+            self.rng_state,
+        );
+        (simulation, self.camera)
+    }
+
+    // Everything wrong with the saved station, which should be nothing unless the file was messed with
+    pub fn validate(&self) -> Vec<Problem> {
+        self.station.validate()
+    }
+
+    pub fn seed(&self) -> GameSeed {
+        self.seed
+    }
+}
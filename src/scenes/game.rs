@@ -1,79 +1,113 @@
 use super::paused::*;
 use super::quit::*;
 use super::scene::*;
+use super::settings::*;
 use crate::camera::Camera;
-use crate::inhabitant::{Inhabitant, InhabitantType};
-use crate::station::station::*;
-use crate::station::tile::*;
-
-use ggez::event::{KeyCode, KeyMods};
+use crate::events::{self, SimEvent};
+use crate::minimap::Minimap;
+use crate::preferences::Preferences;
+use crate::replay::*;
+use crate::savegame::SavedGame;
+use crate::seed::GameSeed;
+use crate::simulation::*;
+use crate::station::build::BuildTool;
+use crate::station::generator::GeneratorType;
+use crate::station::gridposition::GridPosition;
+use crate::station::hull::MAX_INTEGRITY;
+use crate::station::tile::{Tile, TileType};
+
+use ggez::event::{KeyCode, KeyMods, MouseButton};
 use ggez::graphics::{Color, DrawMode, DrawParam, Text};
 use ggez::input::mouse;
-use ggez::{filesystem, graphics, timer, Context, GameResult};
+use ggez::{filesystem, graphics, timer, Context, GameError, GameResult};
 
 use chrono::{DateTime, Local};
-use oorandom::Rand32;
-use serde::{Deserialize, Serialize};
 
-use std::path;
+use std::{mem, path};
 
 type Point2 = glam::Vec2;
 
+// How many simulation ticks run per update at each speed setting
+const SPEEDS: [u32; 4] = [1, 2, 4, 8];
+
+// Build mode previews, for where the current tool can and can't be used
+const BUILD_VALID_COLOR: Color = Color::new(0.0, 1.0, 0.0, 0.3);
+const BUILD_INVALID_COLOR: Color = Color::new(1.0, 0.0, 0.0, 0.3);
+const BLUEPRINT_COLOR: Color = Color::new(0.2, 0.4, 1.0, 0.4);
+const BREACH_ALERT_COLOR: Color = Color::new(1.0, 0.2, 0.2, 1.0);
+
 pub struct Game {
-    rng: oorandom::Rand32,
+    simulation: Simulation,
     is_paused: bool,
+    speed: u32,         // Simulation ticks per update
+    step_pending: bool, // Advance a single tick on the next update, even though we're paused
     camera: Camera,
-    station: Station,
-    inhabitants: Vec<Inhabitant>,
+    replay: ReplayMode,
+    build_tool: Option<BuildTool>, // What we're building, when in build mode
+    painting: Option<GridPosition>, // The last tile built on while the mouse button is held down
+    preferences: Preferences,
+    preferences_changed: bool, // Read the preferences in again on the next update, after coming back from settings
+    menu_open: bool, // A menu's on top of us, so clicks are meant for it and not the station
 
     show_stats: bool,
+    show_heatmap: bool,
+    show_damage: bool,
+    show_minimap: bool,
+}
+
+// A game is always either recording what the player does, or playing a recording back
+enum ReplayMode {
+    Recording(Replay),
+    Playing(Playback),
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context) -> Game {
-        // Create a seeded random-number generator
-        // TODO: Accept seed as input for deterministic replayability
-        let mut seed: [u8; 8] = [0; 8];
-        getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
-        let mut rng = Rand32::new(u64::from_ne_bytes(seed));
-
-        // Make a new station
+    pub fn new(ctx: &mut Context, seed: GameSeed, generator: GeneratorType) -> Game {
+        println!("Creating {} game from seed {}", generator, seed);
+
+        // Make a new station, centered on the screen
         let (screen_width, screen_height) = graphics::drawable_size(ctx);
 
-        let station_width = 21;
-        let station_height = 13;
+        let generator = generator.generator(21, 13);
+        let (station_width, station_height) = generator.size();
 
         let mut station_pos = Point2::new(screen_width / 2.0, screen_height / 2.0);
         station_pos -= Point2::new(
             station_width as f32 * crate::TILE_WIDTH / 2.0,
             station_height as f32 * crate::TILE_WIDTH / 2.0,
         );
-        let station = Station::new(ctx, station_pos, station_width, station_height, &mut rng);
+
+        // Put some people in it
+        let num_crew = 3;
+        let mut simulation = Simulation::new(seed, station_pos, generator.as_ref(), num_crew);
+        simulation.events.subscribe(events::print);
+        let preferences = Preferences::load(ctx);
+        simulation
+            .station
+            .build_mesh(ctx, preferences.renderer)
+            .unwrap();
 
         // Create game state and return it
-        let mut game = Game {
-            rng,
+        let camera = Camera::new();
+        let game = Game {
+            replay: ReplayMode::Recording(Replay::new(SavedGame::new(&simulation, camera))),
+            simulation,
             is_paused: false,
-            camera: Camera::new(),
-            station,
-            inhabitants: Vec::with_capacity(1),
+            speed: SPEEDS[0],
+            step_pending: false,
+            camera,
+            build_tool: None,
+            painting: None,
+            preferences,
+            preferences_changed: false,
+            menu_open: false,
 
             show_stats: false,
+            show_heatmap: false,
+            show_damage: false,
+            show_minimap: true,
         };
 
-        // Put some people in it
-        let tile = game
-            .station
-            .get_random_tile(TileType::Floor, &mut game.rng)
-            .unwrap();
-        let pos = tile.to_world_position(&game.station);
-        let num_crew = 3;
-        for _ in 0..num_crew {
-            // TODO: Don't repeat inhabitant types
-            let inhabitant_type = game.get_random_inhabitant_type();
-            game.add_inhabitant(pos, inhabitant_type);
-        }
-
         // Do we have any saved games?
         let saves = game.list_saves(ctx).unwrap();
         println!("Saves: {:#?}", saves);
@@ -82,38 +116,111 @@ impl Game {
         game
     }
 
-    // Add an inhabitant to the game
-    fn add_inhabitant(&mut self, pos: Point2, kind: InhabitantType) {
-        println!("Putting {:?} inhabitant at {}", kind, pos);
-        self.inhabitants.push(Inhabitant::new(pos, kind));
+    // Create a game that plays back a recording
+    pub fn from_replay(ctx: &mut Context, replay: Replay) -> Game {
+        println!("Playing back replay of seed {}", replay.start.seed());
+        let playback = Playback::new(replay);
+        let (mut simulation, camera) = playback.start().clone().restore();
+        simulation.events.subscribe(events::print);
+        let preferences = Preferences::load(ctx);
+        simulation
+            .station
+            .build_mesh(ctx, preferences.renderer)
+            .unwrap();
+
+        Game {
+            simulation,
+            is_paused: false,
+            speed: SPEEDS[0],
+            step_pending: false,
+            camera,
+            replay: ReplayMode::Playing(playback),
+            build_tool: None,
+            painting: None,
+            preferences,
+            preferences_changed: false,
+            menu_open: false,
+
+            show_stats: false,
+            show_heatmap: false,
+            show_damage: false,
+            show_minimap: true,
+        }
+    }
+
+    // Perform a player action, recording it if we're recording
+    fn apply(&mut self, ctx: &mut Context, command: Command) {
+        if let ReplayMode::Recording(replay) = &mut self.replay {
+            replay.record(command.clone());
+        }
+
+        match command {
+            Command::AddInhabitant => self.simulation.add_random_inhabitant(),
+            Command::DesignateRoom(id, kind) => self.simulation.station.designate_room(id, kind),
+            Command::SetDoorState(pos, state) => self.simulation.station.set_door_state(pos, state),
+            Command::SetDoorAccess(pos, access) => {
+                self.simulation.station.set_door_access(pos, access)
+            }
+            Command::Build(pos, tool) => {
+                self.simulation.plan_build(pos, tool);
+            }
+            Command::Save(name) => {
+                // Only write files when the player asked for it, not when replaying them asking
+                if let ReplayMode::Recording(replay) = &self.replay {
+                    self.save(ctx, &name).unwrap();
+                    replay.save(ctx, &name).unwrap();
+                }
+            }
+            Command::Load(save) => self.restore(ctx, *save).unwrap(),
+            Command::CameraUp => self.camera.move_up(),
+            Command::CameraDown => self.camera.move_down(),
+            Command::CameraLeft => self.camera.move_left(),
+            Command::CameraRight => self.camera.move_right(),
+            Command::CameraReset => self.camera.reset(),
+            Command::CameraZoom(amount) => self.camera.zoom(amount),
+            Command::CameraJump(pos) => self.camera.pos = pos,
+            Command::DeckUp => {
+                let decks = self.simulation.station.get_decks();
+                self.camera.change_deck(&decks, true);
+            }
+            Command::DeckDown => {
+                let decks = self.simulation.station.get_decks();
+                self.camera.change_deck(&decks, false);
+            }
+        }
+    }
+
+    // Perform an action from the player's input. Ignored while playing back a recording
+    fn input(&mut self, ctx: &mut Context, command: Command) {
+        match self.replay {
+            ReplayMode::Recording(_) => self.apply(ctx, command),
+            ReplayMode::Playing(_) => println!("Ignoring input during replay"),
+        }
     }
 
-    fn get_random_inhabitant_type(&mut self) -> InhabitantType {
-        // TODO: Got to be a better way to do this
-        match self.rng.rand_range(0..6) {
-            0 => InhabitantType::Pilot,
-            1 => InhabitantType::Engineer,
-            2 => InhabitantType::Scientist,
-            3 => InhabitantType::Medic,
-            4 => InhabitantType::Soldier,
-            5 => InhabitantType::Miner,
-            6 => InhabitantType::Cook,
-            _ => panic!("Invalid inhabitant type chosen"),
+    // Build with the current tool wherever the mouse is, if it can be built there
+    fn paint(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        let tool = match self.build_tool {
+            Some(tool) => tool,
+            None => return,
+        };
+        let pos = self
+            .simulation
+            .station
+            .grid_position_from_screen(Point2::new(x, y), &self.camera);
+        self.painting = Some(pos);
+        if self.simulation.can_build(pos, tool).is_ok() {
+            self.input(ctx, Command::Build(pos, tool));
         }
     }
 
     // Save the game state to a file, overwriting if it exists
-    fn save(&self, ctx: &mut Context, name: String) -> GameResult<()> {
+    fn save(&self, ctx: &mut Context, name: &str) -> GameResult<()> {
         // Make sure the directory exists
         filesystem::create_dir(ctx, path::Path::new("/saves")).unwrap();
 
         // Create the save game object
-        let state = SavedGame {
-            rng_state: self.rng.state(),
-            camera: self.camera,
-            inhabitants: self.inhabitants.clone(),
-            station: self.station.clone(),
-        };
+        let state = SavedGame::new(&self.simulation, self.camera);
 
         // Write the game state out
         let filename = format!("/saves/{}.cbor", name);
@@ -127,27 +234,91 @@ impl Game {
     }
 
     // Load the game state from a file
-    fn load(&mut self, ctx: &mut Context, filename: &path::Path) -> GameResult<()> {
+    fn load(&self, ctx: &mut Context, filename: &path::Path) -> GameResult<SavedGame> {
         // Load the file
         let file = filesystem::open(ctx, path::Path::new(filename)).unwrap();
         let save: SavedGame = serde_cbor::from_reader(file).unwrap();
 
-        // Copy the data over
-        self.rng = oorandom::Rand32::from_state(save.rng_state);
-        self.camera = save.camera;
-        self.inhabitants = save.inhabitants;
-        self.station = save.station;
+        // Don't let a broken station in
+        let problems = save.validate();
+        if !problems.is_empty() {
+            let problems: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+            return Err(GameError::ResourceLoadError(format!(
+                "Invalid station: {}",
+                problems.join(", ")
+            )));
+        }
+
+        Ok(save)
+    }
+
+    // Replace the game state with a save
+    fn restore(&mut self, ctx: &mut Context, save: SavedGame) -> GameResult<()> {
+        // Copy the data over, keeping everyone subscribed to events
+        let (mut simulation, camera) = save.restore();
+        simulation.events = mem::take(&mut self.simulation.events);
+        self.simulation = simulation;
+        self.camera = camera;
 
         // Rebuild all the meshes
-        self.station.build_mesh(ctx)?;
+        self.simulation
+            .station
+            .build_mesh(ctx, self.preferences.renderer)?;
 
         // Guess it worked
         Ok(())
     }
 
-    // List saved games
+    // Advance the simulation by one fixed step, keeping the replay in step with it
+    fn tick(&mut self, ctx: &mut Context) {
+        // Re-apply anything the player did before this tick in the recording
+        if let ReplayMode::Playing(playback) = &mut self.replay {
+            for command in playback.due_commands() {
+                self.apply(ctx, command);
+            }
+        }
+
+        self.simulation.tick(TICK_DURATION);
+
+        // Keep the recording up to date, or check the playback still matches it
+        let mut finished = false;
+        match &mut self.replay {
+            ReplayMode::Recording(replay) => replay.tick(&self.simulation),
+            ReplayMode::Playing(playback) => {
+                if let Some(expected) = playback.tick(&self.simulation) {
+                    self.simulation.events.publish(SimEvent::ReplayDiverged {
+                        tick: expected.tick,
+                        expected: expected.value,
+                        actual: self.simulation.checksum(),
+                    });
+                    self.simulation.events.dispatch();
+                }
+                finished = playback.is_finished();
+            }
+        }
+
+        // Once the recording runs out, the game carries on as normal
+        if finished {
+            println!("Replay finished, recording from here");
+            self.replay =
+                ReplayMode::Recording(Replay::new(SavedGame::new(&self.simulation, self.camera)));
+        }
+    }
+
+    // Where the minimap is on the screen right now, if there's a station to map
+    fn minimap(&self, ctx: &mut Context) -> Option<Minimap> {
+        let (screen_width, screen_height) = graphics::drawable_size(ctx);
+        Minimap::new(
+            &self.simulation.station,
+            Point2::new(screen_width, screen_height),
+        )
+    }
+
+    // List saved games, skipping the replays that live alongside them
     fn list_saves(&self, ctx: &mut Context) -> GameResult<Vec<path::PathBuf>> {
-        let dir_contents: Vec<path::PathBuf> = filesystem::read_dir(ctx, "/saves")?.collect();
+        let dir_contents: Vec<path::PathBuf> = filesystem::read_dir(ctx, "/saves")?
+            .filter(|path| path.extension().is_some_and(|ext| ext == "cbor"))
+            .collect();
         Ok(dir_contents)
     }
 }
@@ -158,11 +329,43 @@ impl Scene for Game {
     }
 
     fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+        let station = &self.simulation.station;
+
+        // Anything drawn on the station's grid moves and zooms along with it
+        let camera_param = DrawParam::default()
+            .dest(station.pos)
+            .offset(self.camera.pos)
+            .scale(self.camera.zoom);
+
         // Draw the station
-        self.station.draw(ctx, &self.camera)?;
+        station.draw(ctx, &self.camera, self.preferences.renderer)?;
+        if self.show_heatmap {
+            station.draw_heatmap(ctx, &self.camera)?;
+        }
+        if self.show_damage {
+            station.draw_damage(ctx, &self.camera)?;
+        }
+
+        // Draw the blueprints waiting to be built on this deck
+        for job in self
+            .simulation
+            .jobs
+            .iter()
+            .filter(|job| job.pos.z == self.camera.deck)
+        {
+            let job_rect = graphics::Rect::new(
+                (crate::TILE_WIDTH * job.pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+                (crate::TILE_WIDTH * job.pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+                crate::TILE_WIDTH,
+                crate::TILE_WIDTH,
+            );
+            let mesh =
+                graphics::Mesh::new_rectangle(ctx, DrawMode::fill(), job_rect, BLUEPRINT_COLOR)?;
+            graphics::draw(ctx, &mesh, camera_param)?;
+        }
 
         // Draw the inhabitants
-        for inhabitant in &self.inhabitants {
+        for inhabitant in &self.simulation.inhabitants {
             inhabitant.draw(ctx, &self.camera)?;
         }
 
@@ -176,14 +379,21 @@ impl Scene for Game {
             Color::new(0.0, 0.0, 0.0, 1.0),
         )?;
         graphics::draw(ctx, &mesh, DrawParam::default())?;
-        let ui_text = Text::new(format!(
-            "Inhabitants: {}, Food: {}, Drink: {}",
-            self.inhabitants.len(),
-            self.station.find_items(crate::item::get_food_types()).len(),
-            self.station
-                .find_items(crate::item::get_drink_types())
-                .len(),
+        let mut ui_text = Text::new(format!(
+            "Inhabitants: {}, Food: {}, Drink: {}, Materials: {}, Jobs: {}, Speed: {}x",
+            self.simulation.inhabitants.len(),
+            station.count_items(crate::item::get_food_types()),
+            station.count_items(crate::item::get_drink_types()),
+            station.count_items(crate::item::get_material_types()),
+            self.simulation.jobs.len(),
+            self.speed,
         ));
+        if let Some(tool) = self.build_tool {
+            ui_text.add(format!(", Building: {} (Tab for next, B to stop)", tool));
+        }
+        if let ReplayMode::Playing(playback) = &self.replay {
+            ui_text.add(format!(", Replaying: tick {}", playback.ticks()));
+        }
         graphics::queue_text(
             ctx,
             &ui_text,
@@ -194,15 +404,24 @@ impl Scene for Game {
             Some(Color::WHITE),
         );
 
+        // Shout about any holes in the hull
+        if station.get_breaches().next().is_some() {
+            let alert = Text::new("Breach!");
+            graphics::queue_text(
+                ctx,
+                &alert,
+                Point2::new(10.0, (20.0 - alert.height(ctx)) / 2.0),
+                Some(BREACH_ALERT_COLOR),
+            );
+        }
+
         // Draw where the mouse is
-        // TODO: This doesn't use the camera either to find the tile or to scale the drawing of the selection
         let mut mouse_pos = mouse::position(ctx);
         let mut mouse_display = Text::new(format!("Mouse: ({}, {})", mouse_pos.x, mouse_pos.y));
-        if let Some(selected_tile) = self
-            .station
-            .get_tile_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera)
+        if let Some(selected_tile) =
+            station.get_tile_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera)
         {
-            let world_pos = selected_tile.to_world_position(&self.station);
+            let world_pos = selected_tile.to_world_position(station);
             mouse_display.add(format!(
                 "\nTile: Grid ({}, {}), World ({},{}), {:?}",
                 selected_tile.pos.x,
@@ -212,6 +431,59 @@ impl Scene for Game {
                 selected_tile.kind
             ));
 
+            if let Some(room) = station.get_room_at(selected_tile.pos) {
+                mouse_display.add(format!("\nRoom: {}", room));
+            }
+
+            if let Some(gas) = station.get_gas(selected_tile.pos) {
+                mouse_display.add(format!(
+                    "\nAir: {:.1} kPa (O2 {:.1} kPa, CO2 {:.2} kPa)",
+                    gas.pressure(),
+                    gas.o2_pressure(),
+                    gas.co2_pressure()
+                ));
+            }
+
+            if let Some(temperature) = station.get_temperature(selected_tile.pos) {
+                mouse_display.add(format!(
+                    "\nTemperature: {:.1} K ({:.1} C)",
+                    temperature,
+                    temperature - 273.15
+                ));
+            }
+
+            if let Some(network) = station.get_network_at(selected_tile.pos) {
+                mouse_display.add(format!("\nPower: {}", network));
+            }
+
+            if selected_tile.is_breached() {
+                mouse_display.add("\nBreached! Open to space");
+            } else if selected_tile.integrity < MAX_INTEGRITY {
+                mouse_display.add(format!(
+                    "\nIntegrity: {}/{}",
+                    selected_tile.integrity, MAX_INTEGRITY
+                ));
+            }
+
+            if let Some(fire) = station.get_fire(selected_tile.pos) {
+                mouse_display.add(format!("\nOn fire! {}", fire));
+            }
+
+            if let Some(job) = self.simulation.jobs.get(selected_tile.pos) {
+                mouse_display.add(format!(
+                    "\nJob: {}, materials {}/{}, {:.0}s of work left{}",
+                    job.tool,
+                    job.materials_delivered,
+                    job.materials_needed,
+                    job.work_left.max(0.0),
+                    if job.assigned.is_some() {
+                        ""
+                    } else {
+                        " (waiting for an Engineer)"
+                    }
+                ));
+            }
+
             if !selected_tile.items.is_empty() {
                 mouse_display.add(format!("\n{:?}", selected_tile.items));
             }
@@ -228,7 +500,27 @@ impl Scene for Game {
                 tile_rect,
                 Color::new(1.0, 1.0, 0.0, 1.0),
             )?;
-            graphics::draw(ctx, &mesh, DrawParam::default().dest(self.station.pos))?;
+            graphics::draw(ctx, &mesh, camera_param)?;
+        }
+        // Show whether the current tool can be used where the mouse is
+        if let Some(tool) = self.build_tool {
+            let pos = station
+                .grid_position_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera);
+            let color = match self.simulation.can_build(pos, tool) {
+                Ok(()) => BUILD_VALID_COLOR,
+                Err(reason) => {
+                    mouse_display.add(format!("\nCan't build {}: {}", tool, reason));
+                    BUILD_INVALID_COLOR
+                }
+            };
+            let tile_rect = graphics::Rect::new(
+                (crate::TILE_WIDTH * pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+                (crate::TILE_WIDTH * pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+                crate::TILE_WIDTH,
+                crate::TILE_WIDTH,
+            );
+            let mesh = graphics::Mesh::new_rectangle(ctx, DrawMode::fill(), tile_rect, color)?;
+            graphics::draw(ctx, &mesh, camera_param)?;
         }
         mouse_pos.y -= mouse_display.height(ctx);
         graphics::queue_text(ctx, &mouse_display, mouse_pos, Some(Color::WHITE));
@@ -253,10 +545,18 @@ impl Scene for Game {
                 Some(Color::WHITE),
             );
             height += 5.0 + uptime_display.height(ctx);
+            let seed_display = Text::new(format!("Seed: {}", self.simulation.seed()));
+            graphics::queue_text(
+                ctx,
+                &seed_display,
+                Point2::new(10.0, 0.0 + height),
+                Some(Color::WHITE),
+            );
+            height += 5.0 + seed_display.height(ctx);
             let station_display = Text::new(format!(
                 "Station Tiles: {} at {}, Selected: None",
-                self.station.num_tiles(),
-                self.station.pos
+                station.num_tiles(),
+                station.pos
             ));
             graphics::queue_text(
                 ctx,
@@ -266,8 +566,8 @@ impl Scene for Game {
             );
             height += 5.0 + station_display.height(ctx);
             let camera_display = Text::new(format!(
-                "Camera: {} ({1:.1}x)",
-                self.camera.pos, self.camera.zoom.x
+                "Camera: {} ({1:.1}x), deck {2}",
+                self.camera.pos, self.camera.zoom.x, self.camera.deck
             ));
             graphics::queue_text(
                 ctx,
@@ -285,6 +585,13 @@ impl Scene for Game {
             );*/
         }
 
+        // A map of the whole station, to find the way around it
+        if self.show_minimap {
+            if let Some(minimap) = self.minimap(ctx) {
+                minimap.draw(ctx, station, &self.simulation.inhabitants, &self.camera)?;
+            }
+        }
+
         // Render all queued text
         graphics::draw_queued_text(
             ctx,
@@ -297,18 +604,32 @@ impl Scene for Game {
     }
 
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        // Are we paused?
-        if self.is_paused {
-            return Ok(());
+        // Are we paused? Then only run a tick if one was asked for
+        let ticks = if self.is_paused {
+            self.step_pending as u32
+        } else {
+            self.speed
+        };
+        self.step_pending = false;
+
+        // Running faster means more fixed steps per update, not bigger ones, so the simulation
+        // (and any recording of it) comes out the same at any speed
+        for _ in 0..ticks {
+            self.tick(ctx);
         }
 
-        // Update the station
-        self.station.update(ctx)?;
+        // Settings may have changed how the station is drawn
+        if self.preferences_changed {
+            self.preferences = Preferences::load(ctx);
+            self.preferences_changed = false;
+        }
 
-        // Update and move the inhabitants
-        for inhabitant in &mut self.inhabitants {
-            inhabitant.update(ctx, &self.station, &mut self.rng)?;
+        // Anything that changed the station's tiles threw its mesh away
+        let renderer = self.preferences.renderer;
+        if !self.simulation.station.has_mesh(renderer) {
+            self.simulation.station.build_mesh(ctx, renderer)?;
         }
+
         Ok(())
     }
 
@@ -333,52 +654,121 @@ impl Scene for Game {
                 action = SceneAction::Push(Box::new(Paused {}))
             }
 
+            // Simulation speed
+            KeyCode::Key1 => self.speed = SPEEDS[0],
+            KeyCode::Key2 => self.speed = SPEEDS[1],
+            KeyCode::Key3 => self.speed = SPEEDS[2],
+            KeyCode::Key4 => self.speed = SPEEDS[3],
+
+            // Step forward a single tick while paused
+            KeyCode::Period if self.is_paused => self.step_pending = true,
+
             // Add a new inhabitant
             KeyCode::N if !repeat && !self.is_paused => {
-                let tile = self
-                    .station
-                    .get_random_tile(TileType::Floor, &mut self.rng)
-                    .unwrap();
-                let pos = tile.to_world_position(&self.station);
-                let inhabitant_type = self.get_random_inhabitant_type();
-                self.add_inhabitant(pos, inhabitant_type);
+                self.input(ctx, Command::AddInhabitant);
             }
 
-            // Camera movement from arrow keys
-            KeyCode::Up => {
-                self.camera.move_up();
-            }
-            KeyCode::Down => {
-                self.camera.move_down();
-            }
-            KeyCode::Left => {
-                self.camera.move_left();
+            // Designate the room under the mouse as the next type of room
+            KeyCode::D if !repeat => {
+                let mouse_pos = mouse::position(ctx);
+                let station = &self.simulation.station;
+                let room = station
+                    .get_tile_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera)
+                    .and_then(|tile| station.get_room_at(tile.pos));
+                if let Some(room) = room {
+                    let command = Command::DesignateRoom(room.id, room.kind.next());
+                    self.input(ctx, command);
+                }
             }
-            KeyCode::Right => {
-                self.camera.move_right();
+
+            // Open, close, lock or break the door under the mouse
+            KeyCode::O if !repeat => {
+                let mouse_pos = mouse::position(ctx);
+                let tile = self
+                    .simulation
+                    .station
+                    .get_tile_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera);
+                if let Some(Tile {
+                    pos,
+                    kind: TileType::Door(door),
+                    ..
+                }) = tile
+                {
+                    let command = Command::SetDoorState(*pos, door.next_state());
+                    self.input(ctx, command);
+                }
             }
-            KeyCode::C => {
-                self.camera.reset();
+
+            // Change who's allowed through the door under the mouse
+            KeyCode::A if !repeat => {
+                let mouse_pos = mouse::position(ctx);
+                let tile = self
+                    .simulation
+                    .station
+                    .get_tile_from_screen(Point2::new(mouse_pos.x, mouse_pos.y), &self.camera);
+                if let Some(Tile {
+                    pos,
+                    kind: TileType::Door(door),
+                    ..
+                }) = tile
+                {
+                    let command = Command::SetDoorAccess(*pos, door.next_access());
+                    self.input(ctx, command);
+                }
             }
 
+            // Camera movement from arrow keys
+            KeyCode::Up => self.input(ctx, Command::CameraUp),
+            KeyCode::Down => self.input(ctx, Command::CameraDown),
+            KeyCode::Left => self.input(ctx, Command::CameraLeft),
+            KeyCode::Right => self.input(ctx, Command::CameraRight),
+            KeyCode::C => self.input(ctx, Command::CameraReset),
+            KeyCode::PageUp if !repeat => self.input(ctx, Command::DeckUp),
+            KeyCode::PageDown if !repeat => self.input(ctx, Command::DeckDown),
+
             // Save the game
             KeyCode::S if !repeat => {
                 let now: DateTime<Local> = Local::now();
-                self.save(ctx, now.format("%Y-%m-%d %H-%M-%S.%f").to_string())
-                    .unwrap();
+                let name = now.format("%Y-%m-%d %H-%M-%S.%f").to_string();
+                self.input(ctx, Command::Save(name));
             }
 
             // Load a save
             KeyCode::L if !repeat => {
                 let saves = self.list_saves(ctx).unwrap();
                 if let Some(filename) = saves.last() {
-                    self.load(ctx, filename).unwrap();
+                    match self.load(ctx, filename) {
+                        Ok(save) => self.input(ctx, Command::Load(Box::new(save))),
+                        Err(e) => println!("Couldn't load {}: {}", filename.display(), e),
+                    }
                 }
             }
 
+            // Toggle build mode, and pick what to build
+            KeyCode::B if !repeat => {
+                self.build_tool = match self.build_tool {
+                    Some(_) => None,
+                    None => Some(BuildTool::Floor),
+                };
+                self.painting = None;
+            }
+            KeyCode::Tab if !repeat => self.build_tool = self.build_tool.map(|tool| tool.next()),
+
             // Toggle stats
             KeyCode::F1 if !repeat => self.show_stats = !self.show_stats,
 
+            // Toggle the heatmap
+            KeyCode::F2 if !repeat => self.show_heatmap = !self.show_heatmap,
+
+            // Toggle the damage overlay
+            KeyCode::F3 if !repeat => self.show_damage = !self.show_damage,
+
+            // Toggle the minimap
+            KeyCode::M if !repeat => self.show_minimap = !self.show_minimap,
+
+            // Change settings (F10 is taken, it toggles fullscreen everywhere)
+            KeyCode::F9 if !repeat => action = SceneAction::Push(Box::new(Settings::new(ctx))),
+
             // Everything else does nothing
             _ => (),
         }
@@ -386,8 +776,73 @@ impl Scene for Game {
         action
     }
 
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) -> SceneAction {
-        self.camera.zoom(y);
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, _x: f32, y: f32) -> SceneAction {
+        if self.menu_open {
+            return SceneAction::None;
+        }
+        self.input(ctx, Command::CameraZoom(y));
+
+        SceneAction::None
+    }
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: MouseButton,
+        x: f32,
+        y: f32,
+    ) -> SceneAction {
+        if button != MouseButton::Left || self.menu_open {
+            return SceneAction::None;
+        }
+
+        // Clicking on the minimap looks at that part of the station, and anywhere else builds
+        match self.minimap(ctx) {
+            Some(minimap) if self.show_minimap && minimap.contains(Point2::new(x, y)) => {
+                let (screen_width, screen_height) = graphics::drawable_size(ctx);
+                let mut camera = self.camera;
+                camera.look_at(
+                    minimap.to_station(Point2::new(x, y)),
+                    self.simulation.station.pos,
+                    Point2::new(screen_width, screen_height),
+                );
+                self.input(ctx, Command::CameraJump(camera.pos));
+            }
+            _ => self.paint(ctx, x, y),
+        }
+
+        SceneAction::None
+    }
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> SceneAction {
+        if button == MouseButton::Left {
+            self.painting = None;
+        }
+
+        SceneAction::None
+    }
+    fn mouse_motion_event(
+        &mut self,
+        ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> SceneAction {
+        // Dragging paints every tile the mouse passes over, once each
+        if let Some(last) = self.painting.filter(|_| !self.menu_open) {
+            let pos = self
+                .simulation
+                .station
+                .grid_position_from_screen(Point2::new(x, y), &self.camera);
+            if pos != last {
+                self.paint(ctx, x, y);
+            }
+        }
 
         SceneAction::None
     }
@@ -399,7 +854,15 @@ impl Scene for Game {
     fn from_scene(&mut self, kind: SceneType) {
         match kind {
             SceneType::Paused => self.is_paused = false,
-            SceneType::Quit => self.is_paused = false,
+            SceneType::Quit => {
+                self.is_paused = false;
+                self.menu_open = false;
+            }
+            SceneType::Settings => {
+                self.is_paused = false;
+                self.menu_open = false;
+                self.preferences_changed = true;
+            }
             _ => (),
         }
     }
@@ -407,17 +870,12 @@ impl Scene for Game {
     fn to_scene(&mut self, kind: SceneType) {
         match kind {
             SceneType::Paused => self.is_paused = true,
-            SceneType::Quit => self.is_paused = true,
+            SceneType::Quit | SceneType::Settings => {
+                self.is_paused = true;
+                self.menu_open = true;
+                self.painting = None;
+            }
             _ => (),
         }
     }
 }
-
-// Save game serialize/deserialize object
-#[derive(Serialize, Deserialize)]
-struct SavedGame {
-    rng_state: (u64, u64),
-    camera: Camera,
-    station: Station,
-    inhabitants: Vec<Inhabitant>,
-}
@@ -73,13 +73,28 @@ impl Scene for Paused {
                 SceneAction::Pop
             }
 
+            // Let the game underneath step, change speed, and look around while paused
+            KeyCode::Period
+            | KeyCode::Key1
+            | KeyCode::Key2
+            | KeyCode::Key3
+            | KeyCode::Key4
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::C
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::B
+            | KeyCode::Tab
+            | KeyCode::M
+            | KeyCode::F1 => SceneAction::Forward,
+
             _ => SceneAction::None,
         }
     }
 
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) -> SceneAction {
-        SceneAction::None
-    }
     fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) -> SceneAction {
         SceneAction::None
     }
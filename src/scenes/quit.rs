@@ -82,9 +82,6 @@ impl Scene for Quit {
 
         action
     }
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) -> SceneAction {
-        SceneAction::None
-    }
     fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) -> SceneAction {
         SceneAction::None
     }
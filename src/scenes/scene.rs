@@ -1,4 +1,4 @@
-use ggez::event::{KeyCode, KeyMods};
+use ggez::event::{KeyCode, KeyMods, MouseButton};
 use ggez::{Context, GameResult};
 
 // A scene represents a screen to be drawn. This can be something like a loading screen, a menu, the game itself, etc
@@ -16,15 +16,47 @@ pub trait Scene {
         keymods: KeyMods,
         repeat: bool,
     ) -> SceneAction;
-    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) -> SceneAction;
+    // Mouse input goes to whatever's underneath unless a scene wants it
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) -> SceneAction {
+        SceneAction::Forward
+    }
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> SceneAction {
+        SceneAction::Forward
+    }
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> SceneAction {
+        SceneAction::Forward
+    }
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        _x: f32,
+        _y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> SceneAction {
+        SceneAction::Forward
+    }
     fn resize_event(&mut self, ctx: &mut Context, width: f32, height: f32) -> SceneAction;
 
+    #[allow(clippy::wrong_self_convention)]
     fn from_scene(&mut self, kind: SceneType);
     fn to_scene(&mut self, kind: SceneType);
 }
 
 // The list of unique, valid scene types
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SceneType {
     Title,
     Game,
@@ -40,5 +72,6 @@ pub enum SceneAction {
     Pop,
     Push(Box<dyn Scene>),
     PopAndPush(Box<dyn Scene>),
+    Forward, // Let the scene underneath handle the event instead, for overlays like the pause screen
     None,
 }
@@ -1,16 +1,73 @@
 use super::scene::*;
+use crate::preferences::Preferences;
 
 use ggez::event::{KeyCode, KeyMods};
-use ggez::{Context, GameResult};
+use ggez::graphics::{Color, DrawMode, DrawParam, Font, PxScale, Text, TextFragment};
+use ggez::{graphics, Context, GameResult};
 
-pub struct Settings {}
+type Point2 = glam::Vec2;
+
+pub struct Settings {
+    preferences: Preferences, // What's being changed, saved as soon as anything is
+}
+
+impl Settings {
+    pub fn new(ctx: &mut Context) -> Settings {
+        Settings {
+            preferences: Preferences::load(ctx),
+        }
+    }
+}
 
 impl Scene for Settings {
     fn get_type(&self) -> SceneType {
         SceneType::Settings
     }
 
-    fn draw(&self, _ctx: &mut Context) -> GameResult<()> {
+    fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+        let (screen_width, screen_height) = graphics::drawable_size(ctx);
+        let screen_rect = graphics::Rect::new(0.0, 0.0, screen_width, screen_height);
+        let mesh = graphics::Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            screen_rect,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        )?;
+        graphics::draw(ctx, &mesh, DrawParam::default())?;
+
+        let settings_font = Font::new(ctx, "/fonts/Moonhouse-yE5M.ttf")?;
+        let settings_display = Text::new(
+            TextFragment::new("SETTINGS")
+                .font(settings_font)
+                .scale(PxScale::from(80.0)),
+        );
+        graphics::queue_text(
+            ctx,
+            &settings_display,
+            Point2::new(10.0, 0.0),
+            Some(Color::WHITE),
+        );
+        let height = settings_display.height(ctx);
+
+        let options = Text::new(format!(
+            "Renderer: {} (R for another)\n\nPress Escape to go back.",
+            self.preferences.renderer
+        ));
+        graphics::queue_text(
+            ctx,
+            &options,
+            Point2::new(10.0, height + 10.0),
+            Some(Color::new(1.0, 1.0, 0.0, 1.0)),
+        );
+
+        // Render all queued text
+        graphics::draw_queued_text(
+            ctx,
+            DrawParam::default(),
+            None,
+            graphics::FilterMode::Linear,
+        )?;
+
         Ok(())
     }
 
@@ -20,15 +77,26 @@ impl Scene for Settings {
 
     fn key_down_event(
         &mut self,
-        _ctx: &mut Context,
-        _keycode: KeyCode,
+        ctx: &mut Context,
+        keycode: KeyCode,
         _keymods: KeyMods,
-        _repeat: bool,
+        repeat: bool,
     ) -> SceneAction {
-        SceneAction::None
-    }
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) -> SceneAction {
-        SceneAction::None
+        match keycode {
+            // Back to wherever we came from
+            KeyCode::Escape if !repeat => SceneAction::Pop,
+
+            // Draw the station a different way
+            KeyCode::R if !repeat => {
+                self.preferences.renderer = self.preferences.renderer.next();
+                if let Err(e) = self.preferences.save(ctx) {
+                    println!("Couldn't save preferences: {}", e);
+                }
+                SceneAction::None
+            }
+
+            _ => SceneAction::None,
+        }
     }
     fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) -> SceneAction {
         SceneAction::None
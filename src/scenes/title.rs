@@ -2,6 +2,10 @@ use super::game::*;
 use super::load::*;
 use super::quit::*;
 use super::scene::*;
+use super::settings::*;
+use crate::replay::*;
+use crate::seed::GameSeed;
+use crate::station::generator::GeneratorType;
 
 use ggez::event::{KeyCode, KeyMods};
 use ggez::graphics::{Color, DrawParam, Font, PxScale, Text, TextFragment};
@@ -9,7 +13,19 @@ use ggez::{graphics, Context, GameResult};
 
 type Point2 = glam::Vec2;
 
-pub struct Title {}
+pub struct Title {
+    seed: GameSeed,           // The seed the next new game will be created from
+    generator: GeneratorType, // How the next new game's station will be laid out
+}
+
+impl Title {
+    pub fn new(seed: GameSeed) -> Title {
+        Title {
+            seed,
+            generator: GeneratorType::Cellular,
+        }
+    }
+}
 
 impl Scene for Title {
     fn get_type(&self) -> SceneType {
@@ -30,8 +46,10 @@ impl Scene for Title {
         let height = title.height(ctx);
 
         // Instructions
-        let instructions =
-            Text::new("Press N for a new game, L to load a previous save.".to_string());
+        let instructions = Text::new(format!(
+            "Press N for a new game, L to load a previous save, P to play back the last replay, S for settings.\nSeed: {} (R for a new one)\nStation layout: {} (G for another)",
+            self.seed, self.generator
+        ));
         graphics::queue_text(
             ctx,
             &instructions,
@@ -92,7 +110,37 @@ impl Scene for Title {
             // Create a new game
             KeyCode::N if !repeat => {
                 println!("Creating new game");
-                SceneAction::PopAndPush(Box::new(Game::new(ctx)))
+                SceneAction::PopAndPush(Box::new(Game::new(ctx, self.seed, self.generator)))
+            }
+
+            // Play back the most recent replay
+            KeyCode::P if !repeat => match list_replays(ctx).unwrap_or_default().last() {
+                Some(filename) => match Replay::load(ctx, filename) {
+                    Ok(replay) => {
+                        println!("Playing back {}", filename.display());
+                        SceneAction::PopAndPush(Box::new(Game::from_replay(ctx, replay)))
+                    }
+                    Err(e) => {
+                        println!("Couldn't load {}: {}", filename.display(), e);
+                        SceneAction::None
+                    }
+                },
+                None => {
+                    println!("No replays to play back");
+                    SceneAction::None
+                }
+            },
+
+            // Pick a different seed for the next new game
+            KeyCode::R if !repeat => {
+                self.seed = GameSeed::random();
+                SceneAction::None
+            }
+
+            // Pick a different way of laying out the next new game's station
+            KeyCode::G if !repeat => {
+                self.generator = self.generator.next();
+                SceneAction::None
             }
 
             // Load a game
@@ -101,13 +149,13 @@ impl Scene for Title {
                 SceneAction::Push(Box::new(Load {}))
             }
 
+            // Change how the game looks
+            KeyCode::S if !repeat => SceneAction::Push(Box::new(Settings::new(ctx))),
+
             // Everything else does nothing
             _ => SceneAction::None,
         }
     }
-    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, _y: f32) -> SceneAction {
-        SceneAction::None
-    }
     fn resize_event(&mut self, _ctx: &mut Context, _width: f32, _height: f32) -> SceneAction {
         SceneAction::None
     }
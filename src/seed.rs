@@ -0,0 +1,112 @@
+use oorandom::Rand32;
+use serde::{Deserialize, Serialize};
+use uuid::{Builder, Uuid};
+
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+// The seed a game is created from. Every random draw in a game comes from this,
+// so the same seed always produces the same station and crew
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub struct GameSeed(u64);
+
+// Independent random streams derived from a seed, so that drawing more stars
+// doesn't change what station gets generated
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum RngStream {
+    Simulation = 1,
+    Starfield = 2,
+    Music = 3,
+}
+
+impl GameSeed {
+    pub fn new(value: u64) -> GameSeed {
+        GameSeed(value)
+    }
+
+    // Pick a brand new seed from system entropy
+    pub fn random() -> GameSeed {
+        let mut seed: [u8; 8] = [0; 8];
+        getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
+        GameSeed(u64::from_ne_bytes(seed))
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    // Create a random-number generator for one of the streams of this seed
+    pub fn rng(&self, stream: RngStream) -> Rand32 {
+        Rand32::new_inc(self.0, stream as u64)
+    }
+}
+
+impl fmt::Display for GameSeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for GameSeed {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(GameSeed(s.trim().parse()?))
+    }
+}
+
+// Create a unique id from a random-number generator, so that ids are reproducible too
+pub fn new_id(rng: &mut Rand32) -> Uuid {
+    let mut bytes = [0; 16];
+    for chunk in bytes.chunks_mut(4) {
+        chunk.copy_from_slice(&rng.rand_u32().to_le_bytes());
+    }
+    Builder::from_random_bytes(bytes).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{new_id, GameSeed, RngStream};
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let seed = GameSeed::new(1234);
+        let mut a = seed.rng(RngStream::Simulation);
+        let mut b = seed.rng(RngStream::Simulation);
+        for _ in 0..100 {
+            assert_eq!(a.rand_u32(), b.rand_u32());
+        }
+
+        let mut starfield = seed.rng(RngStream::Starfield);
+        assert_ne!(
+            a.rand_u32(),
+            starfield.rand_u32(),
+            "Streams are independent"
+        );
+    }
+
+    #[test]
+    fn new_id_is_reproducible() {
+        let seed = GameSeed::new(1234);
+        let mut a = seed.rng(RngStream::Simulation);
+        let mut b = seed.rng(RngStream::Simulation);
+        assert_eq!(new_id(&mut a), new_id(&mut b));
+        assert_ne!(new_id(&mut a), new_id(&mut a), "Ids are still unique");
+
+        // And the same on every machine, whichever way round it keeps its bytes
+        let mut c = seed.rng(RngStream::Simulation);
+        assert_eq!(
+            "8067fc1d-d389-4b87-9799-196d4fbfa65b",
+            new_id(&mut c).to_string()
+        );
+    }
+
+    #[test]
+    fn parse_seed() {
+        let seed: GameSeed = "1234".parse().unwrap();
+        assert_eq!(1234, seed.value());
+        assert_eq!("1234", seed.to_string());
+        assert!("not a seed".parse::<GameSeed>().is_err());
+    }
+}
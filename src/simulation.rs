@@ -0,0 +1,598 @@
+use crate::events::*;
+use crate::inhabitant::{Inhabitant, InhabitantType};
+use crate::jobs::{Job, Jobs};
+use crate::seed::*;
+use crate::station::build::BuildTool;
+use crate::station::generator::StationGenerator;
+use crate::station::gridposition::GridPosition;
+use crate::station::station::*;
+use crate::station::tile::*;
+use crate::station::tiled::{self, TiledError};
+
+use oorandom::Rand32;
+
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+type Point2 = glam::Vec2;
+
+// How many times per second the simulation advances
+pub const TICKS_PER_SECOND: u32 = 60;
+
+// The fixed amount of time a single tick covers
+pub const TICK_DURATION: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND as u64);
+
+// FNV-1a, which unlike the standard library's hasher is the same between runs and platforms
+// Numbers are always fed in little-endian and 64 bits wide, so it doesn't matter what the machine would do
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// The Simulation owns everything that changes over time in a game, and knows nothing about windows or rendering
+// Scenes drive it by calling `tick` and draw whatever is inside it
+pub struct Simulation {
+    pub station: Station,
+    pub inhabitants: Vec<Inhabitant>,
+    pub jobs: Jobs,       // Construction waiting to be done
+    pub events: EventBus, // Everything that happens gets published here, and dispatched at the end of each tick
+    seed: GameSeed,       // What the whole simulation was generated from
+    rng: Rand32,
+    ticks: u64, // How many ticks have run since this simulation was created
+}
+
+impl Simulation {
+    // Generate a new station and crew from a seed. The same seed always makes the same simulation
+    pub fn new(
+        seed: GameSeed,
+        station_pos: Point2,
+        generator: &dyn StationGenerator,
+        num_crew: usize,
+    ) -> Simulation {
+        let mut rng = seed.rng(RngStream::Simulation);
+        let mut events = EventBus::new();
+        let station = Station::new(station_pos, generator, &mut rng, &mut events);
+
+        let mut sim = Simulation {
+            station,
+            inhabitants: Vec::with_capacity(num_crew),
+            jobs: Jobs::new(),
+            events,
+            seed,
+            rng,
+            ticks: 0,
+        };
+
+        // Put some people in it
+        for _ in 0..num_crew {
+            // TODO: Don't repeat inhabitant types
+            sim.add_random_inhabitant();
+        }
+
+        sim
+    }
+
+    // Start a simulation on a station drawn in Tiled, with a crew member at each spawn point
+    pub fn from_tiled(
+        seed: GameSeed,
+        station_pos: Point2,
+        json: &str,
+    ) -> Result<Simulation, TiledError> {
+        let mut rng = seed.rng(RngStream::Simulation);
+        let (station, spawns) = tiled::import(station_pos, json, &mut rng)?;
+
+        let mut sim = Simulation {
+            station,
+            inhabitants: Vec::with_capacity(spawns.len()),
+            jobs: Jobs::new(),
+            events: EventBus::new(),
+            seed,
+            rng,
+            ticks: 0,
+        };
+
+        for spawn in spawns {
+            let kind = match spawn.kind {
+                Some(kind) => kind,
+                None => sim.get_random_inhabitant_type(),
+            };
+            sim.add_inhabitant(spawn.pos, kind);
+        }
+
+        Ok(sim)
+    }
+
+    // Rebuild a simulation from previously saved parts
+    pub fn from_saved(
+        seed: GameSeed,
+        station: Station,
+        inhabitants: Vec<Inhabitant>,
+        jobs: Jobs,
+        rng_state: (u64, u64),
+    ) -> Simulation {
+        Simulation {
+            station,
+            inhabitants,
+            jobs,
+            events: EventBus::new(),
+            seed,
+            rng: Rand32::from_state(rng_state),
+            ticks: 0,
+        }
+    }
+
+    // Advance the simulation by one fixed step of `dt`
+    pub fn tick(&mut self, dt: Duration) {
+        // Update the station
+        self.station.update(dt);
+
+        // Fires break out and spread, and every one of them needs someone to put it out
+        self.station
+            .update_fires(dt, &mut self.rng, &mut self.events);
+        self.jobs.update_fires(self.station.get_fires(), dt);
+
+        // Micrometeorites wear the hull down, and every hole they make needs an Engineer to patch it
+        self.station
+            .update_hull(dt, &mut self.rng, &mut self.events);
+        let breaches: Vec<GridPosition> = self.station.get_breaches().collect();
+        for pos in breaches {
+            self.plan_build(pos, BuildTool::Repair);
+        }
+
+        // Update and move the inhabitants
+        for inhabitant in &mut self.inhabitants {
+            inhabitant.update(
+                dt,
+                &mut self.station,
+                &mut self.jobs,
+                &mut self.rng,
+                &mut self.events,
+            );
+        }
+
+        // Build whatever's been finished
+        self.finish_jobs();
+
+        self.ticks += 1;
+
+        // Let everyone know what happened
+        self.events.dispatch();
+    }
+
+    // How many ticks have been run
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn seed(&self) -> GameSeed {
+        self.seed
+    }
+
+    // A fingerprint of the whole simulation state, used to notice when replays diverge
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = Fnv::new();
+        self.station.hash_state(&mut hasher);
+        self.inhabitants.len().hash(&mut hasher);
+        for inhabitant in self.inhabitants.iter() {
+            inhabitant.hash_state(&mut hasher);
+        }
+        for job in self.jobs.iter() {
+            job.pos.hash(&mut hasher);
+            job.tool.hash(&mut hasher);
+            job.materials_delivered.hash(&mut hasher);
+            job.work_left.to_bits().hash(&mut hasher);
+            job.assigned.hash(&mut hasher);
+        }
+        self.rng.state().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // The current state of the random-number generator, for saving
+    pub fn rng_state(&self) -> (u64, u64) {
+        self.rng.state()
+    }
+
+    // Can the player build this here right now? The same as `Station::can_build`, except nobody gets walled in
+    // and there's only one job per tile
+    pub fn can_build(&self, pos: GridPosition, tool: BuildTool) -> Result<(), &'static str> {
+        self.station.can_build(pos, tool)?;
+        if self.jobs.get(pos).is_some() {
+            return Err("Already planned");
+        }
+
+        let closes_off = match tool {
+            BuildTool::Wall => true,
+            BuildTool::Remove => self
+                .station
+                .get_tile(pos)
+                .is_some_and(|tile| tile.items.is_empty()),
+            _ => false,
+        };
+        let occupied = self.inhabitants.iter().any(|inhabitant| {
+            self.station
+                .get_tile_from_world(inhabitant.pos, inhabitant.deck)
+                .is_some_and(|tile| tile.pos == pos)
+        });
+        if closes_off && occupied {
+            return Err("Someone is in the way");
+        }
+
+        Ok(())
+    }
+
+    // Put down a blueprint for an Engineer to build, if it can be built. Returns whether it was
+    pub fn plan_build(&mut self, pos: GridPosition, tool: BuildTool) -> bool {
+        if self.can_build(pos, tool).is_err() {
+            return false;
+        }
+        self.events.publish(SimEvent::JobPosted { pos, tool });
+        self.jobs.add(Job::new(pos, tool))
+    }
+
+    // Turn finished jobs into the real thing
+    // If someone's standing in the way it waits for them to move, and if it can't be built at all any more it's dropped
+    fn finish_jobs(&mut self) {
+        for pos in self.jobs.done() {
+            let job = self.jobs.remove(pos).unwrap();
+            match self.can_build(pos, job.tool) {
+                Ok(()) => {
+                    self.station
+                        .build(pos, job.tool, &mut self.rng, &mut self.events);
+
+                    // Anyone who was going to walk through here has to find another way
+                    for inhabitant in self.inhabitants.iter_mut() {
+                        if inhabitant.is_headed_through(pos) {
+                            inhabitant.stop();
+                        }
+                    }
+                }
+                Err(_) if self.station.can_build(pos, job.tool).is_ok() => {
+                    self.jobs.add(job);
+                }
+                Err(reason) => self.events.publish(SimEvent::JobCancelled {
+                    pos,
+                    tool: job.tool,
+                    reason,
+                }),
+            }
+        }
+    }
+
+    // Add an inhabitant to the simulation, standing on a tile
+    pub fn add_inhabitant(&mut self, pos: GridPosition, kind: InhabitantType) {
+        let world_pos = self
+            .station
+            .get_tile(pos)
+            .unwrap()
+            .to_world_position(&self.station);
+        let mut inhabitant = Inhabitant::new(world_pos, kind, &mut self.rng);
+        inhabitant.deck = pos.z;
+        self.events.publish(SimEvent::InhabitantAdded {
+            inhabitant: inhabitant.get_id(),
+            kind,
+            pos,
+        });
+        self.inhabitants.push(inhabitant);
+    }
+
+    // Add an inhabitant of a random type on a random floor tile
+    pub fn add_random_inhabitant(&mut self) {
+        let pos = self
+            .station
+            .get_random_tile(TileType::Floor, &mut self.rng)
+            .unwrap()
+            .pos;
+        let inhabitant_type = self.get_random_inhabitant_type();
+        self.add_inhabitant(pos, inhabitant_type);
+    }
+
+    fn get_random_inhabitant_type(&mut self) -> InhabitantType {
+        // TODO: Got to be a better way to do this
+        match self.rng.rand_range(0..6) {
+            0 => InhabitantType::Pilot,
+            1 => InhabitantType::Engineer,
+            2 => InhabitantType::Scientist,
+            3 => InhabitantType::Medic,
+            4 => InhabitantType::Soldier,
+            5 => InhabitantType::Miner,
+            6 => InhabitantType::Cook,
+            _ => panic!("Invalid inhabitant type chosen"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GameSeed, Point2, Simulation, TICKS_PER_SECOND, TICK_DURATION};
+    use crate::inhabitant::InhabitantType;
+    use crate::item::{get_material_types, ContainerType, ItemType};
+    use crate::jobs::material_cost;
+    use crate::station::atmosphere::Gas;
+    use crate::station::build::BuildTool;
+    use crate::station::generator::CellularAutomaton;
+    use crate::station::gridposition::GridPosition;
+    use crate::station::hull::MAX_INTEGRITY;
+    use crate::station::station::Station;
+    use crate::station::tile::TileType;
+    use oorandom::Rand32;
+
+    // Function to make a small generated simulation, used in tests
+    fn test_simulation(num_crew: usize) -> Simulation {
+        Simulation::new(
+            GameSeed::new(42),
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            num_crew,
+        )
+    }
+
+    #[test]
+    fn tick_counts() {
+        let mut sim = test_simulation(0);
+        for _ in 0..10 {
+            sim.tick(TICK_DURATION);
+        }
+        assert_eq!(10, sim.ticks());
+    }
+
+    #[test]
+    fn tick_headless() {
+        let mut sim = test_simulation(3);
+        assert_eq!(3, sim.inhabitants.len());
+
+        // Ten in-game minutes, without a window
+        for _ in 0..(TICKS_PER_SECOND * 60 * 10) {
+            sim.tick(TICK_DURATION);
+        }
+
+        for inhabitant in &sim.inhabitants {
+            assert!(
+                sim.station
+                    .get_tile_from_world(inhabitant.pos, inhabitant.deck)
+                    .is_some(),
+                "Inhabitants stay on the station"
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_same_crew() {
+        let mut a = test_simulation(3);
+        let mut b = test_simulation(3);
+        assert_eq!(
+            serde_cbor::to_vec(&a.inhabitants).unwrap(),
+            serde_cbor::to_vec(&b.inhabitants).unwrap(),
+            "The same seed makes the same crew"
+        );
+
+        // And they keep doing the same things
+        for _ in 0..(TICKS_PER_SECOND * 60) {
+            a.tick(TICK_DURATION);
+            b.tick(TICK_DURATION);
+        }
+        assert_eq!(
+            serde_cbor::to_vec(&a.inhabitants).unwrap(),
+            serde_cbor::to_vec(&b.inhabitants).unwrap(),
+            "The same seed runs the same way"
+        );
+    }
+
+    #[test]
+    fn checksum_notices_changes() {
+        let mut a = test_simulation(3);
+        let b = test_simulation(3);
+        assert_eq!(a.checksum(), b.checksum());
+
+        let pos = a
+            .station
+            .get_random_tile(TileType::Floor, &mut Rand32::new(1))
+            .unwrap()
+            .pos;
+        *a.station.get_gas_mut(pos).unwrap() = Gas::vacuum();
+        assert_ne!(a.checksum(), b.checksum(), "The air changed");
+    }
+
+    #[test]
+    fn build_wont_wall_anyone_in() {
+        let mut sim = test_simulation(1);
+        let pos = sim
+            .station
+            .get_tile_from_world(sim.inhabitants[0].pos, 0)
+            .unwrap()
+            .pos;
+        assert_eq!(
+            Err("Someone is in the way"),
+            sim.can_build(pos, BuildTool::Wall)
+        );
+        assert!(!sim.plan_build(pos, BuildTool::Wall));
+    }
+
+    #[test]
+    fn build_across_a_path() {
+        let mut sim = test_simulation(0);
+        let mut rng = Rand32::new(1);
+        sim.station =
+            Station::from_ascii(Point2::zero(), "#########\n#.......#\n#########", &mut rng)
+                .unwrap();
+        sim.add_inhabitant(GridPosition::new(1, 1), InhabitantType::Pilot);
+        sim.inhabitants[0].set_destination(&sim.station, GridPosition::new(7, 1), &mut sim.events);
+        assert!(sim.inhabitants[0].is_headed_through(GridPosition::new(5, 1)));
+
+        // Finish a wall right across the way
+        let wall = GridPosition::new(5, 1);
+        assert!(sim.plan_build(wall, BuildTool::Wall));
+        let job = sim.jobs.get_mut(wall).unwrap();
+        job.materials_delivered = job.materials_needed;
+        job.work_left = 0.0;
+        sim.finish_jobs();
+        assert!(!sim.station.get_tile(wall).unwrap().is_walkable());
+        assert_eq!(None, sim.inhabitants[0].dest, "Has to find another way");
+
+        // And there isn't one, so nobody walks through the wall
+        for _ in 0..(TICKS_PER_SECOND * 10) {
+            sim.tick(TICK_DURATION);
+            let inhabitant = &sim.inhabitants[0];
+            let here = sim.station.grid_position_from_world(inhabitant.pos, 0);
+            assert!(here.x < wall.x, "Stayed on this side of the wall");
+        }
+    }
+
+    // Put someone on the station and plan some furniture a little way away from them
+    fn test_job(kind: InhabitantType) -> (Simulation, GridPosition) {
+        let mut sim = test_simulation(0);
+        let mut rng = Rand32::new(1);
+        let start = sim
+            .station
+            .get_random_tile(TileType::Floor, &mut rng)
+            .unwrap()
+            .pos;
+        sim.add_inhabitant(start, kind);
+        let pos = loop {
+            let tile = sim
+                .station
+                .get_random_tile(TileType::Floor, &mut rng)
+                .unwrap();
+            if tile.items.is_empty() && tile.pos != start {
+                break tile.pos;
+            }
+        };
+        assert!(sim.plan_build(pos, BuildTool::Furniture(ContainerType::Fridge)));
+        assert!(
+            !sim.station
+                .get_tile(pos)
+                .unwrap()
+                .has_item(vec![ItemType::Container(ContainerType::Fridge)]),
+            "Nothing happens straight away"
+        );
+        (sim, pos)
+    }
+
+    #[test]
+    fn engineers_build_jobs() {
+        let (mut sim, pos) = test_job(InhabitantType::Engineer);
+        let materials = sim.station.count_items(get_material_types());
+
+        // Give them ten in-game minutes to get it done
+        for _ in 0..(TICKS_PER_SECOND * 60 * 10) {
+            sim.tick(TICK_DURATION);
+            if sim.jobs.is_empty() {
+                break;
+            }
+        }
+
+        assert!(sim.jobs.is_empty(), "The job got done");
+        assert!(sim
+            .station
+            .get_tile(pos)
+            .unwrap()
+            .has_item(vec![ItemType::Container(ContainerType::Fridge)]));
+        assert_eq!(
+            materials - material_cost(BuildTool::Furniture(ContainerType::Fridge)) as usize,
+            sim.station.count_items(get_material_types()),
+            "Materials came out of the locker"
+        );
+    }
+
+    #[test]
+    fn only_engineers_build() {
+        let (mut sim, _pos) = test_job(InhabitantType::Cook);
+        for _ in 0..(TICKS_PER_SECOND * 60) {
+            sim.tick(TICK_DURATION);
+        }
+        let job = sim.jobs.iter().next().unwrap();
+        assert_eq!(None, job.assigned);
+        assert_eq!(0, job.materials_delivered);
+    }
+
+    #[test]
+    fn engineers_patch_breaches() {
+        let mut sim = test_simulation(0);
+        let mut rng = Rand32::new(1);
+        let (start, pos) = loop {
+            let tile = sim
+                .station
+                .get_random_tile(TileType::Floor, &mut rng)
+                .unwrap();
+            let hull = sim
+                .station
+                .get_neighbors(tile.pos)
+                .values()
+                .map(|neighbor| neighbor.pos)
+                .find(|pos| sim.station.is_hull(*pos));
+            if let Some(hull) = hull {
+                break (tile.pos, hull);
+            }
+        };
+        sim.add_inhabitant(start, InhabitantType::Engineer);
+        sim.station.damage_wall(pos, MAX_INTEGRITY, &mut sim.events);
+        assert!(sim.station.get_tile(pos).unwrap().is_breached());
+
+        sim.tick(TICK_DURATION);
+        assert_eq!(
+            Some(BuildTool::Repair),
+            sim.jobs.get(pos).map(|job| job.tool),
+            "Somebody needs to fix that"
+        );
+
+        for _ in 0..(TICKS_PER_SECOND * 60 * 10) {
+            sim.tick(TICK_DURATION);
+            if !sim.station.get_tile(pos).unwrap().is_breached() {
+                break;
+            }
+        }
+        assert!(!sim.station.get_tile(pos).unwrap().is_breached(), "Patched");
+        assert_eq!(MAX_INTEGRITY, sim.station.get_tile(pos).unwrap().integrity);
+    }
+
+    #[test]
+    fn from_tiled() {
+        let json = include_str!("../resources/stations/outpost.json");
+        let sim = Simulation::from_tiled(GameSeed::new(42), Point2::zero(), json).unwrap();
+        let kinds: Vec<InhabitantType> = sim
+            .inhabitants
+            .iter()
+            .map(|inhabitant| inhabitant.get_kind())
+            .collect();
+        assert_eq!(3, kinds.len(), "One for each spawn point");
+        assert_eq!(InhabitantType::Engineer, kinds[0]);
+        assert_eq!(InhabitantType::Cook, kinds[1]);
+
+        let again = Simulation::from_tiled(GameSeed::new(42), Point2::zero(), json).unwrap();
+        assert_eq!(sim.checksum(), again.checksum());
+    }
+}
@@ -13,11 +13,7 @@ pub struct Starfield {
 }
 
 impl Starfield {
-    pub fn new(ctx: &mut Context) -> Starfield {
-        // Create a seeded random-number generator
-        let mut seed: [u8; 8] = [0; 8];
-        getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
-        let rng = Rand32::new(u64::from_ne_bytes(seed));
+    pub fn new(ctx: &mut Context, rng: Rand32) -> Starfield {
         let (screen_width, screen_height) = graphics::drawable_size(ctx);
 
         let mut s = Starfield {
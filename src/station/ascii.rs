@@ -0,0 +1,348 @@
+use super::gridposition::GridPosition;
+use super::tile::{DoorState, LiftType};
+use crate::item::{ContainerType, FixtureType, PowerGeneratorType, ToolType};
+
+use strum::IntoEnumIterator;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+// A station map drawn as plain text, one character per tile, e.g.
+//
+//   # = wall
+//   . = floor
+//   F = fridge
+//
+//   #####
+//   #.F.#
+//   #####
+//
+// The legend at the top is optional, and anything it doesn't mention falls back to the default symbols
+// It ends at the first blank line. Blank lines before the map are skipped, so fixtures can start on a new line
+// Stations with more than one deck have them one after the other, bottom first, with a `---` line between each
+
+// The line between one deck and the next
+const DECK_SEPARATOR: &str = "---";
+
+// What a character in the map stands for
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Symbol {
+    Space,
+    Floor,
+    Wall,
+    Door(DoorState),
+    Furniture(ContainerType),      // Sits on a floor tile
+    Generator(PowerGeneratorType), // So does this
+    Fixture(FixtureType),          // And this
+    Tool(ToolType),                // And this, lying on the floor
+    Conduit,                       // Floor with power cables under it
+    Lift(LiftType),                // Floor with a way up and down to the decks above and below
+}
+
+impl Symbol {
+    // Every symbol there is, with the character it has when there's no legend
+    pub fn defaults() -> Vec<(char, Symbol)> {
+        let mut symbols = vec![
+            (' ', Symbol::Space),
+            ('.', Symbol::Floor),
+            ('#', Symbol::Wall),
+            ('+', Symbol::Door(DoorState::Closed)),
+            ('/', Symbol::Door(DoorState::Open)),
+            ('x', Symbol::Door(DoorState::Locked)),
+            ('%', Symbol::Door(DoorState::Broken)),
+        ];
+        for kind in ContainerType::iter() {
+            let c = match kind {
+                ContainerType::Fridge => 'F',
+                ContainerType::Locker => 'L',
+            };
+            symbols.push((c, Symbol::Furniture(kind)));
+        }
+        for kind in PowerGeneratorType::iter() {
+            let c = match kind {
+                PowerGeneratorType::SolarPanel => 'S',
+                PowerGeneratorType::Reactor => 'R',
+            };
+            symbols.push((c, Symbol::Generator(kind)));
+        }
+        for kind in FixtureType::iter() {
+            let c = match kind {
+                FixtureType::Light => '*',
+                FixtureType::Heater => 'H',
+                FixtureType::Cooler => 'C',
+            };
+            symbols.push((c, Symbol::Fixture(kind)));
+        }
+        for kind in ToolType::iter() {
+            let c = match kind {
+                ToolType::Extinguisher => 'E',
+            };
+            symbols.push((c, Symbol::Tool(kind)));
+        }
+        symbols.push(('=', Symbol::Conduit));
+        for kind in LiftType::iter() {
+            let c = match kind {
+                LiftType::Ladder => '|',
+                LiftType::Elevator => '^',
+            };
+            symbols.push((c, Symbol::Lift(kind)));
+        }
+        symbols
+    }
+
+    // Look a symbol up by the name it has in a legend
+    pub fn from_name(name: &str) -> Option<Symbol> {
+        Symbol::defaults()
+            .into_iter()
+            .map(|(_c, symbol)| symbol)
+            .find(|symbol| symbol.to_string() == name)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Symbol::Space => write!(f, "space"),
+            Symbol::Floor => write!(f, "floor"),
+            Symbol::Wall => write!(f, "wall"),
+            Symbol::Door(DoorState::Closed) => write!(f, "door"),
+            Symbol::Door(state) => write!(f, "{} door", format!("{:?}", state).to_lowercase()),
+            Symbol::Furniture(kind) => write!(f, "{}", format!("{:?}", kind).to_lowercase()),
+            Symbol::Generator(kind) => write!(f, "{}", format!("{:?}", kind).to_lowercase()),
+            Symbol::Fixture(kind) => write!(f, "{}", format!("{:?}", kind).to_lowercase()),
+            Symbol::Tool(kind) => write!(f, "{}", format!("{:?}", kind).to_lowercase()),
+            Symbol::Conduit => write!(f, "conduit"),
+            Symbol::Lift(kind) => write!(f, "{}", format!("{:?}", kind).to_lowercase()),
+        }
+    }
+}
+
+// What each character in a map means
+#[derive(Clone, Debug)]
+pub struct Legend {
+    symbols: BTreeMap<char, Symbol>,
+}
+
+impl Default for Legend {
+    fn default() -> Legend {
+        Legend {
+            symbols: Symbol::defaults().into_iter().collect(),
+        }
+    }
+}
+
+impl Legend {
+    pub fn get(&self, c: char) -> Option<Symbol> {
+        self.symbols.get(&c).copied()
+    }
+
+    // The character drawn for a symbol, if the legend has one
+    pub fn char_for(&self, symbol: Symbol) -> Option<char> {
+        self.symbols
+            .iter()
+            .find(|(_c, s)| **s == symbol)
+            .map(|(c, _s)| *c)
+    }
+
+    // Point a character at a symbol, replacing whatever it meant before
+    pub fn set(&mut self, c: char, symbol: Symbol) {
+        self.symbols.retain(|_c, s| *s != symbol);
+        self.symbols.insert(c, symbol);
+    }
+}
+
+// Why a map couldn't be read
+#[derive(Clone, Debug, PartialEq)]
+pub enum AsciiError {
+    BadLegend(usize), // The line number of a legend entry that doesn't make sense
+    UnknownSymbol(char, GridPosition), // A character in the map that isn't in the legend
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsciiError::BadLegend(line) => write!(f, "Bad legend entry on line {}", line),
+            AsciiError::UnknownSymbol(c, pos) => write!(f, "Unknown symbol '{}' at {}", c, pos),
+        }
+    }
+}
+
+// A legend line looks like `# = wall`
+fn parse_legend_line(line: &str) -> Option<(char, Symbol)> {
+    let mut chars = line.chars();
+    let c = chars.next()?;
+    let name = chars.as_str().strip_prefix(" = ")?;
+    Some((c, Symbol::from_name(name.trim())?))
+}
+
+// Read a map into the symbol at every position that isn't empty space
+pub fn parse(text: &str) -> Result<BTreeMap<GridPosition, Symbol>, AsciiError> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .skip_while(|(_n, line)| line.trim().is_empty())
+        .peekable();
+
+    // Read the legend, if there is one
+    let mut legend = Legend::default();
+    let has_legend = lines
+        .peek()
+        .is_some_and(|(_n, line)| parse_legend_line(line).is_some());
+    if has_legend {
+        for (n, line) in lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            let (c, symbol) = parse_legend_line(line).ok_or(AsciiError::BadLegend(n + 1))?;
+            legend.set(c, symbol);
+        }
+    }
+
+    let mut symbols = BTreeMap::new();
+    let (mut y, mut z) = (0, 0);
+    for (_n, line) in lines {
+        if line.trim() == DECK_SEPARATOR {
+            y = 0;
+            z += 1;
+            continue;
+        }
+        for (x, c) in line.chars().enumerate() {
+            let pos = GridPosition::on_deck(x as i32, y, z);
+            match legend.get(c) {
+                Some(Symbol::Space) => {}
+                Some(symbol) => {
+                    symbols.insert(pos, symbol);
+                }
+                None => return Err(AsciiError::UnknownSymbol(c, pos)),
+            }
+        }
+        y += 1;
+    }
+    Ok(symbols)
+}
+
+// Write out a map with the default symbols, and a legend for the ones it uses
+// The map starts from the top-left-most symbol, so it may be shifted from where things were
+pub fn write(symbols: &BTreeMap<GridPosition, Symbol>) -> String {
+    let legend = Legend::default();
+    let mut out = String::new();
+    if symbols.is_empty() {
+        return out;
+    }
+
+    for (c, symbol) in Symbol::defaults() {
+        if symbols.values().any(|s| *s == symbol) {
+            out.push_str(&format!("{} = {}\n", c, symbol));
+        }
+    }
+    out.push('\n');
+
+    let min_x = symbols.keys().map(|pos| pos.x).min().unwrap();
+    let max_x = symbols.keys().map(|pos| pos.x).max().unwrap();
+    let min_y = symbols.keys().map(|pos| pos.y).min().unwrap();
+    let max_y = symbols.keys().map(|pos| pos.y).max().unwrap();
+    let min_z = symbols.keys().map(|pos| pos.z).min().unwrap();
+    let max_z = symbols.keys().map(|pos| pos.z).max().unwrap();
+    for z in min_z..=max_z {
+        if z != min_z {
+            out.push_str(DECK_SEPARATOR);
+            out.push('\n');
+        }
+        for y in min_y..=max_y {
+            let row: String = (min_x..=max_x)
+                .map(|x| {
+                    let symbol = symbols
+                        .get(&GridPosition::on_deck(x, y, z))
+                        .copied()
+                        .unwrap_or(Symbol::Space);
+                    legend.char_for(symbol).unwrap()
+                })
+                .collect();
+            out.push_str(row.trim_end());
+            out.push('\n');
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, write, AsciiError, Symbol};
+    use crate::item::ContainerType;
+    use crate::station::gridposition::GridPosition;
+    use crate::station::tile::{DoorState, LiftType};
+
+    #[test]
+    fn symbol_names() {
+        for (_c, symbol) in Symbol::defaults() {
+            assert_eq!(Some(symbol), Symbol::from_name(&symbol.to_string()));
+        }
+        assert_eq!("locked door", Symbol::Door(DoorState::Locked).to_string());
+        assert_eq!(None, Symbol::from_name("airlock"));
+    }
+
+    #[test]
+    fn parse_map() {
+        let symbols = parse(
+            "
+###
+#F+
+ x",
+        )
+        .unwrap();
+        assert_eq!(7, symbols.len(), "Spaces are left out");
+        assert_eq!(Some(&Symbol::Wall), symbols.get(&GridPosition::new(0, 0)));
+        assert_eq!(
+            Some(&Symbol::Furniture(ContainerType::Fridge)),
+            symbols.get(&GridPosition::new(1, 1))
+        );
+        assert_eq!(
+            Some(&Symbol::Door(DoorState::Locked)),
+            symbols.get(&GridPosition::new(1, 2))
+        );
+    }
+
+    #[test]
+    fn parse_legend() {
+        let symbols = parse("o = floor\n~ = space\n\n~o~").unwrap();
+        assert_eq!(1, symbols.len());
+        assert_eq!(Some(&Symbol::Floor), symbols.get(&GridPosition::new(1, 0)));
+
+        assert_eq!(
+            Err(AsciiError::UnknownSymbol('.', GridPosition::new(0, 0))),
+            parse("o = floor\n\n.o"),
+            "Renamed symbols lose their old character"
+        );
+        assert_eq!(
+            Err(AsciiError::BadLegend(2)),
+            parse("o = floor\no = airlock\n\no")
+        );
+        assert_eq!(
+            Err(AsciiError::UnknownSymbol('?', GridPosition::new(1, 0))),
+            parse("#?#")
+        );
+    }
+
+    #[test]
+    fn write_round_trips() {
+        let text = ". = floor\n# = wall\nL = locker\n\n####\n#.L#\n####\n   #\n";
+        let symbols = parse(text).unwrap();
+        assert_eq!(text, write(&symbols));
+    }
+
+    #[test]
+    fn decks() {
+        let text = ". = floor\n# = wall\n| = ladder\n\n###\n#|#\n###\n---\n###\n#|.\n###\n";
+        let symbols = parse(text).unwrap();
+        assert_eq!(
+            Some(&Symbol::Lift(LiftType::Ladder)),
+            symbols.get(&GridPosition::on_deck(1, 1, 1)),
+            "Each deck starts again from the top"
+        );
+        assert_eq!(
+            Some(&Symbol::Floor),
+            symbols.get(&GridPosition::on_deck(2, 1, 1))
+        );
+        assert_eq!(text, write(&symbols));
+    }
+}
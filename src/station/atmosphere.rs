@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use std::ops::{Add, Mul, Sub};
+
+// How much air a tile holds, in cubic metres
+pub const TILE_VOLUME: f32 = 2.5;
+
+// Everything's kept at a comfortable room temperature, in kelvin
+pub const ROOM_TEMPERATURE: f32 = 293.15;
+
+// The gas constant, in J/(mol K)
+const GAS_CONSTANT: f32 = 8.314;
+
+// Sea-level air, in kPa
+pub const STANDARD_PRESSURE: f32 = 101.325;
+
+// How quickly gas evens out between neighbouring tiles, and rushes out into space, as a fraction per second
+pub const DIFFUSION_RATE: f32 = 2.0;
+pub const VENT_RATE: f32 = 1.0;
+
+// Air doesn't need updating every tick, so it moves in steps of this many seconds
+pub const ATMOSPHERE_STEP: f32 = 0.1;
+
+// Never move more than this fraction of a tile's gas over one side in one step, or the numbers blow up
+const MAX_FLOW: f32 = 0.2;
+
+// Once no more than this many moles of any gas move over a side in a step, the air has as good as stopped
+pub const SETTLED_FLOW: f32 = 1e-4;
+
+// The mix of gases in a tile, in moles
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Gas {
+    pub o2: f32,
+    pub co2: f32,
+    pub n2: f32,
+}
+
+impl Gas {
+    // Nothing at all
+    pub fn vacuum() -> Gas {
+        Gas::default()
+    }
+
+    // A tile full of fresh, Earth-like air
+    pub fn air() -> Gas {
+        let moles = moles_at(STANDARD_PRESSURE);
+        Gas {
+            o2: moles * 0.21,
+            co2: moles * 0.0004,
+            n2: moles * 0.7896,
+        }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.o2 + self.co2 + self.n2
+    }
+
+    // Total pressure, in kPa
+    pub fn pressure(&self) -> f32 {
+        pressure_of(self.total())
+    }
+
+    // The pressure of just the oxygen, in kPa, which is what matters for breathing
+    pub fn o2_pressure(&self) -> f32 {
+        pressure_of(self.o2)
+    }
+
+    // Too much of this is bad for you too
+    pub fn co2_pressure(&self) -> f32 {
+        pressure_of(self.co2)
+    }
+
+    // The most there is of any one gas, for telling how big a flow is when some gases go one way and some the other
+    pub fn largest(&self) -> f32 {
+        self.o2.abs().max(self.co2.abs()).max(self.n2.abs())
+    }
+
+    // How much would flow from here towards `other` over one side in `dt` seconds at `rate`
+    pub fn flow_to(&self, other: &Gas, rate: f32, dt: f32) -> Gas {
+        (*self - *other) * (rate * dt).min(MAX_FLOW)
+    }
+}
+
+impl Add for Gas {
+    type Output = Gas;
+
+    fn add(self, other: Gas) -> Gas {
+        Gas {
+            o2: self.o2 + other.o2,
+            co2: self.co2 + other.co2,
+            n2: self.n2 + other.n2,
+        }
+    }
+}
+
+impl Sub for Gas {
+    type Output = Gas;
+
+    fn sub(self, other: Gas) -> Gas {
+        Gas {
+            o2: self.o2 - other.o2,
+            co2: self.co2 - other.co2,
+            n2: self.n2 - other.n2,
+        }
+    }
+}
+
+impl Mul<f32> for Gas {
+    type Output = Gas;
+
+    fn mul(self, amount: f32) -> Gas {
+        Gas {
+            o2: self.o2 * amount,
+            co2: self.co2 * amount,
+            n2: self.n2 * amount,
+        }
+    }
+}
+
+// Ideal gas law, PV = nRT, for one tile
+fn pressure_of(moles: f32) -> f32 {
+    moles * GAS_CONSTANT * ROOM_TEMPERATURE / TILE_VOLUME / 1000.0
+}
+
+fn moles_at(pressure: f32) -> f32 {
+    pressure * 1000.0 * TILE_VOLUME / (GAS_CONSTANT * ROOM_TEMPERATURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gas, STANDARD_PRESSURE};
+
+    #[test]
+    fn air_pressure() {
+        let air = Gas::air();
+        assert!((air.pressure() - STANDARD_PRESSURE).abs() < 0.01);
+        assert!(
+            (air.o2_pressure() - 21.3).abs() < 0.1,
+            "About a fifth oxygen"
+        );
+        assert_eq!(0.0, Gas::vacuum().pressure());
+    }
+
+    #[test]
+    fn flow() {
+        let air = Gas::air();
+        let flow = air.flow_to(&Gas::vacuum(), 1.0, 0.1);
+        assert_eq!(air * 0.1, flow);
+        assert_eq!(
+            Gas::vacuum(),
+            air.flow_to(&air, 1.0, 0.1),
+            "Nothing moves when it's even"
+        );
+        assert_eq!(
+            air * 0.2,
+            air.flow_to(&Gas::vacuum(), 100.0, 1.0),
+            "Big steps are capped"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use super::tile::LiftType;
+use crate::item::{ContainerType, PowerGeneratorType};
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+// What the player can put down, or take away, in build mode
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub enum BuildTool {
+    Floor,
+    Wall,
+    Door,
+    Furniture(ContainerType),
+    Generator(PowerGeneratorType),
+    Conduit,        // Cables under a floor, to carry power across a room
+    Lift(LiftType), // A way up to the deck above, starting a new one if there's nothing there yet
+    Repair,         // Patch up a damaged wall, or a hole where one was
+    Remove,         // Furniture first, then any conduit or lift, then the tile itself
+}
+
+impl BuildTool {
+    // The next tool along, for cycling through them in the UI
+    pub fn next(&self) -> BuildTool {
+        match self {
+            BuildTool::Floor => BuildTool::Wall,
+            BuildTool::Wall => BuildTool::Door,
+            BuildTool::Door => BuildTool::Furniture(ContainerType::Fridge),
+            BuildTool::Furniture(ContainerType::Fridge) => {
+                BuildTool::Furniture(ContainerType::Locker)
+            }
+            BuildTool::Furniture(ContainerType::Locker) => {
+                BuildTool::Generator(PowerGeneratorType::SolarPanel)
+            }
+            BuildTool::Generator(PowerGeneratorType::SolarPanel) => {
+                BuildTool::Generator(PowerGeneratorType::Reactor)
+            }
+            BuildTool::Generator(PowerGeneratorType::Reactor) => BuildTool::Conduit,
+            BuildTool::Conduit => BuildTool::Lift(LiftType::Ladder),
+            BuildTool::Lift(LiftType::Ladder) => BuildTool::Lift(LiftType::Elevator),
+            BuildTool::Lift(LiftType::Elevator) => BuildTool::Repair,
+            BuildTool::Repair => BuildTool::Remove,
+            BuildTool::Remove => BuildTool::Floor,
+        }
+    }
+}
+
+impl fmt::Display for BuildTool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildTool::Furniture(kind) => write!(f, "{:?}", kind),
+            BuildTool::Generator(kind) => write!(f, "{:?}", kind),
+            BuildTool::Lift(kind) => write!(f, "{:?}", kind),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BuildTool;
+
+    #[test]
+    fn next_cycles_through_every_tool() {
+        let mut tool = BuildTool::Floor;
+        let mut names = Vec::new();
+        loop {
+            names.push(tool.to_string());
+            tool = tool.next();
+            if tool == BuildTool::Floor {
+                break;
+            }
+        }
+        assert_eq!(
+            vec![
+                "Floor",
+                "Wall",
+                "Door",
+                "Fridge",
+                "Locker",
+                "SolarPanel",
+                "Reactor",
+                "Conduit",
+                "Ladder",
+                "Elevator",
+                "Repair",
+                "Remove"
+            ],
+            names
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use super::gridposition::GridPosition;
+use super::tile::Tile;
+
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::ops::Index;
+
+// Tiles are kept in square chunks this many tiles a side
+pub const CHUNK_SIZE: i32 = 16;
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+// Where a chunk is, counted in chunks rather than tiles
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub struct ChunkPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32, // The deck, same as for tiles
+}
+
+impl ChunkPos {
+    // The chunk a tile is in. Negative positions round down, so the chunk at -1 runs from -16 to -1
+    pub fn of(pos: GridPosition) -> ChunkPos {
+        ChunkPos {
+            x: pos.x.div_euclid(CHUNK_SIZE),
+            y: pos.y.div_euclid(CHUNK_SIZE),
+            z: pos.z,
+        }
+    }
+
+    // Where a tile goes within its chunk's array
+    // This is laid out so a chunk is walked in the same order as `GridPosition` sorts
+    fn index(pos: GridPosition) -> usize {
+        let x = CHUNK_SIZE - 1 - pos.x.rem_euclid(CHUNK_SIZE);
+        let y = pos.y.rem_euclid(CHUNK_SIZE);
+        (x * CHUNK_SIZE + y) as usize
+    }
+}
+
+// Chunks sort the same way tiles do, by deck, then right to left, then top to bottom
+impl Ord for ChunkPos {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.z
+            .cmp(&other.z)
+            .then_with(|| other.x.cmp(&self.x))
+            .then_with(|| self.y.cmp(&other.y))
+    }
+}
+
+impl PartialOrd for ChunkPos {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// One square of the station, with a slot for every tile whether there's anything there or not
+#[derive(Clone, Debug)]
+pub struct Chunk {
+    tiles: Vec<Option<Tile>>,
+    len: usize, // How many of the slots have a tile in
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            tiles: vec![None; CHUNK_AREA],
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Every tile in the chunk
+    pub fn tiles(&self) -> impl DoubleEndedIterator<Item = &Tile> {
+        self.tiles.iter().filter_map(Option::as_ref)
+    }
+
+    fn tiles_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Tile> {
+        self.tiles.iter_mut().filter_map(Option::as_mut)
+    }
+}
+
+// All of a station's tiles, by position, a chunk at a time
+// Looking a tile up is a map lookup for the chunk and then straight into an array, and going through them all
+// only touches the chunks there are. Chunks with nothing left in them are dropped
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<Tile>", into = "Vec<Tile>")]
+pub struct TileMap {
+    chunks: BTreeMap<ChunkPos, Chunk>,
+    len: usize,
+}
+
+impl TileMap {
+    pub fn new() -> TileMap {
+        TileMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains_key(&self, pos: &GridPosition) -> bool {
+        self.get(pos).is_some()
+    }
+
+    pub fn get(&self, pos: &GridPosition) -> Option<&Tile> {
+        self.chunks
+            .get(&ChunkPos::of(*pos))
+            .and_then(|chunk| chunk.tiles[ChunkPos::index(*pos)].as_ref())
+    }
+
+    pub fn get_mut(&mut self, pos: &GridPosition) -> Option<&mut Tile> {
+        self.chunks
+            .get_mut(&ChunkPos::of(*pos))
+            .and_then(|chunk| chunk.tiles[ChunkPos::index(*pos)].as_mut())
+    }
+
+    // Put a tile in, returning whatever was there before
+    pub fn insert(&mut self, pos: GridPosition, tile: Tile) -> Option<Tile> {
+        let chunk = self
+            .chunks
+            .entry(ChunkPos::of(pos))
+            .or_insert_with(Chunk::new);
+        let old = chunk.tiles[ChunkPos::index(pos)].replace(tile);
+        if old.is_none() {
+            chunk.len += 1;
+            self.len += 1;
+        }
+        old
+    }
+
+    // Take a tile out, if there was one
+    pub fn remove(&mut self, pos: &GridPosition) -> Option<Tile> {
+        let chunk_pos = ChunkPos::of(*pos);
+        let chunk = self.chunks.get_mut(&chunk_pos)?;
+        let old = chunk.tiles[ChunkPos::index(*pos)].take();
+        if old.is_some() {
+            chunk.len -= 1;
+            self.len -= 1;
+            if chunk.is_empty() {
+                self.chunks.remove(&chunk_pos);
+            }
+        }
+        old
+    }
+
+    // Every tile, a chunk at a time
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&GridPosition, &Tile)> {
+        self.values().map(|tile| (&tile.pos, tile))
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &GridPosition> {
+        self.values().map(|tile| &tile.pos)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Tile> {
+        self.chunks.values().flat_map(|chunk| chunk.tiles())
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut Tile> {
+        self.chunks.values_mut().flat_map(|chunk| chunk.tiles_mut())
+    }
+
+    // Every chunk with something in it
+    pub fn chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.chunks.iter().map(|(pos, chunk)| (*pos, chunk))
+    }
+
+    // The chunks on one deck
+    pub fn chunks_on_deck(&self, deck: i32) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.chunks().filter(move |(pos, _chunk)| pos.z == deck)
+    }
+}
+
+impl Index<&GridPosition> for TileMap {
+    type Output = Tile;
+
+    fn index(&self, pos: &GridPosition) -> &Tile {
+        self.get(pos).expect("No tile there")
+    }
+}
+
+// Saved as a plain list of tiles, since every tile knows where it goes
+impl From<Vec<Tile>> for TileMap {
+    fn from(tiles: Vec<Tile>) -> TileMap {
+        let mut map = TileMap::new();
+        for tile in tiles {
+            map.insert(tile.pos, tile);
+        }
+        map
+    }
+}
+
+impl From<TileMap> for Vec<Tile> {
+    fn from(map: TileMap) -> Vec<Tile> {
+        map.chunks
+            .into_values()
+            .flat_map(|chunk| chunk.tiles.into_iter().flatten())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChunkPos, TileMap, CHUNK_SIZE};
+    use crate::station::gridposition::GridPosition;
+    use crate::station::tile::{Tile, TileType};
+
+    #[test]
+    fn chunk_pos() {
+        assert_eq!(
+            ChunkPos { x: 0, y: 0, z: 0 },
+            ChunkPos::of(GridPosition::new(0, CHUNK_SIZE - 1))
+        );
+        assert_eq!(
+            ChunkPos { x: 1, y: 0, z: 0 },
+            ChunkPos::of(GridPosition::new(CHUNK_SIZE, 0))
+        );
+        assert_eq!(
+            ChunkPos { x: -1, y: -1, z: 2 },
+            ChunkPos::of(GridPosition::on_deck(-1, -CHUNK_SIZE, 2)),
+            "Negative positions round down"
+        );
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut tiles = TileMap::new();
+        for pos in [(0, 0), (-1, -1), (100, -40)] {
+            let pos = GridPosition::from(pos);
+            assert!(tiles.insert(pos, Tile::new(pos, TileType::Floor)).is_none());
+        }
+        let pos = GridPosition::new(-1, -1);
+        assert!(
+            tiles.insert(pos, Tile::new(pos, TileType::Floor)).is_some(),
+            "Replaced"
+        );
+        assert_eq!(3, tiles.len());
+        assert_eq!(3, tiles.chunks().count(), "One chunk each");
+        assert_eq!(pos, tiles[&pos].pos);
+        assert!(!tiles.contains_key(&GridPosition::new(1, 0)));
+
+        assert!(tiles.remove(&pos).is_some());
+        assert!(tiles.remove(&pos).is_none(), "Already gone");
+        assert_eq!(2, tiles.len());
+        assert_eq!(2, tiles.chunks().count(), "Empty chunks are dropped");
+    }
+
+    #[test]
+    fn iterates_in_order() {
+        let mut tiles = TileMap::new();
+        let mut expected = Vec::new();
+        for x in 0..4 {
+            for y in 0..4 {
+                let pos = GridPosition::new(x, y);
+                tiles.insert(pos, Tile::new(pos, TileType::Floor));
+                expected.push(pos);
+            }
+        }
+        expected.sort();
+        let found: Vec<GridPosition> = tiles.keys().copied().collect();
+        assert_eq!(
+            expected, found,
+            "Within a chunk, the same as positions sort"
+        );
+
+        let saved: Vec<Tile> = tiles.clone().into();
+        let loaded = TileMap::from(saved);
+        assert_eq!(found, loaded.keys().copied().collect::<Vec<GridPosition>>());
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+// Fires are worked out in steps of this many seconds, since spreading is a roll of the dice each time
+pub const FIRE_STEP: f32 = 1.0;
+
+// Below this much oxygen, in kPa, a fire dies down
+pub const MIN_FIRE_O2_PRESSURE: f32 = 10.0;
+
+// Moles of oxygen burnt, and turned into CO2, every second by a fire at full intensity
+pub const FIRE_O2_RATE: f32 = 0.5;
+
+// Kelvin a fire at full intensity heats its tile by every second
+pub const FIRE_HEAT_RATE: f32 = 5.0;
+
+// How quickly a fire grows with enough air, or dies down without it, as intensity per second
+pub const FIRE_GROWTH_RATE: f32 = 0.1;
+pub const FIRE_SMOTHER_RATE: f32 = 0.2;
+
+// Seconds a bare floor burns for at full intensity, and how much longer each flammable thing on it adds
+pub const FLOOR_FUEL: f32 = 60.0;
+pub const ITEM_FUEL: f32 = 120.0;
+
+// Chance per second of a fire at full intensity spreading to each neighbour, three times that if there's anything there to burn
+pub const SPREAD_CHANCE: f32 = 0.02;
+
+// Chance per second of any one powered thing shorting out and starting a fire. About once a week
+pub const ELECTRICAL_FAULT_CHANCE: f32 = 1.0 / (7.0 * 24.0 * 60.0 * 60.0);
+
+// Chance of a meal in the galley going up in flames
+pub const COOKING_ACCIDENT_CHANCE: f32 = 0.01;
+
+// How much intensity a crew member with an extinguisher knocks off a fire every second
+pub const EXTINGUISH_RATE: f32 = 0.25;
+
+// How long a fire burns with nobody coming before the rest of the crew stop waiting for an Engineer or Soldier
+pub const FIRE_RESPONSE_DELAY: f64 = 30.0;
+
+// How a fire got started
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub enum FireCause {
+    Electrical, // Something powered shorted out
+    Cooking,    // Somebody got careless in the galley
+    Spread,     // Caught from the tile next door
+}
+
+// A fire burning on a tile
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Fire {
+    pub intensity: f32, // From just lit at 0 to raging at 1
+    pub fuel: f32,      // Seconds it can keep burning at full intensity
+    pub cause: FireCause,
+}
+
+impl Fire {
+    pub fn new(fuel: f32, cause: FireCause) -> Fire {
+        Fire {
+            intensity: 0.1,
+            fuel,
+            cause,
+        }
+    }
+
+    // Burn for `dt` seconds, with or without enough air. Returns whether it's still going
+    pub fn burn(&mut self, dt: f32, has_air: bool) -> bool {
+        if has_air {
+            self.intensity = (self.intensity + FIRE_GROWTH_RATE * dt).min(1.0);
+        } else {
+            self.intensity -= FIRE_SMOTHER_RATE * dt;
+        }
+        self.fuel -= self.intensity.max(0.0) * dt;
+        self.is_burning()
+    }
+
+    pub fn is_burning(&self) -> bool {
+        self.intensity > 0.0 && self.fuel > 0.0
+    }
+}
+
+impl fmt::Display for Fire {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.0}% ({:?}), {:.0}s of fuel left",
+            self.intensity * 100.0,
+            self.cause,
+            self.fuel.max(0.0)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Fire, FireCause};
+
+    #[test]
+    fn burn() {
+        let mut fire = Fire::new(10.0, FireCause::Electrical);
+        assert!(fire.burn(1.0, true));
+        assert!(fire.intensity > 0.1, "Grows with air");
+
+        while fire.burn(1.0, false) {}
+        assert!(fire.fuel > 0.0, "Smothered before it burnt out");
+
+        let mut fire = Fire::new(10.0, FireCause::Spread);
+        fire.intensity = 1.0;
+        let mut seconds = 0;
+        while fire.burn(1.0, true) {
+            seconds += 1;
+        }
+        assert_eq!(9, seconds, "Burns through its fuel");
+    }
+}
@@ -0,0 +1,779 @@
+use super::gridposition::*;
+use super::tile::*;
+
+use oorandom::Rand32;
+
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+// What a generator produces: the tiles of a station before it gets walled in and furnished
+pub type Layout = BTreeMap<GridPosition, TileType>;
+
+// Something that can lay out a new station from a seeded random-number generator
+// Generators only have to place floors, plus whatever walls and doors they care about. Afterwards the station
+// walls in any floor left open to space, finds the rooms, adds doors between rooms that can't reach each other,
+// and points all the walls the right way
+pub trait StationGenerator {
+    // How many tiles across and down the layout is, not counting walls added around the outside
+    fn size(&self) -> (usize, usize);
+
+    fn layout(&self, rng: &mut Rand32) -> Layout;
+}
+
+// The generators a new game can be made with
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum GeneratorType {
+    Cellular,
+    Bsp,
+    Ring,
+    Prefab,
+}
+
+impl GeneratorType {
+    pub fn all() -> [GeneratorType; 4] {
+        [
+            GeneratorType::Cellular,
+            GeneratorType::Bsp,
+            GeneratorType::Ring,
+            GeneratorType::Prefab,
+        ]
+    }
+
+    // The next type along, for cycling through them in the UI
+    pub fn next(&self) -> GeneratorType {
+        match self {
+            GeneratorType::Cellular => GeneratorType::Bsp,
+            GeneratorType::Bsp => GeneratorType::Ring,
+            GeneratorType::Ring => GeneratorType::Prefab,
+            GeneratorType::Prefab => GeneratorType::Cellular,
+        }
+    }
+
+    // A generator of this type with its default parameters, sized to fit in `width` by `height`
+    pub fn generator(&self, width: usize, height: usize) -> Box<dyn StationGenerator> {
+        match self {
+            GeneratorType::Cellular => Box::new(CellularAutomaton {
+                width,
+                height,
+                ..CellularAutomaton::default()
+            }),
+            GeneratorType::Bsp => Box::new(Bsp {
+                width,
+                height,
+                ..Bsp::default()
+            }),
+            // Round, so as big as fits in the shorter side
+            GeneratorType::Ring => Box::new(RingAndSpoke {
+                radius: width.min(height).saturating_sub(1) / 2,
+                ..RingAndSpoke::default()
+            }),
+            GeneratorType::Prefab => Box::new(Prefabs {
+                width,
+                height,
+                ..Prefabs::default()
+            }),
+        }
+    }
+}
+
+impl fmt::Display for GeneratorType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            GeneratorType::Cellular => "cellular",
+            GeneratorType::Bsp => "bsp",
+            GeneratorType::Ring => "ring",
+            GeneratorType::Prefab => "prefab",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for GeneratorType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GeneratorType::all()
+            .iter()
+            .find(|kind| kind.to_string() == s.trim())
+            .copied()
+            .ok_or_else(|| format!("Unknown generator: {}", s))
+    }
+}
+
+// Randomly fill the area with floor, then smooth it out into blobs, like the game always has
+// The blobs are divided up into rooms by straight walls across the whole thing
+pub struct CellularAutomaton {
+    pub width: usize,
+    pub height: usize,
+    pub fill_chance: f32,         // Chance of each tile starting out as floor
+    pub iterations: usize,        // How many times to smooth
+    pub starve_below: usize,      // Floors with fewer floor neighbours than this are removed
+    pub birth_at: usize, // Empty spaces with exactly this many floor neighbours become floor
+    pub room_widths: (u32, u32), // Range of how far apart the walls down the station are
+    pub room_heights: (u32, u32), // Range of how far apart the walls across the station are
+}
+
+impl Default for CellularAutomaton {
+    fn default() -> Self {
+        CellularAutomaton {
+            width: 21,
+            height: 13,
+            fill_chance: 0.70,
+            iterations: 2,
+            starve_below: 2,
+            birth_at: 3,
+            room_widths: (4, 8),
+            room_heights: (3, 6),
+        }
+    }
+}
+
+impl StationGenerator for CellularAutomaton {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn layout(&self, rng: &mut Rand32) -> Layout {
+        let mut layout = Layout::new();
+        let (width, height) = (self.width as i32, self.height as i32);
+
+        // Randomly place floor tiles to give us a base
+        for x in 0..width {
+            for y in 0..height {
+                if rng.rand_float() < self.fill_chance {
+                    layout.insert(GridPosition::new(x, y), TileType::Floor);
+                }
+            }
+        }
+
+        // Loop over the floor tiles we placed and expand into bigger spaces
+        for _ in 0..self.iterations {
+            for x in 0..width {
+                for y in 0..height {
+                    let pos = GridPosition::new(x, y);
+                    let neighbor_count = count_floor_neighbors(&layout, pos);
+                    match layout.entry(pos) {
+                        Entry::Occupied(tile) => {
+                            if neighbor_count < self.starve_below {
+                                tile.remove();
+                            }
+                        }
+                        Entry::Vacant(space) => {
+                            if neighbor_count == self.birth_at {
+                                space.insert(TileType::Floor);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Divide the floor up into rooms with some interior walls, a few tiles apart
+        let mut x = 0;
+        loop {
+            x += rng.rand_range(self.room_widths.0..self.room_widths.1) as i32;
+            if x >= width - 2 {
+                break;
+            }
+            for y in 0..height {
+                wall_over_floor(&mut layout, GridPosition::new(x, y));
+            }
+        }
+        let mut y = 0;
+        loop {
+            y += rng.rand_range(self.room_heights.0..self.room_heights.1) as i32;
+            if y >= height - 2 {
+                break;
+            }
+            for x in 0..width {
+                wall_over_floor(&mut layout, GridPosition::new(x, y));
+            }
+        }
+
+        layout
+    }
+}
+
+// Binary space partitioning: keep cutting the area in two, put a room in each piece,
+// and join the pieces back up with corridors
+pub struct Bsp {
+    pub width: usize,
+    pub height: usize,
+    pub min_leaf: usize, // Pieces smaller than this across aren't cut any further
+    pub max_depth: usize, // How many times to cut
+    pub min_room: usize, // Smallest room, not counting its walls
+}
+
+impl Default for Bsp {
+    fn default() -> Self {
+        Bsp {
+            width: 24,
+            height: 16,
+            min_leaf: 6,
+            max_depth: 4,
+            min_room: 3,
+        }
+    }
+}
+
+impl Bsp {
+    // Lay out a piece of the station, returning the middle of one of its rooms to run corridors to
+    fn partition(
+        &self,
+        (x, y, w, h): (i32, i32, i32, i32),
+        depth: usize,
+        rng: &mut Rand32,
+        layout: &mut Layout,
+    ) -> GridPosition {
+        let min_leaf = self.min_leaf as i32;
+        let can_cut_across = w >= min_leaf * 2;
+        let can_cut_down = h >= min_leaf * 2;
+
+        if depth < self.max_depth && (can_cut_across || can_cut_down) {
+            // Cut the longer way, so pieces stay roughly square
+            let across = if can_cut_across && can_cut_down {
+                w >= h
+            } else {
+                can_cut_across
+            };
+            let (a, b) = if across {
+                let at = random_between(rng, min_leaf, w - min_leaf);
+                ((x, y, at, h), (x + at, y, w - at, h))
+            } else {
+                let at = random_between(rng, min_leaf, h - min_leaf);
+                ((x, y, w, at), (x, y + at, w, h - at))
+            };
+
+            let a = self.partition(a, depth + 1, rng, layout);
+            let b = self.partition(b, depth + 1, rng, layout);
+            carve_corridor(layout, a, b);
+            return a;
+        }
+
+        // Put a room somewhere in this piece, leaving space for its walls
+        let min_room = self.min_room as i32;
+        let room_w = random_between(rng, min_room.min(w - 2), w - 2);
+        let room_h = random_between(rng, min_room.min(h - 2), h - 2);
+        let room_x = x + 1 + random_between(rng, 0, w - 2 - room_w);
+        let room_y = y + 1 + random_between(rng, 0, h - 2 - room_h);
+        for rx in room_x - 1..=room_x + room_w {
+            for ry in room_y - 1..=room_y + room_h {
+                let inside =
+                    rx >= room_x && rx < room_x + room_w && ry >= room_y && ry < room_y + room_h;
+                let kind = if inside {
+                    TileType::Floor
+                } else {
+                    TileType::Wall(WallDirection::Full)
+                };
+                layout.insert(GridPosition::new(rx, ry), kind);
+            }
+        }
+
+        GridPosition::new(room_x + room_w / 2, room_y + room_h / 2)
+    }
+}
+
+impl StationGenerator for Bsp {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn layout(&self, rng: &mut Rand32) -> Layout {
+        let mut layout = Layout::new();
+        let area = (0, 0, self.width as i32, self.height as i32);
+        self.partition(area, 0, rng, &mut layout);
+        layout
+    }
+}
+
+// A round hub in the middle, a ring corridor around the outside, and spokes joining them up
+pub struct RingAndSpoke {
+    pub radius: usize,     // Out to the outside edge of the ring
+    pub ring_width: usize, // How wide the ring corridor is
+    pub hub_radius: usize,
+    pub spokes: usize, // Up to four, one for each direction
+}
+
+impl Default for RingAndSpoke {
+    fn default() -> Self {
+        RingAndSpoke {
+            radius: 7,
+            ring_width: 2,
+            hub_radius: 2,
+            spokes: 4,
+        }
+    }
+}
+
+impl StationGenerator for RingAndSpoke {
+    fn size(&self) -> (usize, usize) {
+        (self.radius * 2 + 1, self.radius * 2 + 1)
+    }
+
+    fn layout(&self, rng: &mut Rand32) -> Layout {
+        let mut layout = Layout::new();
+        let r = self.radius as i32;
+        let center = GridPosition::new(r, r);
+        let distance = |pos: GridPosition| {
+            (((pos.x - center.x).pow(2) + (pos.y - center.y).pow(2)) as f32).sqrt()
+        };
+        // A ring wider than the station just fills it in
+        let ring_inside = self.radius.saturating_sub(self.ring_width) as f32 + 0.5;
+        let in_hub = |pos: GridPosition| distance(pos) <= self.hub_radius as f32 + 0.5;
+        let in_ring = |pos: GridPosition| {
+            let d = distance(pos);
+            d > ring_inside && d <= self.radius as f32 + 0.5
+        };
+
+        for x in 0..=r * 2 {
+            for y in 0..=r * 2 {
+                let pos = GridPosition::new(x, y);
+                if in_hub(pos) || in_ring(pos) {
+                    layout.insert(pos, TileType::Floor);
+                }
+            }
+        }
+
+        // Pick which ways the spokes go
+        let mut directions = vec![(0, -1), (1, 0), (0, 1), (-1, 0)];
+        for i in (1..directions.len()).rev() {
+            let j = rng.rand_range(0..i as u32 + 1) as usize;
+            directions.swap(i, j);
+        }
+
+        // Run each spoke out from the hub to the ring, with a door at each end
+        for (dx, dy) in directions.into_iter().take(self.spokes.clamp(1, 4)) {
+            let mut spoke = Vec::new();
+            let mut pos = center;
+            while in_hub(pos) {
+                pos = GridPosition::new(pos.x + dx, pos.y + dy);
+            }
+            while !in_ring(pos) && distance(pos) <= self.radius as f32 {
+                spoke.push(pos);
+                pos = GridPosition::new(pos.x + dx, pos.y + dy);
+            }
+
+            for (i, pos) in spoke.iter().enumerate() {
+                let kind = if i == 0 || i == spoke.len() - 1 {
+                    TileType::Door(Door::new(WallDirection::Full))
+                } else {
+                    TileType::Floor
+                };
+                layout.insert(*pos, kind);
+            }
+        }
+
+        layout
+    }
+}
+
+// Little premade modules, joined up door to door
+// `#` is a wall, `.` is floor and `+` is a door where another module can be attached
+const PREFABS: [&[&str]; 8] = [
+    // A junction with a way out on every side
+    &[
+        "###+###", "#.....#", "#.....#", "+.....+", "#.....#", "#.....#", "###+###",
+    ],
+    // Corridors
+    &["#####", "+...+", "#####"],
+    &["#+#", "#.#", "#.#", "#.#", "#+#"],
+    // Rooms with a single way in, one for each side
+    &["#####", "#...#", "#...+", "#...#", "#####"],
+    &["#####", "#...#", "+...#", "#...#", "#####"],
+    &["##+##", "#...#", "#...#", "#####"],
+    &["#####", "#...#", "#...#", "##+##"],
+    // A bigger room you can walk through
+    &["########", "#......#", "+......+", "#......#", "########"],
+];
+
+// Stamp premade modules down one after another, each attached to a free door of one already placed
+pub struct Prefabs {
+    pub width: usize,
+    pub height: usize,
+    pub modules: usize, // How many modules to try to place
+}
+
+impl Default for Prefabs {
+    fn default() -> Self {
+        Prefabs {
+            width: 25,
+            height: 17,
+            modules: 12,
+        }
+    }
+}
+
+// A door on the edge of a prefab, and which way out of the prefab it faces
+struct Connector {
+    pos: GridPosition,
+    facing: (i32, i32),
+}
+
+impl Prefabs {
+    // Where a prefab's doors are and which way they face
+    fn connectors(prefab: &[&str]) -> Vec<Connector> {
+        let height = prefab.len() as i32;
+        let mut connectors = Vec::new();
+        for (y, row) in prefab.iter().enumerate() {
+            let width = row.len() as i32;
+            for (x, c) in row.chars().enumerate() {
+                if c != '+' {
+                    continue;
+                }
+                let (x, y) = (x as i32, y as i32);
+                let facing = if y == 0 {
+                    (0, -1)
+                } else if y == height - 1 {
+                    (0, 1)
+                } else if x == 0 {
+                    (-1, 0)
+                } else if x == width - 1 {
+                    (1, 0)
+                } else {
+                    continue;
+                };
+                connectors.push(Connector {
+                    pos: GridPosition::new(x, y),
+                    facing,
+                });
+            }
+        }
+        connectors
+    }
+
+    // Try to put a prefab down with its top-left corner at `origin`, sharing the door at `shared`
+    // Modules can share walls, but nothing else can overlap. Returns the prefab's connectors, moved into place
+    fn stamp(
+        &self,
+        layout: &mut Layout,
+        prefab: &[&str],
+        origin: GridPosition,
+        shared: Option<GridPosition>,
+    ) -> Option<Vec<Connector>> {
+        let mut cells = Vec::new();
+        for (y, row) in prefab.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let pos = GridPosition::new(origin.x + x as i32, origin.y + y as i32);
+                let kind = match c {
+                    '.' => TileType::Floor,
+                    _ => TileType::Wall(WallDirection::Full), // Doors stay shut until something is attached
+                };
+                let in_bounds = pos.x >= 0
+                    && pos.y >= 0
+                    && pos.x < self.width as i32
+                    && pos.y < self.height as i32;
+                let fits = Some(pos) == shared
+                    || match layout.get(&pos) {
+                        None => true,
+                        Some(TileType::Wall(_)) => matches!(kind, TileType::Wall(_)) && c != '+',
+                        Some(_) => false,
+                    };
+                if !in_bounds || !fits {
+                    return None;
+                }
+                cells.push((pos, kind));
+            }
+        }
+
+        for (pos, kind) in cells {
+            if Some(pos) != shared {
+                layout.insert(pos, kind);
+            }
+        }
+        if let Some(pos) = shared {
+            layout.insert(pos, TileType::Door(Door::new(WallDirection::Full)));
+        }
+
+        Some(
+            Prefabs::connectors(prefab)
+                .into_iter()
+                .map(|connector| Connector {
+                    pos: GridPosition::new(origin.x + connector.pos.x, origin.y + connector.pos.y),
+                    facing: connector.facing,
+                })
+                .filter(|connector| Some(connector.pos) != shared)
+                .collect(),
+        )
+    }
+}
+
+impl StationGenerator for Prefabs {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn layout(&self, rng: &mut Rand32) -> Layout {
+        let mut layout = Layout::new();
+
+        // Start with the junction in the middle
+        let first = PREFABS[0];
+        let origin = GridPosition::new(
+            (self.width as i32 - first[0].len() as i32) / 2,
+            (self.height as i32 - first.len() as i32) / 2,
+        );
+        let mut open = self
+            .stamp(&mut layout, first, origin, None)
+            .unwrap_or_default();
+
+        // Keep attaching modules to free doors until we've placed enough or run out of tries
+        let mut placed = 1;
+        let mut tries = self.modules * 10;
+        while placed < self.modules && tries > 0 && !open.is_empty() {
+            tries -= 1;
+            let index = rng.rand_range(0..open.len() as u32) as usize;
+            let prefab = PREFABS[rng.rand_range(0..PREFABS.len() as u32) as usize];
+
+            // It has to have a door facing back the way we came
+            let target = &open[index];
+            let back = (-target.facing.0, -target.facing.1);
+            let connector = match Prefabs::connectors(prefab)
+                .into_iter()
+                .find(|connector| connector.facing == back)
+            {
+                Some(connector) => connector,
+                None => continue,
+            };
+
+            let origin = GridPosition::new(
+                target.pos.x - connector.pos.x,
+                target.pos.y - connector.pos.y,
+            );
+            let shared = target.pos;
+            if let Some(connectors) = self.stamp(&mut layout, prefab, origin, Some(shared)) {
+                open.remove(index);
+                open.extend(connectors);
+                placed += 1;
+            }
+        }
+
+        layout
+    }
+}
+
+// How many of the four tiles next to a position are floor
+fn count_floor_neighbors(layout: &Layout, pos: GridPosition) -> usize {
+    [(1, 0), (-1, 0), (0, -1), (0, 1)]
+        .iter()
+        .filter(|(x, y)| {
+            layout.get(&GridPosition::new(pos.x + x, pos.y + y)) == Some(&TileType::Floor)
+        })
+        .count()
+}
+
+// Put a wall where there's floor, leaving empty space empty
+fn wall_over_floor(layout: &mut Layout, pos: GridPosition) {
+    if layout.get(&pos) == Some(&TileType::Floor) {
+        layout.insert(pos, TileType::Wall(WallDirection::Full));
+    }
+}
+
+// Dig a corridor from one place to another, across and then down
+// Where it goes straight through a wall it gets a door, otherwise it just knocks the wall through
+fn carve_corridor(layout: &mut Layout, from: GridPosition, to: GridPosition) {
+    let mut path = vec![from];
+    let mut pos = from;
+    while pos != to {
+        pos = if pos.x != to.x {
+            GridPosition::new(pos.x + (to.x - pos.x).signum(), pos.y)
+        } else {
+            GridPosition::new(pos.x, pos.y + (to.y - pos.y).signum())
+        };
+        path.push(pos);
+    }
+
+    for i in 1..path.len() - 1 {
+        let (before, pos, after) = (path[i - 1], path[i], path[i + 1]);
+        let kind = match layout.get(&pos) {
+            Some(TileType::Wall(_)) => {
+                // Going straight through the wall means there's wall either side of us
+                let (dx, dy) = (pos.x - before.x, pos.y - before.y);
+                let straight = after == GridPosition::new(pos.x + dx, pos.y + dy);
+                let is_wall = |side| matches!(layout.get(&side), Some(TileType::Wall(_)));
+                if straight
+                    && is_wall(GridPosition::new(pos.x + dy, pos.y + dx))
+                    && is_wall(GridPosition::new(pos.x - dy, pos.y - dx))
+                {
+                    TileType::Door(Door::new(WallDirection::Full))
+                } else {
+                    TileType::Floor
+                }
+            }
+            Some(kind) => *kind,
+            None => TileType::Floor,
+        };
+        layout.insert(pos, kind);
+    }
+}
+
+// A random number from `low` to `high`, inclusive
+fn random_between(rng: &mut Rand32, low: i32, high: i32) -> i32 {
+    if high <= low {
+        return low;
+    }
+    low + rng.rand_range(0..(high - low + 1) as u32) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GeneratorType, StationGenerator};
+    use crate::events::EventBus;
+    use crate::inhabitant::InhabitantType;
+    use crate::item::{ContainerType, ItemType};
+    use crate::seed::{GameSeed, RngStream};
+    use crate::station::gridposition::GridPosition;
+    use crate::station::room::RoomType;
+    use crate::station::station::{Problem, Station};
+    use crate::station::tile::{TileType, WallDirection};
+
+    type Point2 = glam::Vec2;
+
+    fn generate(generator: &dyn StationGenerator, seed: u64) -> Station {
+        Station::new(
+            Point2::zero(),
+            generator,
+            &mut GameSeed::new(seed).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        )
+    }
+
+    #[test]
+    fn generator_type_names() {
+        for kind in GeneratorType::all().iter() {
+            assert_eq!(Ok(*kind), kind.to_string().parse());
+            assert_ne!(*kind, kind.next());
+        }
+        assert!("nope".parse::<GeneratorType>().is_err());
+    }
+
+    #[test]
+    fn generators_are_reproducible() {
+        for kind in GeneratorType::all().iter() {
+            let generator = kind.generator(21, 13);
+            for seed in 1..5 {
+                let a = generator.layout(&mut GameSeed::new(seed).rng(RngStream::Simulation));
+                let b = generator.layout(&mut GameSeed::new(seed).rng(RngStream::Simulation));
+                assert_eq!(a, b, "{} with seed {}", kind, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn ring_fits_in_the_shorter_side() {
+        for (width, height) in [(21, 13), (13, 21), (12, 40), (3, 3), (1, 1)] {
+            let generator = GeneratorType::Ring.generator(width, height);
+            let (w, h) = generator.size();
+            assert!(
+                w <= width && h <= height,
+                "{}x{} in {}x{}",
+                w,
+                h,
+                width,
+                height
+            );
+
+            let layout = generator.layout(&mut GameSeed::new(1).rng(RngStream::Simulation));
+            assert!(layout
+                .keys()
+                .all(|pos| pos.x >= 0 && pos.y >= 0 && pos.x < w as i32 && pos.y < h as i32));
+        }
+    }
+
+    // Every generator has to make a station people can actually live in
+    #[test]
+    fn generated_stations_are_sound() {
+        for kind in GeneratorType::all().iter() {
+            let generator = kind.generator(21, 13);
+            let (width, height) = generator.size();
+            for seed in 1..20 {
+                let s = generate(generator.as_ref(), seed);
+                let about = format!("{} with seed {}", kind, seed);
+                let mut floors = Vec::new();
+                let mut seen = 0;
+
+                for x in -1..=width as i32 {
+                    for y in -1..=height as i32 {
+                        let pos = GridPosition::new(x, y);
+                        let tile = match s.get_tile(pos) {
+                            Some(tile) => tile,
+                            None => continue,
+                        };
+                        seen += 1;
+                        let walkable = |dx: i32, dy: i32| {
+                            s.get_tile(GridPosition::new(x + dx, y + dy))
+                                .is_some_and(|t| !matches!(t.kind, TileType::Wall(_)))
+                        };
+
+                        match tile.kind {
+                            TileType::Floor => floors.push(pos),
+                            TileType::Door(door) => {
+                                assert!(
+                                    (walkable(-1, 0) && walkable(1, 0))
+                                        || (walkable(0, -1) && walkable(0, 1)),
+                                    "{}: door at {:?} doesn't lead anywhere",
+                                    about,
+                                    pos
+                                );
+                                let (a, b) = match door.direction {
+                                    WallDirection::InteriorVertical => ((-1, 0), (1, 0)),
+                                    _ => ((0, -1), (0, 1)),
+                                };
+                                let room = |(dx, dy): (i32, i32)| {
+                                    s.get_room_at(pos.offset(dx, dy)).map(|room| room.id)
+                                };
+                                assert_ne!(
+                                    room(a),
+                                    room(b),
+                                    "{}: door at {:?} has the same room both sides",
+                                    about,
+                                    pos
+                                );
+                            }
+                            TileType::Wall(_) => (),
+                        }
+                    }
+                }
+                assert!(!floors.is_empty(), "{}: has some floor", about);
+                assert_eq!(Vec::<Problem>::new(), s.validate(), "{}", about);
+                assert_eq!(
+                    seen,
+                    s.num_tiles(),
+                    "{}: stays within its size, plus a wall round the edge",
+                    about
+                );
+                assert_eq!(
+                    floors.len(),
+                    s.get_rooms().map(|room| room.area()).sum::<usize>(),
+                    "{}: every floor tile is in a room",
+                    about
+                );
+
+                let galleys = s.get_rooms_of_type(RoomType::Galley);
+                assert_eq!(1, galleys.len(), "{}: has a galley", about);
+                assert!(
+                    galleys[0]
+                        .get_items(&s)
+                        .iter()
+                        .any(|item| item.get_type() == ItemType::Container(ContainerType::Fridge)),
+                    "{}: the galley has the fridge",
+                    about
+                );
+
+                // And the crew can get everywhere from there
+                let fridge = s.find_items(
+                    vec![ItemType::Container(ContainerType::Fridge)],
+                    GridPosition::new(0, 0),
+                )[0];
+                for pos in floors.iter().filter(|pos| **pos != fridge) {
+                    assert!(
+                        !s.path_to(fridge, *pos, InhabitantType::Engineer).is_empty(),
+                        "{}: can't get from the galley to {}",
+                        about,
+                        pos
+                    );
+                }
+            }
+        }
+    }
+}
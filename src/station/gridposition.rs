@@ -3,28 +3,54 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
 
-// A position on a grid
+// A position on a grid, on one of the station's decks
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct GridPosition {
     pub x: i32,
     pub y: i32,
+    #[serde(default)]
+    pub z: i32, // Which deck, counting up from 0. Saves from before there were decks load onto the first
 }
 
 impl GridPosition {
     // We make a standard helper function so that we can create a new `GridPosition` more easily.
+    // Most things only have the one deck, so this is on the first one
     pub fn new(x: i32, y: i32) -> Self {
-        GridPosition { x, y }
+        GridPosition { x, y, z: 0 }
     }
 
-    // Manhattan distance on a square grid
+    pub fn on_deck(x: i32, y: i32, z: i32) -> Self {
+        GridPosition { x, y, z }
+    }
+
+    // The position this far across from here, on the same deck
+    pub fn offset(&self, dx: i32, dy: i32) -> Self {
+        GridPosition::on_deck(self.x + dx, self.y + dy, self.z)
+    }
+
+    // The same spot on the deck above
+    pub fn above(&self) -> Self {
+        GridPosition::on_deck(self.x, self.y, self.z + 1)
+    }
+
+    // And the deck below
+    pub fn below(&self) -> Self {
+        GridPosition::on_deck(self.x, self.y, self.z - 1)
+    }
+
+    // Manhattan distance on a square grid, counting each deck as one step
     pub fn distance(&self, other: GridPosition) -> i32 {
-        (self.x - other.x).abs() + (self.y - other.y).abs()
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
     }
 }
 
+// Deck by deck, and then the same as always within a deck
 impl Ord for GridPosition {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.x.cmp(&self.x).then_with(|| self.y.cmp(&other.y))
+        self.z
+            .cmp(&other.z)
+            .then_with(|| other.x.cmp(&self.x))
+            .then_with(|| self.y.cmp(&other.y))
     }
 }
 
@@ -37,12 +63,58 @@ impl PartialOrd for GridPosition {
 // Convenient creation of a GridPosition from a tuple
 impl From<(i32, i32)> for GridPosition {
     fn from(pos: (i32, i32)) -> Self {
-        GridPosition { x: pos.0, y: pos.1 }
+        GridPosition::new(pos.0, pos.1)
     }
 }
 
+// The deck is only shown when it isn't the first, so single-deck stations read the same as ever
 impl fmt::Display for GridPosition {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "({}, {})", self.x, self.y)
+        if self.z == 0 {
+            write!(f, "({}, {})", self.x, self.y)
+        } else {
+            write!(f, "({}, {}, deck {})", self.x, self.y, self.z)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GridPosition;
+    use serde::Serialize;
+
+    #[test]
+    fn decks() {
+        let here = GridPosition::on_deck(1, 1, 1);
+        assert_eq!(GridPosition::on_deck(2, 0, 1), here.offset(1, -1));
+        assert_eq!(
+            2,
+            here.distance(GridPosition::new(2, 1)),
+            "A step across and a deck down"
+        );
+        assert!(
+            GridPosition::new(0, 5) < here,
+            "Everything on a lower deck comes first"
+        );
+        assert_eq!("(1, 1, deck 1)", here.to_string());
+        assert_eq!("(1, 1)", GridPosition::new(1, 1).to_string());
+    }
+
+    #[test]
+    fn loads_from_before_decks() {
+        // How positions were written when there was only the one deck
+        #[derive(Serialize)]
+        struct Flat {
+            x: i32,
+            y: i32,
+        }
+        let bytes = serde_cbor::to_vec(&Flat { x: 3, y: -2 }).unwrap();
+        let pos: GridPosition = serde_cbor::from_slice(&bytes).unwrap();
+        assert_eq!(GridPosition::new(3, -2), pos, "On the first deck");
+
+        // And ones with a deck come back the same
+        let here = GridPosition::on_deck(3, -2, 4);
+        let bytes = serde_cbor::to_vec(&here).unwrap();
+        assert_eq!(here, serde_cbor::from_slice(&bytes).unwrap());
     }
 }
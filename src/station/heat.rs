@@ -0,0 +1,84 @@
+use super::tile::*;
+
+pub use super::atmosphere::ROOM_TEMPERATURE;
+
+// How cold it is outside, in kelvin
+pub const SPACE_TEMPERATURE: f32 = 2.7;
+
+// Heat moves slowly, so it's worked out in steps of this many seconds
+pub const HEAT_STEP: f32 = 0.5;
+
+// Never move more than this fraction of the difference over one side in one step, or the numbers blow up
+const MAX_FLOW: f32 = 0.2;
+
+// Once no tile's temperature changes by more than this many kelvin in a step, the heat has as good as stopped moving
+pub const SETTLED_HEAT: f32 = 1e-4;
+
+// Heaters and coolers are thermostatic, and only work while their tile is on the wrong side of these
+pub const HEATER_SETPOINT: f32 = ROOM_TEMPERATURE + 2.0;
+pub const COOLER_SETPOINT: f32 = ROOM_TEMPERATURE - 2.0;
+
+// How many kelvin a heater or cooler changes its tile by every second
+pub const HEATER_RATE: f32 = 0.5;
+
+// What a working fridge keeps its contents at, and how warm food can get before it starts going off
+pub const FRIDGE_TEMPERATURE: f32 = 277.15;
+pub const SPOIL_TEMPERATURE: f32 = 281.15;
+
+// How quickly items warm up or cool down to what's around them, as a fraction of the difference per second
+pub const ITEM_HEAT_RATE: f32 = 0.01;
+
+// How readily heat gets through a tile, as a fraction of the difference per second
+// Floors let it through freely, walls insulate, and the hull most of all
+pub fn conductance(kind: &TileType) -> f32 {
+    match kind {
+        TileType::Floor => 0.5,
+        TileType::Door(door) if door.state == DoorState::Open => 0.5,
+        TileType::Door(_) => 0.05,
+        TileType::Wall(direction) => match direction {
+            WallDirection::InteriorVertical
+            | WallDirection::InteriorHorizontal
+            | WallDirection::InteriorCross
+            | WallDirection::InteriorCornerTopLeft
+            | WallDirection::InteriorCornerTopRight
+            | WallDirection::InteriorCornerBottomLeft
+            | WallDirection::InteriorCornerBottomRight => 0.01,
+            WallDirection::Full => 0.001,
+            _ => 0.000001, // Exterior walls are the hull, which is built to keep the cold out
+        },
+    }
+}
+
+// How much heat moves from `from` towards `to` over one side in `dt` seconds, between two tiles that conduct this well
+pub fn flow(from: f32, to: f32, conductance: f32, dt: f32) -> f32 {
+    (from - to) * (conductance * dt).min(MAX_FLOW)
+}
+
+// Where a temperature ends up after `dt` seconds of settling towards `target` at `rate`
+pub fn settle(temperature: f32, target: f32, rate: f32, dt: f32) -> f32 {
+    target + (temperature - target) * (-rate * dt).exp()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{conductance, flow, settle};
+    use crate::station::tile::{TileType, WallDirection};
+
+    #[test]
+    fn walls_insulate() {
+        let floor = conductance(&TileType::Floor);
+        let interior = conductance(&TileType::Wall(WallDirection::InteriorVertical));
+        let hull = conductance(&TileType::Wall(WallDirection::ExteriorTop));
+        assert!(floor > interior && interior > hull);
+    }
+
+    #[test]
+    fn flow_and_settle() {
+        assert_eq!(1.0, flow(20.0, 10.0, 1.0, 0.1));
+        assert_eq!(2.0, flow(20.0, 10.0, 100.0, 1.0), "Big steps are capped");
+        assert_eq!(0.0, flow(10.0, 10.0, 1.0, 0.1));
+
+        assert!((settle(300.0, 280.0, 1.0, 100.0) - 280.0).abs() < 0.01);
+        assert_eq!(300.0, settle(300.0, 280.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,13 @@
+use std::ops::Range;
+
+// How much of a beating a wall can take, brand new
+pub const MAX_INTEGRITY: u8 = 100;
+
+// Strikes are rolled for in steps of this many seconds
+pub const HULL_STEP: f32 = 1.0;
+
+// Chance per second of a micrometeorite hitting the hull somewhere. About once every ten minutes
+pub const METEOR_CHANCE: f32 = 1.0 / (10.0 * 60.0);
+
+// How much integrity a strike knocks off the wall it hits, so a wall gives way after two to ten of them
+pub const METEOR_DAMAGE: Range<u32> = 10..60;
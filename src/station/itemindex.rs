@@ -0,0 +1,141 @@
+use super::gridposition::GridPosition;
+use crate::item::{Item, ItemType};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+// Where every item on the station is, by type, so finding something doesn't mean looking through every tile
+// Anything in a container counts as being on the container's tile
+// The station keeps this up to date as items are put down, picked up and destroyed
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ItemIndex {
+    counts: BTreeMap<ItemType, BTreeMap<GridPosition, usize>>, // How many of each type there are on each tile
+}
+
+impl ItemIndex {
+    pub fn new() -> ItemIndex {
+        ItemIndex::default()
+    }
+
+    // Count an item as being on a tile, along with anything inside it
+    pub fn add(&mut self, pos: GridPosition, item: &Item) {
+        *self
+            .counts
+            .entry(item.get_type())
+            .or_default()
+            .entry(pos)
+            .or_default() += 1;
+        for inside in item.get_items() {
+            self.add(pos, inside);
+        }
+    }
+
+    // Stop counting an item as being on a tile, along with anything inside it
+    pub fn remove(&mut self, pos: GridPosition, item: &Item) {
+        if let Some(positions) = self.counts.get_mut(&item.get_type()) {
+            if let Some(count) = positions.get_mut(&pos) {
+                *count -= 1;
+                if *count == 0 {
+                    positions.remove(&pos);
+                }
+            }
+            if positions.is_empty() {
+                self.counts.remove(&item.get_type());
+            }
+        }
+        for inside in item.get_items() {
+            self.remove(pos, inside);
+        }
+    }
+
+    // How many items of these types there are, anywhere
+    pub fn count(&self, kinds: &[ItemType]) -> usize {
+        kinds
+            .iter()
+            .filter_map(|kind| self.counts.get(kind))
+            .flat_map(|positions| positions.values())
+            .sum()
+    }
+
+    // Every tile with at least one item of these types
+    pub fn positions(&self, kinds: &[ItemType]) -> BTreeSet<GridPosition> {
+        kinds
+            .iter()
+            .filter_map(|kind| self.counts.get(kind))
+            .flat_map(|positions| positions.keys().copied())
+            .collect()
+    }
+
+    // Every tile with anything on it at all
+    pub fn all_positions(&self) -> BTreeSet<GridPosition> {
+        self.counts
+            .values()
+            .flat_map(|positions| positions.keys().copied())
+            .collect()
+    }
+
+    // Every tile with at least one item of these types, closest to `from` first
+    pub fn nearest(&self, kinds: &[ItemType], from: GridPosition) -> Vec<GridPosition> {
+        let mut found: Vec<GridPosition> = self.positions(kinds).into_iter().collect();
+        found.sort_by_key(|pos| pos.distance(from));
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ItemIndex;
+    use crate::item::{ContainerType, FoodType, Item, ItemType};
+    use crate::station::gridposition::GridPosition;
+    use oorandom::Rand32;
+
+    #[test]
+    fn add_and_remove() {
+        let mut rng = Rand32::new(1);
+        let mut index = ItemIndex::new();
+        let food = vec![ItemType::Food(FoodType::EnergyBar)];
+        let far = GridPosition::new(10, 0);
+        let near = GridPosition::new(1, 0);
+        let bar = Item::new(far, food[0], &mut rng);
+        index.add(far, &bar);
+        index.add(near, &Item::new(near, food[0], &mut rng));
+        index.add(near, &Item::new(near, food[0], &mut rng));
+        assert_eq!(3, index.count(&food));
+        assert_eq!(
+            vec![near, far],
+            index.nearest(&food, GridPosition::new(0, 0)),
+            "Closest first, once each"
+        );
+
+        index.remove(far, &bar);
+        assert_eq!(vec![near], index.nearest(&food, GridPosition::new(0, 0)));
+        assert_eq!(
+            0,
+            index.count(&[ItemType::Container(ContainerType::Fridge)])
+        );
+    }
+
+    #[test]
+    fn containers_count_their_contents() {
+        let mut rng = Rand32::new(1);
+        let mut index = ItemIndex::new();
+        let pos = GridPosition::new(0, 0);
+        let fridge = Item::new(pos, ItemType::Container(ContainerType::Fridge), &mut rng);
+        assert!(!fridge.get_items().is_empty(), "Comes stocked");
+        index.add(pos, &fridge);
+        let mut kinds: Vec<ItemType> = fridge.get_items().iter().map(Item::get_type).collect();
+        kinds.sort();
+        kinds.dedup();
+        assert_eq!(fridge.get_items().len(), index.count(&kinds));
+        assert_eq!(1, index.count(&[fridge.get_type()]));
+        assert_eq!(
+            vec![pos],
+            index.all_positions().into_iter().collect::<Vec<_>>()
+        );
+
+        index.remove(pos, &fridge);
+        assert!(index.positions(&kinds).is_empty());
+        assert!(index.all_positions().is_empty());
+    }
+}
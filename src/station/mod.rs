@@ -1,4 +1,18 @@
+pub mod ascii;
+pub mod atmosphere;
+pub mod build;
+pub mod chunk;
+pub mod fire;
+pub mod generator;
 pub mod gridposition;
+pub mod heat;
+pub mod hull;
+pub mod itemindex;
 mod pathfinding;
+pub mod power;
+pub mod room;
+#[allow(clippy::module_inception)]
 pub mod station;
 pub mod tile;
+pub mod tiled;
+pub mod tileset;
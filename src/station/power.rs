@@ -0,0 +1,55 @@
+use super::gridposition::*;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+// How many watts a door draws to keep its motor ready
+pub const DOOR_DRAW: u32 = 50;
+
+// And an elevator, for each deck it stops at
+pub const ELEVATOR_DRAW: u32 = 500;
+
+// Wiring doesn't change much, so networks are only worked out again this often, in seconds
+pub const POWER_STEP: f32 = 1.0;
+
+// A PowerNetwork is a set of tiles wired together, and the total of everything plugged into them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PowerNetwork {
+    pub tiles: BTreeSet<GridPosition>,
+    pub supply: u32, // Watts put in by generators
+    pub demand: u32, // Watts wanted by everything else
+}
+
+impl PowerNetwork {
+    pub fn new(tiles: BTreeSet<GridPosition>) -> PowerNetwork {
+        PowerNetwork {
+            tiles,
+            supply: 0,
+            demand: 0,
+        }
+    }
+
+    // Is there enough to go round? If not, everything on the network browns out together
+    pub fn is_powered(&self) -> bool {
+        self.supply >= self.demand
+    }
+}
+
+impl fmt::Display for PowerNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} W supply, {} W demand ({} tiles){}",
+            self.supply,
+            self.demand,
+            self.tiles.len(),
+            if self.is_powered() {
+                ""
+            } else {
+                ", browned out"
+            }
+        )
+    }
+}
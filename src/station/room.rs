@@ -0,0 +1,95 @@
+use super::gridposition::*;
+use super::station::*;
+use crate::item::*;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+// Rooms are numbered as they are found, and keep their number for as long as they exist
+pub type RoomId = u32;
+
+// What a room is used for, as designated by the player
+#[derive(Copy, Clone, Eq, PartialEq, Debug, EnumIter, Serialize, Deserialize)]
+pub enum RoomType {
+    Unassigned,
+    Galley,
+    Quarters,
+    Medbay,
+    Lab,
+    Engineering,
+    Bridge,
+    Storage,
+}
+
+// A Room is a set of floor tiles closed off by walls and doors
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Room {
+    pub id: RoomId,
+    pub kind: RoomType,
+    pub tiles: BTreeSet<GridPosition>,
+}
+
+impl Room {
+    pub fn new(id: RoomId, kind: RoomType, tiles: BTreeSet<GridPosition>) -> Room {
+        Room { id, kind, tiles }
+    }
+
+    // How many tiles the room covers
+    pub fn area(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn contains(&self, pos: GridPosition) -> bool {
+        self.tiles.contains(&pos)
+    }
+
+    // Everything lying around in the room, not counting what's inside containers
+    pub fn get_items<'a>(&self, station: &'a Station) -> Vec<&'a Item> {
+        self.tiles
+            .iter()
+            .filter_map(|pos| station.get_tile(*pos))
+            .flat_map(|tile| tile.items.iter())
+            .collect()
+    }
+}
+
+impl RoomType {
+    // The next type along, for cycling through them in the UI
+    pub fn next(&self) -> RoomType {
+        match self {
+            RoomType::Unassigned => RoomType::Galley,
+            RoomType::Galley => RoomType::Quarters,
+            RoomType::Quarters => RoomType::Medbay,
+            RoomType::Medbay => RoomType::Lab,
+            RoomType::Lab => RoomType::Engineering,
+            RoomType::Engineering => RoomType::Bridge,
+            RoomType::Bridge => RoomType::Storage,
+            RoomType::Storage => RoomType::Unassigned,
+        }
+    }
+}
+
+impl fmt::Display for Room {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} #{} ({} tiles)", self.kind, self.id, self.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoomType;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn next_cycles_through_every_type() {
+        let mut kind = RoomType::Unassigned;
+        for expected in RoomType::iter().skip(1) {
+            kind = kind.next();
+            assert_eq!(expected, kind);
+        }
+        assert_eq!(RoomType::Unassigned, kind.next(), "Wraps back around");
+    }
+}
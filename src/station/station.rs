@@ -1,16 +1,37 @@
+use super::ascii::{self, AsciiError, Symbol};
+use super::atmosphere::*;
+use super::build::BuildTool;
+use super::chunk::*;
+use super::fire::*;
+use super::generator::*;
 use super::gridposition::*;
+use super::heat::*;
+use super::hull::*;
+use super::itemindex::ItemIndex;
 use super::pathfinding::*;
+use super::power::*;
+use super::room::*;
 use super::tile::*;
+use super::tileset::*;
 use crate::camera::Camera;
+use crate::events::*;
+use crate::inhabitant::InhabitantType;
 use crate::item::*;
+use crate::minimap;
 
-use ggez::graphics::{Color, DrawMode, DrawParam, Mesh, MeshBuilder};
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::graphics::{Color, DrawMode, DrawParam, Image, Mesh, MeshBuilder};
 use ggez::{graphics, Context, GameResult};
 
 use oorandom::Rand32;
 use serde::{Deserialize, Serialize};
 
-use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::time::Duration;
 
 type Point2 = glam::Vec2;
 
@@ -18,73 +39,390 @@ const FLOOR_COLOR: Color = Color::new(0.1, 0.1, 0.1, 1.0);
 const WALL_COLOR: Color = Color::new(0.3, 0.3, 0.3, 1.0);
 const BORDER_COLOR: Color = Color::BLACK;
 
+// The heatmap runs from blue at the cold end to red at the hot end, in kelvin
+const HEATMAP_COLD: f32 = 263.15;
+const HEATMAP_HOT: f32 = 323.15;
+const HEATMAP_ALPHA: f32 = 0.5;
+
+const LIFT_COLOR: Color = Color::new(0.8, 0.8, 0.2, 1.0);
+
+// The deck below the one being looked at shows through anywhere there's nothing in the way, darkened
+const DECK_BELOW_TINT: Color = Color::new(0.35, 0.35, 0.35, 1.0);
+
+const FIRE_COLOR: Color = Color::new(1.0, 0.4, 0.0, 1.0);
+
+// A hole in the hull is drawn as the space it lets in, and the damage overlay reddens walls the more battered they are
+const BREACH_COLOR: Color = Color::BLACK;
+const DAMAGE_COLOR: Color = Color::new(1.0, 0.0, 0.0, 1.0);
+const DAMAGE_ALPHA: f32 = 0.7;
+
+// How much worse than a tile of walking it is to go through one that's on fire, so people go around if they can
+const BURNING_COST: usize = 20_000;
+
+// The four sides of a tile, in the order `get_neighbors` has them
+const SIDES: [(i32, i32); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+// Bits for each of a tile's neighbours, for describing its surroundings as a bitmask
+const N: u8 = 1;
+const NE: u8 = 2;
+const E: u8 = 4;
+const SE: u8 = 8;
+const S: u8 = 16;
+const SW: u8 = 32;
+const W: u8 = 64;
+const NW: u8 = 128;
+const NEIGHBOR_BITS: [(i32, i32, u8); 8] = [
+    (0, -1, N),
+    (1, -1, NE),
+    (1, 0, E),
+    (1, 1, SE),
+    (0, 1, S),
+    (-1, 1, SW),
+    (-1, 0, W),
+    (-1, -1, NW),
+];
+const DOOR_OPEN_COLOR: Color = Color::new(0.2, 0.4, 0.2, 1.0);
+const DOOR_CLOSED_COLOR: Color = Color::WHITE;
+const DOOR_LOCKED_COLOR: Color = Color::new(0.8, 0.1, 0.1, 1.0);
+const DOOR_BROKEN_COLOR: Color = Color::new(0.9, 0.5, 0.1, 1.0);
+
+// How far apart two parts of a generated station can be and still get a corridor between them
+const MAX_CORRIDOR_LENGTH: i32 = 10;
+
+// Something wrong with a station, found by `Station::validate`
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Problem {
+    OpenToSpace(GridPosition), // Somewhere you can walk that isn't walled in
+    OrphanWall(GridPosition),  // A wall with nothing next to it to wall in
+    ItemOnWall(GridPosition),  // Something stuck inside a wall
+    UnreachableContainer(GridPosition), // A container the crew can't walk to
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::OpenToSpace(pos) => write!(f, "{} is open to space", pos),
+            Problem::OrphanWall(pos) => write!(f, "Wall at {} isn't next to anything", pos),
+            Problem::ItemOnWall(pos) => write!(f, "Wall at {} has items on it", pos),
+            Problem::UnreachableContainer(pos) => {
+                write!(f, "Container at {} can't be reached", pos)
+            }
+        }
+    }
+}
+
 // A type for the Station itself
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Station {
     pub pos: Point2, // The position of the station (upper-left, basically), in world coordinates
-    tiles: HashMap<GridPosition, Tile>, // All the Tiles that make up the station
+    tiles: TileMap, // All the Tiles that make up the station, in chunks, ordered so iteration is reproducible
+    rooms: BTreeMap<RoomId, Room>, // Floor areas closed off by walls and doors
+    room_at: BTreeMap<GridPosition, RoomId>, // Which room each floor tile is in
+    next_room_id: RoomId,
+    items: ItemIndex,                          // Where every item is, by type
+    atmosphere: BTreeMap<GridPosition, Gas>,   // The air in every tile you can walk on
+    atmosphere_time: f32,                      // Seconds since the air last moved
+    settled_air: BTreeSet<ChunkPos>, // Chunks where the air had stopped moving, left alone until something changes
+    networks: BTreeMap<usize, PowerNetwork>, // Tiles wired together, and the power each lot has
+    network_at: BTreeMap<GridPosition, usize>, // Which network each wired tile is on
+    next_network_id: usize,
+    rewired: BTreeSet<GridPosition>, // Tiles changed since the networks were last worked out
+    powered: BTreeSet<GridPosition>, // Tiles with things on them that had power then
+    power_time: f32,                 // Seconds since the networks were last worked out
+    temperature: BTreeMap<GridPosition, f32>, // How hot every tile is, in kelvin
+    heat_time: f32,                  // Seconds since heat last moved
+    settled_heat: BTreeSet<ChunkPos>, // Chunks where the heat had stopped moving
+    fires: BTreeMap<GridPosition, Fire>, // Every tile that's burning
+    fire_time: f32,                  // Seconds since fires last burnt and spread
+    breaches: BTreeSet<GridPosition>, // Walls knocked through to space
+    hull_time: f32,                  // Seconds since a strike was last rolled for
     #[serde(skip)]
-    mesh: Option<Mesh>, // A cache of the mesh making up the station structure
+    meshes: BTreeMap<ChunkPos, Mesh>, // A cache of the meshes making up the station structure, one for each chunk
+    #[serde(skip)]
+    tileset: Option<Tileset>, // Loaded the first time the station is drawn with sprites
+    #[serde(skip)]
+    sprites: BTreeMap<ChunkPos, (SpriteBatch, SpriteBatch)>, // The same as sprites, as (lit, darkened for under the deck above)
+    #[serde(skip)]
+    minimap: BTreeMap<i32, Image>, // The minimap's picture of each deck, a pixel for each tile within the bounds
+    #[serde(skip)]
+    bounds: Option<(GridPosition, GridPosition)>, // What `get_bounds` last worked out, kept until a tile changes it
 }
 
 impl Station {
-    // Creates a new station from scratch.
+    // Creates a new station from scratch, laid out by the given generator
+    // No mesh is built here so that stations can exist without a window; call `build_mesh` before drawing
     pub fn new(
-        ctx: &mut Context,
         pos: Point2,
-        width: usize,
-        height: usize,
+        generator: &dyn StationGenerator,
         rng: &mut Rand32,
+        events: &mut EventBus,
     ) -> Station {
-        let mut s = Station {
-            pos,
-            tiles: HashMap::with_capacity(width * height),
-            mesh: None,
-        };
-
-        s.generate(width, height, rng);
-        s.build_mesh(ctx).unwrap();
+        let mut s = Station::empty(pos);
+        s.generate(generator, rng, events);
 
         s
     }
 
-    // Randomly generate a new station
-    fn generate(&mut self, width: usize, height: usize, rng: &mut Rand32) {
-        // Randomly place floor tiles to give us a base
-        for x in 0..width as i32 {
-            for y in 0..height as i32 {
-                if rng.rand_float() < 0.70 {
-                    let tile = Tile::new(GridPosition::new(x, y), TileType::Floor);
-                    self.add_tile(tile);
+    // A station with nothing in it yet, to be filled in a tile at a time
+    pub fn empty(pos: Point2) -> Station {
+        Station {
+            pos,
+            tiles: TileMap::new(),
+            rooms: BTreeMap::new(),
+            room_at: BTreeMap::new(),
+            next_room_id: 1,
+            items: ItemIndex::new(),
+            atmosphere: BTreeMap::new(),
+            atmosphere_time: 0.0,
+            settled_air: BTreeSet::new(),
+            networks: BTreeMap::new(),
+            network_at: BTreeMap::new(),
+            next_network_id: 0,
+            rewired: BTreeSet::new(),
+            powered: BTreeSet::new(),
+            power_time: 0.0,
+            temperature: BTreeMap::new(),
+            heat_time: 0.0,
+            settled_heat: BTreeSet::new(),
+            fires: BTreeMap::new(),
+            fire_time: 0.0,
+            breaches: BTreeSet::new(),
+            hull_time: 0.0,
+            meshes: BTreeMap::new(),
+            tileset: None,
+            sprites: BTreeMap::new(),
+            minimap: BTreeMap::new(),
+            bounds: None,
+        }
+    }
+
+    // Creates a station from an ASCII map, see `ascii` for the format
+    // Furniture comes stocked just like in a generated station, which is what the rng is for
+    pub fn from_ascii(pos: Point2, text: &str, rng: &mut Rand32) -> Result<Station, AsciiError> {
+        let mut s = Station::empty(pos);
+        for (pos, symbol) in ascii::parse(text)? {
+            let mut tile = match symbol {
+                Symbol::Space => continue,
+                Symbol::Floor
+                | Symbol::Furniture(_)
+                | Symbol::Generator(_)
+                | Symbol::Fixture(_)
+                | Symbol::Tool(_)
+                | Symbol::Conduit
+                | Symbol::Lift(_) => Tile::new(pos, TileType::Floor),
+                Symbol::Wall => Tile::new(pos, TileType::Wall(WallDirection::Full)),
+                Symbol::Door(state) => {
+                    let mut door = Door::new(WallDirection::Full);
+                    door.state = state;
+                    Tile::new(pos, TileType::Door(door))
+                }
+            };
+            match symbol {
+                Symbol::Furniture(kind) => {
+                    tile.add_item(Item::new(pos, ItemType::Container(kind), rng))
+                }
+                Symbol::Generator(kind) => {
+                    tile.add_item(Item::new(pos, ItemType::Generator(kind), rng))
+                }
+                Symbol::Fixture(kind) => {
+                    tile.add_item(Item::new(pos, ItemType::Fixture(kind), rng))
                 }
+                Symbol::Tool(kind) => tile.add_item(Item::new(pos, ItemType::Tool(kind), rng)),
+                Symbol::Conduit => tile.conduit = true,
+                Symbol::Lift(kind) => tile.lift = Some(kind),
+                _ => (),
             }
+            s.add_tile(tile);
         }
+        s.fill_atmosphere();
+        s.fill_temperature();
+        s.update_power();
 
-        // Loop over the floor tiles we placed and expand into bigger spaces
-        // Do this a couple times
-        for _ in 0..2 {
-            for x in 0..width as i32 {
-                for y in 0..height as i32 {
-                    let pos = GridPosition::new(x, y);
-                    let neighbor_count = self.get_neighbors(pos).len();
-                    if self.has_tile(pos) {
-                        if neighbor_count < 2 {
-                            self.remove_tile(pos);
-                        }
-                    } else if neighbor_count == 3 {
-                        let tile = Tile::new(pos, TileType::Floor);
-                        self.add_tile(tile);
-                    }
-                }
+        Ok(s)
+    }
+
+    // Writes the station out as an ASCII map
+    // Only the tiles, doors, furniture and conduits make it; loose items, door access and room designations don't
+    // A conduit or lift under something else is lost too, since there's only one character per tile
+    pub fn to_ascii(&self) -> String {
+        let symbols = self
+            .tiles
+            .iter()
+            .map(|(pos, tile)| {
+                let symbol = match tile.kind {
+                    TileType::Wall(_) => Symbol::Wall,
+                    TileType::Door(door) => Symbol::Door(door.state),
+                    TileType::Floor => tile
+                        .items
+                        .iter()
+                        .find_map(|item| match item.get_type() {
+                            ItemType::Container(kind) => Some(Symbol::Furniture(kind)),
+                            ItemType::Generator(kind) => Some(Symbol::Generator(kind)),
+                            ItemType::Fixture(kind) => Some(Symbol::Fixture(kind)),
+                            ItemType::Tool(kind) => Some(Symbol::Tool(kind)),
+                            _ => None,
+                        })
+                        .unwrap_or(match tile.lift {
+                            Some(kind) => Symbol::Lift(kind),
+                            None if tile.conduit => Symbol::Conduit,
+                            None => Symbol::Floor,
+                        }),
+                };
+                (*pos, symbol)
+            })
+            .collect();
+        ascii::write(&symbols)
+    }
+
+    // Generate a new station from a generator's layout
+    // Tiles go straight into the map here, and rooms are found once at the end rather than after every tile
+    fn generate(
+        &mut self,
+        generator: &dyn StationGenerator,
+        rng: &mut Rand32,
+        events: &mut EventBus,
+    ) {
+        for (pos, kind) in generator.layout(rng) {
+            self.tiles.insert(pos, Tile::new(pos, kind));
+        }
+
+        self.wall_in();
+
+        // Now the walls are up, find the rooms they make and join them up with doors
+        self.detect_rooms();
+        self.place_doors();
+
+        // Anywhere still cut off from the rest gets a corridor, or gets dropped if it's too far away
+        if self.connect_regions() {
+            self.detect_rooms();
+        }
+
+        // Then make every wall and door face the right way
+        let all: Vec<GridPosition> = self.tiles.keys().copied().collect();
+        self.autotile(&all);
+        self.fill_atmosphere();
+        self.fill_temperature();
+
+        // Place some items on the tiles
+        let mut galley = None;
+        let site = self
+            .tiles
+            .values()
+            .find(|tile| tile.kind == TileType::Floor)
+            .map(|tile| tile.pos);
+        if let Some(pos) = site {
+            let fridge = Item::new(pos, ItemType::Container(ContainerType::Fridge), rng);
+            events.publish(SimEvent::ItemPlaced {
+                item: fridge.get_type(),
+                pos,
+            });
+            self.add_item(pos, fridge);
+            galley = self.room_at.get(&pos).copied();
+        }
+
+        // Wherever the fridge ended up is the galley
+        if let Some(id) = galley {
+            self.designate_room(id, RoomType::Galley);
+        }
+
+        // And a locker of building materials as far away as we can, in storage
+        let mut storage = None;
+        let site = self
+            .tiles
+            .values()
+            .rev()
+            .find(|tile| tile.kind == TileType::Floor && tile.items.is_empty())
+            .map(|tile| tile.pos);
+        if let Some(pos) = site {
+            let locker = Item::new(pos, ItemType::Container(ContainerType::Locker), rng);
+            events.publish(SimEvent::ItemPlaced {
+                item: locker.get_type(),
+                pos,
+            });
+            self.add_item(pos, locker);
+            storage = self.room_at.get(&pos).copied();
+        }
+        if let Some(id) = storage.filter(|id| Some(*id) != galley) {
+            self.designate_room(id, RoomType::Storage);
+        }
+
+        // Then a reactor up against a wall, where it can plug into the wiring, to keep it all running
+        let mut engineering = None;
+        let sites: Vec<GridPosition> = self
+            .tiles
+            .values()
+            .filter(|tile| tile.kind == TileType::Floor && tile.items.is_empty())
+            .map(|tile| tile.pos)
+            .collect();
+        let site = sites.into_iter().find(|pos| {
+            self.get_neighbors(*pos)
+                .values()
+                .any(|tile| tile.carries_power())
+        });
+        if let Some(pos) = site {
+            let reactor = Item::new(pos, ItemType::Generator(PowerGeneratorType::Reactor), rng);
+            events.publish(SimEvent::ItemPlaced {
+                item: reactor.get_type(),
+                pos,
+            });
+            self.add_item(pos, reactor);
+            engineering = self.room_at.get(&pos).copied();
+        }
+        if let Some(id) = engineering.filter(|id| Some(*id) != galley && Some(*id) != storage) {
+            self.designate_room(id, RoomType::Engineering);
+        }
+
+        // And a heater next to it, to make up for what leaks out through the hull
+        let site = self
+            .tiles
+            .values()
+            .filter(|tile| tile.kind == TileType::Floor && tile.items.is_empty())
+            .map(|tile| tile.pos)
+            .find(|pos| {
+                self.get_neighbors(*pos)
+                    .values()
+                    .any(|tile| tile.carries_power())
+            });
+        if let Some(pos) = site {
+            let heater = Item::new(pos, ItemType::Fixture(FixtureType::Heater), rng);
+            events.publish(SimEvent::ItemPlaced {
+                item: heater.get_type(),
+                pos,
+            });
+            self.add_item(pos, heater);
+        }
+
+        // And an extinguisher in every room, in case any of that catches fire
+        let ids: Vec<RoomId> = self.rooms.keys().copied().collect();
+        for id in ids {
+            let site = self.rooms[&id]
+                .tiles
+                .iter()
+                .copied()
+                .find(|pos| self.tiles[pos].items.is_empty());
+            if let Some(pos) = site {
+                let extinguisher = Item::new(pos, ItemType::Tool(ToolType::Extinguisher), rng);
+                events.publish(SimEvent::ItemPlaced {
+                    item: extinguisher.get_type(),
+                    pos,
+                });
+                self.add_item(pos, extinguisher);
             }
         }
 
+        // Most of the tiles went straight into the map, so wire up the lot
+        self.rewired.extend(self.tiles.keys().copied());
+        self.update_power();
+    }
+
+    // Put walls in every empty space next to something you can walk on
+    fn wall_in(&mut self) {
         // Loop over the floor tiles and place walls around the edges
         // This is done in two loops because I am not good at Rust and don't
         // know how to solve the memory access issues of modifying while looping
         let mut to_place = HashMap::new();
         for (pos, tile) in self.tiles.iter() {
-            if tile.kind == TileType::Floor {
+            if tile.is_walkable() {
                 for x in -1..2 {
                     for y in -1..2 {
                         // Don't consider ourselves
@@ -93,13 +431,10 @@ impl Station {
                         }
 
                         // If the neighbor doesn't have a floor, make it a wall
-                        let neighbor_pos = GridPosition::new(pos.x + x, pos.y + y);
+                        // Which way it faces is worked out once all the walls are up
+                        let neighbor_pos = pos.offset(x, y);
                         if !self.has_tile(neighbor_pos) {
-                            // Decide on the type of wall and add it
-                            to_place.insert(
-                                neighbor_pos,
-                                TileType::Wall(self.get_wall_direction(*pos)),
-                            );
+                            to_place.insert(neighbor_pos, TileType::Wall(WallDirection::Full));
                         }
                     }
                 }
@@ -107,920 +442,3935 @@ impl Station {
         }
 
         for (&pos, &tile_type) in to_place.iter() {
-            let new_tile = Tile::new(pos, tile_type);
-            self.add_tile(new_tile);
-        }
-
-        // Place some items on the tiles
-        for (_pos, tile) in self.tiles.iter_mut() {
-            if tile.kind == TileType::Floor {
-                println!("Placing fridge at {:#?}", tile);
-                tile.add_item(Item::new(
-                    tile.pos,
-                    ItemType::Container(ContainerType::Fridge),
-                ));
-                break;
-            }
+            self.tiles.insert(pos, Tile::new(pos, tile_type));
         }
     }
 
-    // For a given position, get the best wall direction based on neighbors
-    // Used for station generation
-    fn get_wall_direction(&self, pos: GridPosition) -> WallDirection {
-        let neighbors = self.get_neighbors(pos);
+    // Join every part of the station you can't walk to from the biggest part, or drop it if it's too far away
+    // Returns whether anything changed, in which case the rooms need finding again
+    fn connect_regions(&mut self) -> bool {
+        let mut regions = self.walkable_regions().into_iter();
+        let mut main = match regions.next() {
+            Some(main) => main,
+            None => return false,
+        };
 
-        let mut direction = WallDirection::Full;
+        // Biggest first, so anything a corridor happens to dig through is joined up by the time it's reached
+        let mut changed = false;
+        let mut knocked = Vec::new();
+        for island in regions {
+            changed = true;
+            let (from, to) = match Station::nearest_between(&island, &main) {
+                Some(pair) => pair,
+                None => {
+                    for pos in island {
+                        self.tiles.remove(&pos);
+                    }
+                    continue;
+                }
+            };
 
-        // Place the exterior corners first
-        if !neighbors.contains_key(&(-1, 1)) && !neighbors.contains_key(&(0, 0)) {
-            direction = WallDirection::ExteriorCornerTopLeft;
-        } else if !neighbors.contains_key(&(1, 1)) && !neighbors.contains_key(&(0, 0)) {
-            direction = WallDirection::ExteriorCornerTopRight;
-        } else if !neighbors.contains_key(&(-1, 1)) && !neighbors.contains_key(&(0, 1)) {
-            direction = WallDirection::ExteriorCornerBottomLeft;
-        } else if !neighbors.contains_key(&(1, 1)) && !neighbors.contains_key(&(0, 1)) {
-            direction = WallDirection::ExteriorCornerBottomRight;
-        }
-        // Exterior walls are fairly easy
-        else if !neighbors.contains_key(&(-1, 1)) {
-            direction = WallDirection::ExteriorLeft;
-        } else if !neighbors.contains_key(&(1, 1)) {
-            direction = WallDirection::ExteriorRight;
-        } else if !neighbors.contains_key(&(0, 0)) {
-            direction = WallDirection::ExteriorTop;
-        } else if !neighbors.contains_key(&(0, 1)) {
-            direction = WallDirection::ExteriorBottom;
+            // Dig across and then down, knocking through any walls in the way
+            let mut pos = from;
+            while pos != to {
+                pos = if pos.x != to.x {
+                    pos.offset((to.x - pos.x).signum(), 0)
+                } else {
+                    pos.offset(0, (to.y - pos.y).signum())
+                };
+                match self.get_tile(pos) {
+                    Some(tile) if tile.is_walkable() => (),
+                    existing => {
+                        if existing.is_some() {
+                            knocked.push(pos);
+                        }
+                        self.tiles.insert(pos, Tile::new(pos, TileType::Floor));
+                    }
+                }
+                main.insert(pos);
+            }
+            main.extend(island);
         }
 
-        direction = WallDirection::Full; // Temporary override while I figure this function out
-        direction
-    }
-
-    // Adds a tile to the station. Trusts the tile's position
-    pub fn add_tile(&mut self, tile: Tile) {
-        self.tiles.insert(tile.pos, tile);
-    }
+        if changed {
+            self.wall_in();
+            let orphans: Vec<GridPosition> = self
+                .tiles
+                .keys()
+                .filter(|pos| self.is_orphan_wall(**pos))
+                .copied()
+                .collect();
+            for pos in orphans {
+                self.tiles.remove(&pos);
+            }
 
-    // How many tiles do we have?
-    pub fn num_tiles(&self) -> usize {
-        self.tiles.len()
+            // Where a corridor goes straight through a room's wall it gets a door, so the room stays a room
+            // Going in order means a thick wall only gets the one door, as the next tile along has a door beside it
+            for pos in knocked {
+                if let Some((_, _, direction)) = self.door_sides(pos) {
+                    self.tiles
+                        .insert(pos, Tile::new(pos, TileType::Door(Door::new(direction))));
+                }
+            }
+        }
+        changed
     }
 
-    // Do we have a tile at a grid position?
-    pub fn has_tile(&self, pos: GridPosition) -> bool {
-        self.tiles.contains_key(&pos)
-    }
+    // The closest an island gets to the main part of the station on the same deck, as (island tile, main tile)
+    // Searches outwards from the island, which is the smaller of the two, and gives up past the longest corridor worth digging
+    fn nearest_between(
+        island: &BTreeSet<GridPosition>,
+        main: &BTreeSet<GridPosition>,
+    ) -> Option<(GridPosition, GridPosition)> {
+        let mut seen: BTreeSet<GridPosition> = island.clone();
+        let mut frontier: VecDeque<(GridPosition, GridPosition, i32)> =
+            island.iter().map(|pos| (*pos, *pos, 0)).collect();
 
-    // Get tile at a grid position, if any
-    pub fn get_tile(&self, pos: GridPosition) -> Option<&Tile> {
-        self.tiles.get(&pos)
-    }
+        while let Some((pos, from, distance)) = frontier.pop_front() {
+            if main.contains(&pos) {
+                return Some((from, pos));
+            }
+            if distance == MAX_CORRIDOR_LENGTH {
+                continue;
+            }
+            for (x, y) in [(1, 0), (-1, 0), (0, -1), (0, 1)] {
+                let next = pos.offset(x, y);
+                if seen.insert(next) {
+                    frontier.push_back((next, from, distance + 1));
+                }
+            }
+        }
 
-    // Get tile at a grid position, if any
-    pub fn get_tile_mut(&mut self, pos: GridPosition) -> Option<&mut Tile> {
-        self.tiles.get_mut(&pos)
+        None
     }
 
-    // Removes a tile
-    pub fn remove_tile(&mut self, pos: GridPosition) {
-        self.tiles.remove(&pos);
-    }
+    // Every group of tiles you can walk between, ignoring what state the doors are in, biggest first
+    fn walkable_regions(&self) -> Vec<BTreeSet<GridPosition>> {
+        let mut regions: Vec<BTreeSet<GridPosition>> = Vec::new();
+        let mut seen = BTreeSet::new();
+        for (pos, tile) in self.tiles.iter() {
+            if !tile.is_walkable() || seen.contains(pos) {
+                continue;
+            }
 
-    // Get a random tile within the station
-    pub fn get_random_tile(&self, kind: TileType, rng: &mut Rand32) -> Option<&Tile> {
-        let mut options = Vec::with_capacity(self.num_tiles());
-        for tile in self.tiles.values() {
-            if tile.kind == kind {
-                options.push(tile);
+            let mut region = BTreeSet::new();
+            let mut frontier = vec![*pos];
+            region.insert(*pos);
+            while let Some(pos) = frontier.pop() {
+                for (_pos, next) in self.get_neighbors(pos) {
+                    if next.is_walkable() && region.insert(next.pos) {
+                        frontier.push(next.pos);
+                    }
+                }
             }
-        }
 
-        if options.is_empty() {
-            return None;
+            seen.extend(region.iter().copied());
+            regions.push(region);
         }
 
-        let index = rng.rand_range(0..options.len() as u32) as usize;
-        Some(options[index])
+        // Sorting is stable, so equal-sized regions stay in a reproducible order
+        regions.sort_by_key(|region| Reverse(region.len()));
+        regions
     }
 
-    // Get a tile at a screen position, if any
-    // TODO: position should be a Point2 once ggez updates it
-    pub fn get_tile_from_screen(&self, pos: Point2, camera: &Camera) -> Option<&Tile> {
-        // TODO: This is just world coordinates with camera translation
-        self.get_tile_from_world(pos)
+    // A wall with nothing to wall in
+    fn is_orphan_wall(&self, pos: GridPosition) -> bool {
+        matches!(
+            self.get_tile(pos),
+            Some(Tile {
+                kind: TileType::Wall(_),
+                ..
+            })
+        ) && self.neighbor_mask(pos, |kind| !matches!(kind, TileType::Wall(_))) == 0
     }
 
-    pub fn get_tile_from_world(&self, pos: Point2) -> Option<&Tile> {
-        // Translate the world position into a grid position
-        let screen_pos = pos - (Point2::one() * crate::TILE_WIDTH / 2.0); // Move up and to the left by half a tile
-        let mut translated = (screen_pos / crate::TILE_WIDTH) - (self.pos / crate::TILE_WIDTH); // Move from world to grid by dividing by tile width
-        translated = translated.ceil(); // Snap to grid
-        let grid_pos = GridPosition::new(translated.x as i32, translated.y as i32); // Convert types
+    // Check the station is in a state the game can cope with, returning everything that's wrong with it
+    // Generated stations should always pass, and loaded ones are checked in case the save was tampered with
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let reachable = self
+            .walkable_regions()
+            .into_iter()
+            .next()
+            .unwrap_or_default();
 
-        // Return the tile, if any
-        self.get_tile(grid_pos)
+        for (pos, tile) in self.tiles.iter() {
+            match tile.kind {
+                TileType::Wall(_) => {
+                    if self.is_orphan_wall(*pos) {
+                        problems.push(Problem::OrphanWall(*pos));
+                    }
+                    if !tile.items.is_empty() {
+                        problems.push(Problem::ItemOnWall(*pos));
+                    }
+                }
+                _ => {
+                    let enclosed = NEIGHBOR_BITS
+                        .iter()
+                        .all(|(x, y, _bit)| self.has_tile(pos.offset(*x, *y)));
+                    if !enclosed {
+                        problems.push(Problem::OpenToSpace(*pos));
+                    }
+                    let has_container = tile
+                        .items
+                        .iter()
+                        .any(|item| matches!(item.get_type(), ItemType::Container(_)));
+                    if has_container && !reachable.contains(pos) {
+                        problems.push(Problem::UnreachableContainer(*pos));
+                    }
+                }
+            }
+        }
+
+        problems
     }
 
-    // Get the neighbors of a tile, ignoring diagonal directions, because we don't move that way
-    pub fn get_neighbors(&self, pos: GridPosition) -> HashMap<(i32, i32), &Tile> {
-        let mut neighbors = HashMap::with_capacity(4);
-
-        let x = pos.x;
-        let y = pos.y;
-
-        // E W N S
-        let mut dirs = vec![
-            GridPosition::new(x + 1, y),
-            GridPosition::new(x - 1, y),
-            GridPosition::new(x, y - 1),
-            GridPosition::new(x, y + 1),
-        ];
-        // see "Ugly paths" section for an explanation: https://www.redblobgames.com/pathfinding/a-star/implementation.html#troubleshooting-ugly-path
-        if (x + y) % 2 == 0 {
-            dirs.reverse(); // S N W E
+    // Turn walls into doors where they're all that separates two rooms, one door for each pair of rooms
+    // Rooms the generator already put a door between count as joined
+    fn place_doors(&mut self) {
+        let mut joined = BTreeSet::new();
+        let mut doors = Vec::new();
+        for (pos, tile) in self.tiles.iter() {
+            let (a, b, direction) = match self.door_sides(*pos) {
+                Some(sides) => sides,
+                None => continue,
+            };
+            let (a, b) = match (self.room_at.get(&a), self.room_at.get(&b)) {
+                (Some(&a), Some(&b)) => (a, b),
+                _ => continue,
+            };
+            let pair = (a.min(b), a.max(b));
+            match tile.kind {
+                TileType::Door(_) => {
+                    joined.insert(pair);
+                }
+                // Only if the two sides aren't already joined up
+                TileType::Wall(_) => {
+                    if a != b && !joined.contains(&pair) {
+                        doors.push((*pos, direction, pair));
+                    }
+                }
+                TileType::Floor => (),
+            }
         }
 
-        for dir in dirs {
-            // Check if there is a tile there, and add it if so
-            if let Some(tile) = self.get_tile(dir) {
-                neighbors.insert((dir.x, dir.y), tile);
+        // Doors already in the layout are counted first, wherever they are, so they don't get doubled up
+        for (pos, direction, pair) in doors {
+            if joined.insert(pair) {
+                self.tiles
+                    .insert(pos, Tile::new(pos, TileType::Door(Door::new(direction))));
             }
         }
+    }
 
-        neighbors
+    // The two tiles either side of a spot that could take a door, and which way the door would face
+    // A door needs floor on opposite sides, and wall (or nothing) on the other two
+    fn door_sides(&self, pos: GridPosition) -> Option<(GridPosition, GridPosition, WallDirection)> {
+        let left = pos.offset(-1, 0);
+        let right = pos.offset(1, 0);
+        let up = pos.offset(0, -1);
+        let down = pos.offset(0, 1);
+        if self.is_floor(left) && self.is_floor(right) && !self.is_floor(up) && !self.is_floor(down)
+        {
+            Some((left, right, WallDirection::InteriorVertical))
+        } else if self.is_floor(up)
+            && self.is_floor(down)
+            && !self.is_floor(left)
+            && !self.is_floor(right)
+        {
+            Some((up, down, WallDirection::InteriorHorizontal))
+        } else {
+            None
+        }
     }
 
-    // From a tile in the station, generate a list of reachable non-wall tile positions to the target
-    // Keys are reached tile positions, values are where we came from to get there
-    // Costs are taken into account and in the future could route around tough doors or whatever
-    // This is A*
-    fn search(
-        &self,
-        start: GridPosition,
-        target: GridPosition,
-    ) -> HashMap<GridPosition, Option<GridPosition>> {
-        let mut frontier = BinaryHeap::new();
-        frontier.push(Movement {
-            cost: 0,
-            pos: start,
+    // Work out which way a wall should face from what's around it, using a bitmask of its 8 neighbours
+    // Walls join up with the walls and doors next to them, and which sides have floor decide whether
+    // it's on the outside of the station (Exterior) or has floor on both sides (Interior)
+    fn get_wall_direction(&self, pos: GridPosition) -> WallDirection {
+        let walls = self.neighbor_mask(pos, |kind| {
+            matches!(kind, TileType::Wall(_) | TileType::Door(_))
         });
+        let floors = self.neighbor_mask(pos, |kind| *kind == TileType::Floor);
 
-        let mut came_from = HashMap::new();
-        came_from.insert(start, None);
-        let mut cost_so_far = HashMap::new();
-        cost_so_far.insert(start, 0);
-
-        while !frontier.is_empty() {
-            let current = frontier.pop().unwrap();
-
-            if current.pos == target {
-                break;
+        // Corners are interior when there's floor on the outside of the corner too
+        let corner = |outside: u8, interior: WallDirection, exterior: WallDirection| {
+            if floors & outside != 0 {
+                interior
+            } else {
+                exterior
             }
+        };
 
-            for (_pos, next) in self.get_neighbors(current.pos) {
-                let new_cost = cost_so_far.get(&current.pos).unwrap_or(&0)
-                    + self.movement_cost(&current.pos, next);
-                if new_cost < *cost_so_far.get(&next.pos).unwrap_or(&usize::MAX) {
-                    cost_so_far.insert(next.pos, new_cost);
-                    match next.kind {
-                        TileType::Wall(_) => {}
-                        _ => {
-                            frontier.push(Movement {
-                                cost: new_cost + self.movement_heuristic(next.pos, target) as usize,
-                                pos: next.pos,
-                            });
-                            came_from.insert(next.pos, Some(current.pos));
-                        }
-                    }
-                }
-            }
-        }
+        match walls & (N | E | S | W) {
+            // Surrounded by wall, so it's solid
+            connected if connected.count_ones() >= 3 && floors == 0 => WallDirection::Full,
+            connected if connected.count_ones() >= 3 => WallDirection::InteriorCross,
 
-        // If the frontier is empty, that means we searched and searched but couldn't find the target
-        if frontier.is_empty() {
-            return HashMap::new();
-        }
+            // Corners are named for the corner of the room they would be
+            connected if connected == E | S => corner(
+                N | NW | W,
+                WallDirection::InteriorCornerTopLeft,
+                WallDirection::ExteriorCornerTopLeft,
+            ),
+            connected if connected == W | S => corner(
+                N | NE | E,
+                WallDirection::InteriorCornerTopRight,
+                WallDirection::ExteriorCornerTopRight,
+            ),
+            connected if connected == E | N => corner(
+                S | SW | W,
+                WallDirection::InteriorCornerBottomLeft,
+                WallDirection::ExteriorCornerBottomLeft,
+            ),
+            connected if connected == W | N => corner(
+                S | SE | E,
+                WallDirection::InteriorCornerBottomRight,
+                WallDirection::ExteriorCornerBottomRight,
+            ),
 
-        came_from
-    }
+            // A pillar on its own
+            0 => WallDirection::Full,
 
-    // Compute the cost of moving from a position to a tile. Lower is better
-    // I'd like to use floating point values here, but that's problematic for
-    // sorting in the binary heap. So instead we'll just multiply everything by 1,000
-    fn movement_cost(&self, current: &GridPosition, next: &Tile) -> usize {
-        // TODO: Locked doors
-        // Cost is distance between the grid positions
-        (current.distance(next.pos) * 1000) as usize
-    }
+            // Running left to right (or ending here)
+            connected if connected & (N | S) == 0 => match (floors & N != 0, floors & S != 0) {
+                (false, true) => WallDirection::ExteriorTop,
+                (true, false) => WallDirection::ExteriorBottom,
+                _ => WallDirection::InteriorHorizontal,
+            },
 
-    // Calculate the heuristic value between two grid positions, to be used for pathfinding
-    fn movement_heuristic(&self, a: GridPosition, b: GridPosition) -> u32 {
-        (((a.x - b.x).abs() + (a.y - b.y).abs()) * 1000) as u32
+            // Running top to bottom (or ending here)
+            _ => match (floors & W != 0, floors & E != 0) {
+                (false, true) => WallDirection::ExteriorLeft,
+                (true, false) => WallDirection::ExteriorRight,
+                _ => WallDirection::InteriorVertical,
+            },
+        }
     }
 
-    // Given a start and an end, generate a path that doesn't include walls
-    // TODO: This needs to be able to path outside of the station somehow (for ghosts or future things like going to exterior ships or asteroids, etc)
-    pub fn path_to(&self, start: GridPosition, target: GridPosition) -> Vec<GridPosition> {
-        // Start at the end and work backwards
-        let mut current = target;
-        let mut path = Vec::new();
-
-        // Get list of reachable positions from start to end, return early if no path
-        let reachable = self.search(start, target);
-        if reachable.is_empty() {
-            return Vec::new();
+    // Doors sit in a wall, so they run the same way as the walls either side of them
+    fn get_door_direction(&self, pos: GridPosition) -> WallDirection {
+        let walls = self.neighbor_mask(pos, |kind| {
+            matches!(kind, TileType::Wall(_) | TileType::Door(_))
+        });
+        if walls & (N | S) == N | S {
+            WallDirection::InteriorVertical
+        } else {
+            WallDirection::InteriorHorizontal
         }
+    }
 
-        // Follow the reachable list back to the start
-        while current != start {
-            path.push(current);
-            if let Some(next) = reachable.get(&current).unwrap_or(&None) {
-                current = *next;
+    // A bit for each of the 8 neighbours of a position whose tile passes the test
+    fn neighbor_mask<F>(&self, pos: GridPosition, test: F) -> u8
+    where
+        F: Fn(&TileType) -> bool,
+    {
+        let mut mask = 0;
+        for (x, y, bit) in NEIGHBOR_BITS.iter() {
+            if let Some(tile) = self.get_tile(pos.offset(*x, *y)) {
+                if test(&tile.kind) {
+                    mask |= bit;
+                }
             }
         }
-
-        // Reverse the path to make it from start to end
-        path.reverse();
-        path
+        mask
     }
 
-    // Returns grid positions of tiles containing the desired items
-    // TODO: Take a starting position and return items closest first
-    pub fn find_items(&self, kinds: Vec<ItemType>) -> Vec<&GridPosition> {
-        let mut found = Vec::new();
-        for (pos, tile) in self.tiles.iter() {
-            for item in tile.items.iter() {
-                // Is this what we're looking for?
-                if kinds.contains(&item.get_type()) {
-                    found.push(pos);
-                }
-
-                // If this is a container, we need to iterate inside
-                if let ItemType::Container(_) = item.get_type() {
-                    for subitem in item.get_items().iter() {
-                        // Is this what we're looking for?
-                        if kinds.contains(&subitem.get_type()) {
-                            found.push(pos);
+    // Point every wall and door in and around these positions the right way, after the layout changed
+    fn autotile(&mut self, around: &[GridPosition]) {
+        let mut changes = BTreeMap::new();
+        for pos in around {
+            for x in -1..2 {
+                for y in -1..2 {
+                    let pos = pos.offset(x, y);
+                    if changes.contains_key(&pos) {
+                        continue;
+                    }
+                    match self.get_tile(pos).map(|tile| tile.kind) {
+                        Some(TileType::Wall(_)) => {
+                            changes.insert(pos, TileType::Wall(self.get_wall_direction(pos)));
                         }
+                        Some(TileType::Door(door)) => {
+                            let direction = self.get_door_direction(pos);
+                            changes.insert(pos, TileType::Door(Door { direction, ..door }));
+                        }
+                        _ => (),
                     }
                 }
             }
         }
 
-        found
+        for (pos, kind) in changes {
+            if let Some(tile) = self.tiles.get_mut(&pos) {
+                if tile.kind != kind {
+                    tile.kind = kind;
+                    self.unsettle(pos);
+                    self.invalidate_mesh(pos);
+                }
+            }
+        }
     }
 
-    // Update callback on the station
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        // Update all items
-        for (_pos, tile) in self.tiles.iter_mut() {
-            for item in tile.items.iter_mut() {
-                item.update(ctx)?;
+    // Adds a tile to the station. Trusts the tile's position
+    pub fn add_tile(&mut self, tile: Tile) {
+        let pos = tile.pos;
+
+        // New floor starts out as vacuum, and fills up from whatever's around it
+        if tile.is_walkable() {
+            self.atmosphere.entry(pos).or_insert_with(Gas::vacuum);
+        } else {
+            self.atmosphere.remove(&pos);
+        }
+        self.temperature.entry(pos).or_insert(ROOM_TEMPERATURE);
+        if tile.is_breached() {
+            self.breaches.insert(pos);
+        } else {
+            self.breaches.remove(&pos);
+        }
+        // Only floors burn
+        if tile.kind != TileType::Floor {
+            self.fires.remove(&pos);
+        }
+
+        for item in tile.items.iter() {
+            self.items.add(pos, item);
+        }
+        if let Some(old) = self.tiles.insert(pos, tile) {
+            for item in old.items.iter() {
+                self.items.remove(pos, item);
             }
         }
+        self.rewired.insert(pos);
+        self.unsettle(pos);
+        self.update_rooms(&Station::neighborhood(pos));
+        self.autotile(&[pos]);
+        self.invalidate_mesh(pos);
+    }
 
-        Ok(())
+    // How many tiles do we have?
+    pub fn num_tiles(&self) -> usize {
+        self.tiles.len()
     }
 
-    // Draw callback
-    pub fn draw(&self, ctx: &mut Context, camera: &Camera) -> GameResult<()> {
-        // Draw the pre-calculated station mesh
-        match &self.mesh {
-            Some(mesh) => graphics::draw(
-                ctx,
-                mesh,
-                DrawParam::default()
-                    .dest(self.pos)
-                    .offset(camera.pos)
-                    .scale(camera.zoom),
-            ),
-            None => Ok(()),
-        }?;
+    // Do we have a tile at a grid position?
+    pub fn has_tile(&self, pos: GridPosition) -> bool {
+        self.tiles.contains_key(&pos)
+    }
 
-        // Draw items on tiles
-        for (_pos, tile) in self.tiles.iter() {
-            for item in tile.items.iter() {
-                item.draw(ctx, self.pos, camera)?;
-            }
+    // Get tile at a grid position, if any
+    pub fn get_tile(&self, pos: GridPosition) -> Option<&Tile> {
+        self.tiles.get(&pos)
+    }
+
+    // Get tile at a grid position, if any
+    // Put down and pick up items with `add_item` and `take_item` rather than through this, so they can still be found
+    // Whatever gets changed might matter to the air, heat or wiring, so they all take another look around it
+    pub fn get_tile_mut(&mut self, pos: GridPosition) -> Option<&mut Tile> {
+        self.rewired.insert(pos);
+        self.unsettle(pos);
+        self.tiles.get_mut(&pos)
+    }
+
+    // Put an item down on a tile, if there's a tile there
+    pub fn add_item(&mut self, pos: GridPosition, item: Item) {
+        if let Some(tile) = self.tiles.get_mut(&pos) {
+            self.items.add(pos, &item);
+            tile.add_item(item);
+            self.rewired.insert(pos);
+            self.unsettle(pos);
         }
+    }
 
-        Ok(())
+    // Pick up the first item of one of these types from a tile, or out of a container on it, if there is one
+    pub fn take_item(&mut self, pos: GridPosition, item_types: Vec<ItemType>) -> Option<Item> {
+        let item = self.tiles.get_mut(&pos)?.take_item(item_types)?;
+        self.items.remove(pos, &item);
+        self.rewired.insert(pos);
+        self.unsettle(pos);
+        Some(item)
     }
 
-    // Create a mesh from our state
-    pub fn build_mesh(&mut self, ctx: &mut Context) -> GameResult<()> {
-        let mb = &mut MeshBuilder::new();
-        for (index, tile) in &self.tiles {
-            let tile_rect = graphics::Rect::new(
-                (crate::TILE_WIDTH * index.x as f32) - (crate::TILE_WIDTH / 2.0),
-                (crate::TILE_WIDTH * index.y as f32) - (crate::TILE_WIDTH / 2.0),
-                crate::TILE_WIDTH,
-                crate::TILE_WIDTH,
-            );
-            let center = tile_rect.center();
+    // Get rid of everything on a tile
+    pub fn clear_items(&mut self, pos: GridPosition) {
+        if let Some(tile) = self.tiles.get_mut(&pos) {
+            for item in tile.items.drain(..) {
+                self.items.remove(pos, &item);
+            }
+            self.rewired.insert(pos);
+            self.unsettle(pos);
+        }
+    }
 
-            match &tile.kind {
-                TileType::Floor => {
-                    // Fill the floor
-                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?
-                }
-                TileType::Wall(wall_direction) => match wall_direction {
-                    WallDirection::ExteriorCornerTopLeft => {
-                        // Fill the bottom-right quarter of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            center.x,
-                            center.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorCornerTopRight => {
-                        // Fill the bottom-left quarter of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            center.x + 1.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorCornerBottomLeft => {
-                        // Fill the top-right quarter of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            center.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y + 1.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorCornerBottomRight => {
-                        // Fill the top-left quarter of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y + 1.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            center.x + 1.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorTop => {
-                        // Fill the bottom-half of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw the top-half of the tile as wall
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorBottom => {
-                        // Fill the top-half of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw the bottom-half of the tile as wall
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y + 1.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorLeft => {
-                        // Fill the right-half of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            center.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw the left-half of the tile as wall
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
-                    }
-                    WallDirection::ExteriorRight => {
-                        // Fill the left-half of the tile as a floor
-                        let floor_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
-
-                        // Draw the right-half of the tile as wall
-                        let wall_rect = graphics::Rect::new(
-                            center.x + 1.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
-                    }
-                    WallDirection::InteriorVertical => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Create a vertical wall centered to the tile
-                        let wall_rect = graphics::Rect::new(
-                            center.x - crate::TILE_WIDTH / 4.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
-                    }
-                    WallDirection::InteriorHorizontal => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Create a horizontal wall centered to the tile
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y - crate::TILE_WIDTH / 4.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+    // Removes a tile
+    pub fn remove_tile(&mut self, pos: GridPosition) {
+        if let Some(old) = self.tiles.remove(&pos) {
+            for item in old.items.iter() {
+                self.items.remove(pos, item);
+            }
+        }
+        self.atmosphere.remove(&pos);
+        self.temperature.remove(&pos);
+        self.breaches.remove(&pos);
+        self.fires.remove(&pos);
+        self.rewired.insert(pos);
+        self.unsettle(pos);
+        self.update_rooms(&Station::neighborhood(pos));
+        self.autotile(&[pos]);
+        self.invalidate_mesh(pos);
+    }
+
+    // Can this tool be used here? If not, the reason why not
+    pub fn can_build(&self, pos: GridPosition, tool: BuildTool) -> Result<(), &'static str> {
+        let tile = self.get_tile(pos);
+        let has_items = tile.is_some_and(|tile| !tile.items.is_empty());
+        match tool {
+            BuildTool::Floor => match tile.map(|tile| tile.kind) {
+                Some(TileType::Floor) => Err("Already floor"),
+                Some(TileType::Door(_)) => Err("There's a door here"),
+                _ if self.get_neighbors(pos).values().any(|t| t.is_walkable()) => Ok(()),
+                _ => Err("Floor has to join onto the station"),
+            },
+            BuildTool::Wall => match tile.map(|tile| tile.kind) {
+                Some(TileType::Wall(_)) => Err("Already wall"),
+                None => Err("Walls have to wall something in"),
+                _ if has_items => Err("Something's in the way"),
+                _ => Ok(()),
+            },
+            BuildTool::Door => match tile.map(|tile| tile.kind) {
+                Some(TileType::Door(_)) => Err("Already a door"),
+                Some(TileType::Wall(_)) if self.door_sides(pos).is_some() => Ok(()),
+                _ => Err("Doors go in walls, between two floors"),
+            },
+            BuildTool::Furniture(_) => match tile {
+                Some(tile) if tile.kind != TileType::Floor => Err("Furniture goes on floor"),
+                Some(tile) if tile.has_item(get_container_types()) => Err("Already furnished"),
+                Some(_) => Ok(()),
+                None => Err("Furniture goes on floor"),
+            },
+            BuildTool::Generator(_) => match tile {
+                Some(tile) if tile.kind != TileType::Floor => Err("Generators go on floor"),
+                Some(tile) if tile.has_item(get_generator_types()) => Err("Already a generator"),
+                Some(_) => Ok(()),
+                None => Err("Generators go on floor"),
+            },
+            BuildTool::Conduit => match tile {
+                Some(tile) if tile.kind != TileType::Floor => Err("Conduits go under floor"),
+                Some(tile) if tile.conduit => Err("Already a conduit"),
+                Some(_) => Ok(()),
+                None => Err("Conduits go under floor"),
+            },
+            BuildTool::Lift(kind) => match tile {
+                Some(tile) if tile.kind != TileType::Floor => Err("Lifts go on floor"),
+                Some(tile) if tile.lift.is_some() => Err("Already a lift"),
+                Some(_) => match self.get_tile(pos.above()) {
+                    None => Ok(()),
+                    Some(above) if above.kind == TileType::Floor && above.lift.is_none() => Ok(()),
+                    Some(above) if above.kind == TileType::Floor && above.lift == Some(kind) => {
+                        Ok(())
                     }
-                    WallDirection::InteriorCross => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Create a horizontal wall centered to the tile
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y - crate::TILE_WIDTH / 4.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-
-                        // Create a vertical wall centered to the tile
-                        let wall_rect = graphics::Rect::new(
-                            center.x - crate::TILE_WIDTH / 4.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                    Some(_) => Err("Lifts need floor on the deck above"),
+                },
+                None => Err("Lifts go on floor"),
+            },
+            BuildTool::Repair => match tile {
+                Some(tile) if matches!(tile.kind, TileType::Wall(_)) => {
+                    if tile.integrity < MAX_INTEGRITY {
+                        Ok(())
+                    } else {
+                        Err("Nothing to repair")
                     }
-                    WallDirection::InteriorCornerTopLeft => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                _ => Err("Only walls need repairing"),
+            },
+            BuildTool::Remove => match tile {
+                None => Err("Nothing to remove"),
+                Some(_) if has_items => Ok(()),
+                Some(tile) if !tile.is_walkable() && !self.is_orphan_wall(pos) => {
+                    Err("That would open the station to space")
+                }
+                Some(_) => Ok(()),
+            },
+        }
+    }
+
+    // Use a tool on a tile, keeping the station walled in. Returns whether anything changed
+    pub fn build(
+        &mut self,
+        pos: GridPosition,
+        tool: BuildTool,
+        rng: &mut Rand32,
+        events: &mut EventBus,
+    ) -> bool {
+        if self.can_build(pos, tool).is_err() {
+            return false;
+        }
+
+        match tool {
+            BuildTool::Floor => {
+                self.add_tile(Tile::new(pos, TileType::Floor));
+                self.wall_in_around(pos);
+            }
+            BuildTool::Wall => self.add_tile(Tile::new(pos, TileType::Wall(WallDirection::Full))),
+            BuildTool::Door => self.add_tile(Tile::new(
+                pos,
+                TileType::Door(Door::new(WallDirection::Full)),
+            )),
+            BuildTool::Furniture(kind) => {
+                let item = Item::new(pos, ItemType::Container(kind), rng);
+                events.publish(SimEvent::ItemPlaced {
+                    item: item.get_type(),
+                    pos,
+                });
+                self.add_item(pos, item);
+            }
+            BuildTool::Generator(kind) => {
+                let item = Item::new(pos, ItemType::Generator(kind), rng);
+                events.publish(SimEvent::ItemPlaced {
+                    item: item.get_type(),
+                    pos,
+                });
+                self.add_item(pos, item);
+            }
+            BuildTool::Conduit => self.get_tile_mut(pos).unwrap().conduit = true,
+            BuildTool::Lift(kind) => {
+                // Up to the deck above, which gets started if there's nothing there yet
+                let above = pos.above();
+                if !self.has_tile(above) {
+                    self.add_tile(Tile::new(above, TileType::Floor));
+                    self.wall_in_around(above);
+                }
+                for pos in [pos, above] {
+                    self.get_tile_mut(pos).unwrap().lift = Some(kind);
+                    self.invalidate_mesh(pos);
+                }
+            }
+            BuildTool::Repair => {
+                self.get_tile_mut(pos).unwrap().integrity = MAX_INTEGRITY;
+                if self.breaches.remove(&pos) {
+                    self.invalidate_mesh(pos);
+                }
+            }
+            BuildTool::Remove => {
+                let tile = self.get_tile(pos).unwrap();
+                if !tile.items.is_empty() {
+                    self.clear_items(pos);
+                } else if tile.conduit {
+                    self.get_tile_mut(pos).unwrap().conduit = false;
+                } else if tile.lift.is_some() {
+                    self.get_tile_mut(pos).unwrap().lift = None;
+                    self.invalidate_mesh(pos);
+                } else {
+                    // Anything left next to the hole still needs walling in
+                    let walkable = tile.is_walkable();
+                    let still_needed =
+                        self.neighbor_mask(pos, |kind| !matches!(kind, TileType::Wall(_))) != 0;
+                    if walkable && still_needed {
+                        self.add_tile(Tile::new(pos, TileType::Wall(WallDirection::Full)));
+                    } else {
+                        self.remove_tile(pos);
                     }
-                    WallDirection::InteriorCornerTopRight => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            center.x + 1.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+
+                    // Which might leave walls around it with nothing to do
+                    for (x, y, _bit) in NEIGHBOR_BITS.iter() {
+                        let neighbor = pos.offset(*x, *y);
+                        if self.is_orphan_wall(neighbor) {
+                            self.remove_tile(neighbor);
+                        }
                     }
-                    WallDirection::InteriorCornerBottomLeft => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y + 1.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            tile_rect.x,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+            }
+        }
+
+        events.publish(SimEvent::Built { pos, tool });
+        true
+    }
+
+    // Put walls in any empty space around a position
+    fn wall_in_around(&mut self, pos: GridPosition) {
+        for (x, y, _bit) in NEIGHBOR_BITS.iter() {
+            let neighbor = pos.offset(*x, *y);
+            if !self.has_tile(neighbor) {
+                self.add_tile(Tile::new(neighbor, TileType::Wall(WallDirection::Full)));
+            }
+        }
+    }
+
+    // A position and the four next to it, which is everything a change to one tile can split or join
+    fn neighborhood(pos: GridPosition) -> [GridPosition; 5] {
+        [
+            pos,
+            pos.offset(1, 0),
+            pos.offset(-1, 0),
+            pos.offset(0, -1),
+            pos.offset(0, 1),
+        ]
+    }
+
+    // Find every room in the station from scratch
+    fn detect_rooms(&mut self) {
+        let all: Vec<GridPosition> = self.tiles.keys().copied().collect();
+        self.update_rooms(&all);
+    }
+
+    // Flood fill the rooms at these positions again, after the tiles there have changed
+    // Each new room keeps the id and designation of whichever old room it overlaps most,
+    // so knocking a hole in the galley wall doesn't make the player designate it again
+    fn update_rooms(&mut self, around: &[GridPosition]) {
+        // Forget the rooms touching the change, remembering which tiles were in them
+        let mut old_rooms = BTreeMap::new();
+        let mut starts = around.to_vec();
+        for pos in around {
+            if let Some(id) = self.room_at.get(pos).copied() {
+                if let Some(room) = self.rooms.remove(&id) {
+                    for tile_pos in room.tiles {
+                        self.room_at.remove(&tile_pos);
+                        old_rooms.insert(tile_pos, (room.id, room.kind));
+                        starts.push(tile_pos);
                     }
-                    WallDirection::InteriorCornerBottomRight => {
-                        // Fill the floor
-                        mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
-
-                        // Draw two "wall" sections. One vertical, one horizontal.
-                        let wall_rect = graphics::Rect::new(
-                            tile_rect.x,
-                            center.y + 1.0,
-                            crate::TILE_WIDTH,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
-                        let wall_rect2 = graphics::Rect::new(
-                            center.x + 1.0,
-                            tile_rect.y,
-                            crate::TILE_WIDTH / 2.0 - 1.0,
-                            crate::TILE_WIDTH,
-                        );
-                        mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+            }
+        }
+
+        // Find all the new rooms first, biggest first, so if one split the bigger part gets first pick of the old id
+        let mut regions: Vec<BTreeSet<GridPosition>> = Vec::new();
+        for start in starts {
+            if self.room_at.contains_key(&start)
+                || !self.is_floor(start)
+                || regions.iter().any(|tiles| tiles.contains(&start))
+            {
+                continue;
+            }
+            regions.push(self.flood_fill(start));
+        }
+        regions.sort_by_key(|tiles| Reverse(tiles.len()));
+
+        for tiles in regions {
+            // Which old room does this one overlap most?
+            let mut overlaps: BTreeMap<RoomId, (usize, RoomType)> = BTreeMap::new();
+            for pos in tiles.iter() {
+                if let Some((id, kind)) = old_rooms.get(pos) {
+                    overlaps.entry(*id).or_insert((0, *kind)).0 += 1;
+                }
+            }
+            let inherited = overlaps
+                .iter()
+                .max_by_key(|(id, (count, _kind))| (*count, Reverse(**id)))
+                .map(|(id, (_count, kind))| (*id, *kind));
+
+            // If it split in two, the bigger part keeps the id but both keep the designation
+            let (id, kind) = match inherited {
+                Some((id, kind)) if !self.rooms.contains_key(&id) => (id, kind),
+                Some((_id, kind)) => (self.new_room_id(), kind),
+                None => (self.new_room_id(), RoomType::Unassigned),
+            };
+
+            for pos in tiles.iter() {
+                self.room_at.insert(*pos, id);
+            }
+            self.rooms.insert(id, Room::new(id, kind, tiles));
+        }
+    }
+
+    // All the floor tiles reachable from a floor tile without going through a wall or door
+    fn flood_fill(&self, start: GridPosition) -> BTreeSet<GridPosition> {
+        let mut tiles = BTreeSet::new();
+        let mut frontier = vec![start];
+        tiles.insert(start);
+
+        while let Some(pos) = frontier.pop() {
+            for (_pos, next) in self.get_neighbors(pos) {
+                if next.kind == TileType::Floor && tiles.insert(next.pos) {
+                    frontier.push(next.pos);
+                }
+            }
+        }
+
+        tiles
+    }
+
+    fn is_floor(&self, pos: GridPosition) -> bool {
+        self.get_tile(pos)
+            .is_some_and(|tile| tile.kind == TileType::Floor)
+    }
+
+    fn new_room_id(&mut self) -> RoomId {
+        let id = self.next_room_id;
+        self.next_room_id += 1;
+        id
+    }
+
+    // Get a room by id, if it still exists
+    pub fn get_room(&self, id: RoomId) -> Option<&Room> {
+        self.rooms.get(&id)
+    }
+
+    // Get the room a floor tile is in, if any
+    pub fn get_room_at(&self, pos: GridPosition) -> Option<&Room> {
+        self.room_at.get(&pos).and_then(|id| self.rooms.get(id))
+    }
+
+    pub fn get_rooms(&self) -> impl Iterator<Item = &Room> {
+        self.rooms.values()
+    }
+
+    // All the rooms designated for something, like every galley on the station
+    pub fn get_rooms_of_type(&self, kind: RoomType) -> Vec<&Room> {
+        self.rooms
+            .values()
+            .filter(|room| room.kind == kind)
+            .collect()
+    }
+
+    // Change the state of the door at a position, if there is one there
+    pub fn set_door_state(&mut self, pos: GridPosition, state: DoorState) {
+        if let Some(Tile {
+            kind: TileType::Door(door),
+            ..
+        }) = self.tiles.get_mut(&pos)
+        {
+            door.state = state;
+            self.unsettle(pos);
+            self.invalidate_mesh(pos);
+        }
+    }
+
+    // Change who's allowed through the door at a position, if there is one there
+    pub fn set_door_access(&mut self, pos: GridPosition, access: DoorAccess) {
+        if let Some(Tile {
+            kind: TileType::Door(door),
+            ..
+        }) = self.tiles.get_mut(&pos)
+        {
+            door.access = access;
+        }
+    }
+
+    // Mark what a room is for
+    pub fn designate_room(&mut self, id: RoomId, kind: RoomType) {
+        if let Some(room) = self.rooms.get_mut(&id) {
+            room.kind = kind;
+        }
+    }
+
+    // Get a random tile within the station
+    pub fn get_random_tile(&self, kind: TileType, rng: &mut Rand32) -> Option<&Tile> {
+        // Count them first and then go back for the one picked, rather than collecting every match
+        let count = self.tiles.values().filter(|tile| tile.kind == kind).count();
+        if count == 0 {
+            return None;
+        }
+
+        let index = rng.rand_range(0..count as u32) as usize;
+        self.tiles
+            .values()
+            .filter(|tile| tile.kind == kind)
+            .nth(index)
+    }
+
+    // Get a tile at a screen position on the deck the camera is looking at, if any
+    // TODO: position should be a Point2 once ggez updates it
+    pub fn get_tile_from_screen(&self, pos: Point2, camera: &Camera) -> Option<&Tile> {
+        self.get_tile(self.grid_position_from_screen(pos, camera))
+    }
+
+    // World positions are the same on every deck, so which one has to be said
+    pub fn get_tile_from_world(&self, pos: Point2, deck: i32) -> Option<&Tile> {
+        // Return the tile, if any
+        self.get_tile(self.grid_position_from_world(pos, deck))
+    }
+
+    // The grid position at a screen position, whether there's a tile there or not
+    pub fn grid_position_from_screen(&self, pos: Point2, camera: &Camera) -> GridPosition {
+        self.grid_position_from_world(camera.to_world(pos, self.pos), camera.deck)
+    }
+
+    pub fn grid_position_from_world(&self, pos: Point2, deck: i32) -> GridPosition {
+        // Translate the world position into a grid position
+        let screen_pos = pos - (Point2::one() * crate::TILE_WIDTH / 2.0); // Move up and to the left by half a tile
+        let mut translated = (screen_pos / crate::TILE_WIDTH) - (self.pos / crate::TILE_WIDTH); // Move from world to grid by dividing by tile width
+        translated = translated.ceil(); // Snap to grid
+        GridPosition::on_deck(translated.x as i32, translated.y as i32, deck) // Convert types
+    }
+
+    // Get the neighbors of a tile, ignoring diagonal directions, because we don't move that way
+    pub fn get_neighbors(&self, pos: GridPosition) -> BTreeMap<(i32, i32), &Tile> {
+        let mut neighbors = BTreeMap::new();
+
+        // E W N S, though they come back sorted by position whatever order they're looked at in
+        let dirs = [
+            pos.offset(1, 0),
+            pos.offset(-1, 0),
+            pos.offset(0, -1),
+            pos.offset(0, 1),
+        ];
+
+        for dir in dirs {
+            // Check if there is a tile there, and add it if so
+            if let Some(tile) = self.get_tile(dir) {
+                neighbors.insert((dir.x, dir.y), tile);
+            }
+        }
+
+        neighbors
+    }
+
+    // The tiles on the decks directly above and below that a ladder or elevator here goes to
+    // Elevators only go anywhere while both ends have power
+    pub fn get_lift_stops(&self, pos: GridPosition) -> Vec<&Tile> {
+        let lift = match self.get_tile(pos).and_then(|tile| tile.lift) {
+            Some(lift) => lift,
+            None => return Vec::new(),
+        };
+        if lift == LiftType::Elevator && !self.is_powered(pos) {
+            return Vec::new();
+        }
+
+        [pos.z - 1, pos.z + 1]
+            .iter()
+            .filter_map(|z| self.get_tile(GridPosition::on_deck(pos.x, pos.y, *z)))
+            .filter(|tile| tile.lift == Some(lift))
+            .filter(|tile| lift != LiftType::Elevator || self.is_powered(tile.pos))
+            .collect()
+    }
+
+    // The tiles either side of a position, or None where there isn't one, in the same order as `get_neighbors`
+    // The air, heat and wiring look at every tile's neighbours, too often to collect them up each time
+    fn adjacent(&self, pos: GridPosition) -> impl Iterator<Item = Option<&Tile>> + '_ {
+        SIDES
+            .iter()
+            .map(move |(x, y)| self.get_tile(pos.offset(*x, *y)))
+    }
+
+    // The tiles air and heat get to from a position: the ones either side, then up or down a ladder's open hatch
+    // An elevator's car fills its shaft, so nothing gets past that
+    fn flows_to(&self, pos: GridPosition) -> impl Iterator<Item = &Tile> + '_ {
+        let ladder = self
+            .get_tile(pos)
+            .is_some_and(|tile| tile.lift == Some(LiftType::Ladder));
+        let shafts = IntoIterator::into_iter([pos.above(), pos.below()])
+            .filter(move |_pos| ladder)
+            .filter_map(move |pos| self.get_tile(pos))
+            .filter(|tile| tile.lift == Some(LiftType::Ladder));
+        self.adjacent(pos).flatten().chain(shafts)
+    }
+
+    // Every tile on one deck
+    pub fn get_tiles_on_deck(&self, deck: i32) -> impl Iterator<Item = &Tile> {
+        self.tiles
+            .chunks_on_deck(deck)
+            .flat_map(|(_chunk_pos, chunk)| chunk.tiles())
+    }
+
+    // Every deck there's anything on, from the bottom up
+    pub fn get_decks(&self) -> BTreeSet<i32> {
+        self.tiles
+            .chunks()
+            .map(|(chunk_pos, _chunk)| chunk_pos.z)
+            .collect()
+    }
+
+    // The minimap's picture of a deck, if it's been built
+    pub fn get_minimap(&self, deck: i32) -> Option<&Image> {
+        self.minimap.get(&deck)
+    }
+
+    // Feed every tile, and the air, heat and fire on it, into a checksum
+    // Floats go in as their bits, so the checksum only matches if the simulation matches exactly
+    pub fn hash_state<H: Hasher>(&self, state: &mut H) {
+        self.tiles.len().hash(state);
+        for tile in self.tiles.values() {
+            tile.pos.hash(state);
+            tile.kind.hash(state);
+            tile.conduit.hash(state);
+            tile.lift.hash(state);
+            tile.integrity.hash(state);
+            tile.items.len().hash(state);
+            for item in tile.items.iter() {
+                item.hash_state(state);
+            }
+        }
+        for (pos, gas) in self.atmosphere.iter() {
+            pos.hash(state);
+            gas.o2.to_bits().hash(state);
+            gas.co2.to_bits().hash(state);
+            gas.n2.to_bits().hash(state);
+        }
+        for (pos, temperature) in self.temperature.iter() {
+            pos.hash(state);
+            temperature.to_bits().hash(state);
+        }
+        for (pos, fire) in self.fires.iter() {
+            pos.hash(state);
+            fire.intensity.to_bits().hash(state);
+            fire.fuel.to_bits().hash(state);
+            fire.cause.hash(state);
+        }
+        for time in [
+            self.atmosphere_time,
+            self.power_time,
+            self.heat_time,
+            self.fire_time,
+            self.hull_time,
+        ] {
+            time.to_bits().hash(state);
+        }
+    }
+
+    // The top-left and bottom-right corners of the smallest rectangle every deck fits in, if there's anything at all
+    pub fn get_bounds(&self) -> Option<(GridPosition, GridPosition)> {
+        if self.bounds.is_some() {
+            return self.bounds;
+        }
+
+        let mut positions = self.tiles.keys();
+        let first = positions.next()?;
+        let mut min = GridPosition::new(first.x, first.y);
+        let mut max = min;
+        for pos in positions {
+            min = GridPosition::new(min.x.min(pos.x), min.y.min(pos.y));
+            max = GridPosition::new(max.x.max(pos.x), max.y.max(pos.y));
+        }
+        Some((min, max))
+    }
+
+    // From a tile in the station, generate a list of tile positions a type of inhabitant can reach on the way to the target
+    // Keys are reached tile positions, values are where we came from to get there
+    // Costs are taken into account, so slow doors are avoided when there's a better way round
+    // This is A*
+    fn search(
+        &self,
+        start: GridPosition,
+        target: GridPosition,
+        kind: InhabitantType,
+    ) -> HashMap<GridPosition, Option<GridPosition>> {
+        self.search_for(
+            start,
+            kind,
+            |pos| pos == target,
+            |pos| self.movement_heuristic(pos, target) as usize,
+        )
+        .map(|(_found, came_from)| came_from)
+        .unwrap_or_default()
+    }
+
+    // The search behind `search`, stopping at the first tile `is_target` picks out, and returning which one that was
+    // With a heuristic of nothing this is Dijkstra, so whatever it stops at is the cheapest of the targets to get to
+    fn search_for<T, H>(
+        &self,
+        start: GridPosition,
+        kind: InhabitantType,
+        is_target: T,
+        heuristic: H,
+    ) -> Option<(GridPosition, HashMap<GridPosition, Option<GridPosition>>)>
+    where
+        T: Fn(GridPosition) -> bool,
+        H: Fn(GridPosition) -> usize,
+    {
+        let mut frontier = BinaryHeap::new();
+        frontier.push(Movement {
+            cost: 0,
+            pos: start,
+        });
+
+        let mut came_from = HashMap::new();
+        came_from.insert(start, None);
+        let mut cost_so_far = HashMap::new();
+        cost_so_far.insert(start, 0);
+
+        while let Some(current) = frontier.pop() {
+            if is_target(current.pos) {
+                return Some((current.pos, came_from));
+            }
+
+            let neighbors = self.get_neighbors(current.pos).into_values();
+            for next in neighbors.chain(self.get_lift_stops(current.pos)) {
+                let new_cost = cost_so_far.get(&current.pos).unwrap_or(&0)
+                    + self.movement_cost(&current.pos, next);
+                if new_cost < *cost_so_far.get(&next.pos).unwrap_or(&usize::MAX) {
+                    cost_so_far.insert(next.pos, new_cost);
+                    if next.is_passable(kind) {
+                        frontier.push(Movement {
+                            cost: new_cost + heuristic(next.pos),
+                            pos: next.pos,
+                        });
+                        came_from.insert(next.pos, Some(current.pos));
                     }
-                    WallDirection::Full => mb.rectangle(DrawMode::fill(), tile_rect, WALL_COLOR)?,
-                },
-                TileType::Door(_) => mb.rectangle(DrawMode::fill(), tile_rect, Color::WHITE)?,
+                }
+            }
+        }
+
+        // If we ran out of places to look, we searched and searched but couldn't find the target
+        None
+    }
+
+    // Compute the cost of moving from a position to a tile. Lower is better
+    // I'd like to use floating point values here, but that's problematic for
+    // sorting in the binary heap. So instead we'll just multiply everything by 1,000
+    fn movement_cost(&self, current: &GridPosition, next: &Tile) -> usize {
+        // Cost is distance between the grid positions, plus however long it takes to get through any door
+        let mut cost = (current.distance(next.pos) * 1000) as usize;
+        if self.fires.contains_key(&next.pos) {
+            cost += BURNING_COST;
+        }
+        if next.pos.z != current.z {
+            cost += next.lift.map_or(0, |lift| lift.extra_cost());
+        }
+        match next.kind {
+            // Without power a closed door won't open by itself, and has to be forced like a broken one
+            TileType::Door(door)
+                if door.state == DoorState::Closed && !self.is_powered(next.pos) =>
+            {
+                let jammed = Door {
+                    state: DoorState::Broken,
+                    ..door
+                };
+                cost + jammed.extra_cost()
+            }
+            TileType::Door(door) => cost + door.extra_cost(),
+            _ => cost,
+        }
+    }
+
+    // Calculate the heuristic value between two grid positions, to be used for pathfinding
+    fn movement_heuristic(&self, a: GridPosition, b: GridPosition) -> u32 {
+        (a.distance(b) * 1000) as u32
+    }
+
+    // Given a start and an end, generate a path that doesn't include walls or doors this type of inhabitant can't get through
+    // TODO: This needs to be able to path outside of the station somehow (for ghosts or future things like going to exterior ships or asteroids, etc)
+    pub fn path_to(
+        &self,
+        start: GridPosition,
+        target: GridPosition,
+        kind: InhabitantType,
+    ) -> Vec<GridPosition> {
+        // Get list of reachable positions from start to end, return early if no path
+        let reachable = self.search(start, target, kind);
+        if reachable.is_empty() {
+            return Vec::new();
+        }
+
+        Station::follow_path(&reachable, start, target)
+    }
+
+    // Like `path_to`, but to whichever of the targets is quickest to get to
+    pub fn path_to_any(
+        &self,
+        start: GridPosition,
+        targets: &BTreeSet<GridPosition>,
+        kind: InhabitantType,
+    ) -> Vec<GridPosition> {
+        if targets.is_empty() {
+            return Vec::new();
+        }
+
+        match self.search_for(start, kind, |pos| targets.contains(&pos), |_pos| 0) {
+            Some((target, reachable)) => Station::follow_path(&reachable, start, target),
+            None => Vec::new(),
+        }
+    }
+
+    // Walk back from the target along what a search found, to get the path from the start to it
+    fn follow_path(
+        reachable: &HashMap<GridPosition, Option<GridPosition>>,
+        start: GridPosition,
+        target: GridPosition,
+    ) -> Vec<GridPosition> {
+        // Start at the end and work backwards
+        let mut current = target;
+        let mut path = Vec::new();
+
+        // Follow the reachable list back to the start
+        while current != start {
+            path.push(current);
+            if let Some(next) = reachable.get(&current).unwrap_or(&None) {
+                current = *next;
+            }
+        }
+
+        // Reverse the path to make it from start to end
+        path.reverse();
+        path
+    }
+
+    // Returns grid positions of tiles containing the desired items, closest to `from` first
+    pub fn find_items(&self, kinds: Vec<ItemType>, from: GridPosition) -> Vec<GridPosition> {
+        self.items.nearest(&kinds, from)
+    }
+
+    // How many of the desired items there are on the station, counting what's in containers
+    pub fn count_items(&self, kinds: Vec<ItemType>) -> usize {
+        self.items.count(&kinds)
+    }
+
+    // A path to the nearest of the desired items a type of inhabitant can get to, if there's one they can
+    // Nearest is by how long it takes to walk there, not as the crow flies
+    pub fn path_to_item(
+        &self,
+        start: GridPosition,
+        kinds: Vec<ItemType>,
+        kind: InhabitantType,
+    ) -> Vec<GridPosition> {
+        self.path_to_any(start, &self.items.positions(&kinds), kind)
+    }
+
+    // Update callback on the station, advancing it by `dt`
+    pub fn update(&mut self, dt: Duration) {
+        // Anything could have been plugged in or unplugged since last time
+        self.power_time += dt.as_secs_f32();
+        if self.power_time >= POWER_STEP {
+            self.power_time = 0.0;
+            self.update_power();
+        }
+
+        // Update all items, with the power they had when the networks were last worked out
+        // Only the tiles with something on them, which on a big station is hardly any of them
+        for pos in self.items.all_positions() {
+            let tile = match self.tiles.get_mut(&pos) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            let ambient = self
+                .temperature
+                .get(&tile.pos)
+                .copied()
+                .unwrap_or(ROOM_TEMPERATURE);
+            let powered = self.powered.contains(&tile.pos);
+            for item in tile.items.iter_mut() {
+                item.update(dt, powered, ambient);
+            }
+        }
+
+        self.update_atmosphere(dt);
+        self.update_temperature(dt);
+    }
+
+    // Start every tile off at room temperature
+    pub fn fill_temperature(&mut self) {
+        self.temperature = self
+            .tiles
+            .keys()
+            .map(|pos| (*pos, ROOM_TEMPERATURE))
+            .collect();
+        self.settled_heat.clear();
+    }
+
+    // How hot a tile is, in kelvin, if there's a tile there
+    pub fn get_temperature(&self, pos: GridPosition) -> Option<f32> {
+        self.temperature.get(&pos).copied()
+    }
+
+    pub fn set_temperature(&mut self, pos: GridPosition, temperature: f32) {
+        if let Some(t) = self.temperature.get_mut(&pos) {
+            *t = temperature;
+            self.unsettle(pos);
+        }
+    }
+
+    // Let heat spread between tiles as well as each of them conducts it, and radiate away wherever a tile is open to space
+    fn update_temperature(&mut self, dt: Duration) {
+        self.heat_time += dt.as_secs_f32();
+        while self.heat_time >= HEAT_STEP {
+            self.heat_time -= HEAT_STEP;
+            self.step_temperature(HEAT_STEP);
+        }
+    }
+
+    fn step_temperature(&mut self, dt: f32) {
+        // Like the air, work out every change first and then apply them all at once
+        // Only chunks where the heat's still moving get looked at, and each tile there adds up everything going in and out of it
+        let mut changes: Vec<(GridPosition, f32)> = Vec::new();
+        for (chunk_pos, chunk) in self.tiles.chunks() {
+            if self.settled_heat.contains(&chunk_pos) {
+                continue;
+            }
+            for tile in chunk.tiles() {
+                let pos = tile.pos;
+                let temperature = match self.temperature.get(&pos) {
+                    Some(temperature) => *temperature,
+                    None => continue,
+                };
+                let here = conductance(&tile.kind);
+
+                // A breach is a hole, and as cold as the space outside it
+                if tile.is_breached() {
+                    changes.push((pos, SPACE_TEMPERATURE - temperature));
+                    continue;
+                }
+
+                let mut change = 0.0;
+                for other in self.flows_to(pos) {
+                    if other.is_breached() {
+                        continue;
+                    }
+                    if let Some(there) = self.temperature.get(&other.pos) {
+                        let heat =
+                            flow(temperature, *there, here.min(conductance(&other.kind)), dt);
+                        change -= heat;
+
+                        // A tile that's been left alone won't work this out for itself, so it gets its share from here
+                        if self.settled_heat.contains(&ChunkPos::of(other.pos)) {
+                            changes.push((other.pos, heat));
+                        }
+                    }
+                }
+
+                let open_sides = self.open_sides(pos);
+                if open_sides > 0 {
+                    change -= flow(temperature, SPACE_TEMPERATURE, here, dt) * open_sides as f32;
+                }
+
+                // Heaters and coolers only run when they're powered and their tile needs it
+                if !tile.items.is_empty() && self.powered.contains(&pos) {
+                    for item in tile.items.iter() {
+                        change += match item.get_type() {
+                            ItemType::Fixture(FixtureType::Heater)
+                                if temperature < HEATER_SETPOINT =>
+                            {
+                                (HEATER_RATE * dt).min(HEATER_SETPOINT - temperature)
+                            }
+                            ItemType::Fixture(FixtureType::Cooler)
+                                if temperature > COOLER_SETPOINT =>
+                            {
+                                -(HEATER_RATE * dt).min(temperature - COOLER_SETPOINT)
+                            }
+                            _ => 0.0,
+                        };
+                    }
+                }
+                changes.push((pos, change));
+            }
+        }
+
+        // Chunks where no tile's warming up or cooling down have settled, until something stirs them up again
+        // Heat can still be flowing through them, as long as as much is coming in as going out
+        let mut moving = BTreeSet::new();
+        for (pos, change) in changes {
+            *self.temperature.get_mut(&pos).unwrap() += change;
+            if change.abs() > SETTLED_HEAT {
+                moving.insert(ChunkPos::of(pos));
+            }
+        }
+        self.settled_heat = self
+            .tiles
+            .chunks()
+            .map(|(chunk_pos, _chunk)| chunk_pos)
+            .filter(|chunk_pos| !moving.contains(chunk_pos))
+            .collect();
+    }
+
+    // Work out which tiles are wired together, and how much power each lot of them has to go round
+    // Only the networks around tiles that have changed since last time get worked out again
+    pub fn update_power(&mut self) {
+        if self.rewired.is_empty() {
+            return;
+        }
+
+        // Taking wiring out might split a network in two, so the whole of it gets flooded again
+        // Wiring put in only joins networks up, so it's flooded from by itself
+        let mut starts = Vec::new();
+        let mut stale = BTreeSet::new();
+        for pos in mem::take(&mut self.rewired) {
+            let wired = self.get_tile(pos).is_some_and(|tile| tile.carries_power());
+            match self.network_at.get(&pos) {
+                Some(id) if !wired => {
+                    stale.insert(*id);
+                }
+                None if wired => starts.push(pos),
+                _ => (),
+            }
+        }
+        for id in stale {
+            for pos in self.networks.remove(&id).unwrap().tiles {
+                self.network_at.remove(&pos);
+                starts.push(pos);
+            }
+        }
+
+        for start in starts {
+            let wired = self
+                .get_tile(start)
+                .is_some_and(|tile| tile.carries_power());
+            if !wired || self.network_at.contains_key(&start) {
+                continue;
+            }
+
+            // Flood out over the wiring that isn't on a network yet, noting the networks it runs into
+            let mut tiles = BTreeSet::new();
+            let mut joins = BTreeSet::new();
+            let mut frontier = vec![start];
+            tiles.insert(start);
+            while let Some(pos) = frontier.pop() {
+                for next in self.adjacent(pos).flatten() {
+                    if !next.carries_power() {
+                        continue;
+                    }
+                    match self.network_at.get(&next.pos) {
+                        Some(id) => {
+                            joins.insert(*id);
+                        }
+                        None if tiles.insert(next.pos) => frontier.push(next.pos),
+                        None => (),
+                    }
+                }
+            }
+
+            // Then it all goes into the biggest of those, or a new network if it didn't run into any
+            let id = joins
+                .iter()
+                .copied()
+                .max_by_key(|id| self.networks[id].tiles.len())
+                .unwrap_or_else(|| {
+                    self.next_network_id += 1;
+                    self.networks
+                        .insert(self.next_network_id, PowerNetwork::new(BTreeSet::new()));
+                    self.next_network_id
+                });
+            for other in joins.into_iter().filter(|other| *other != id) {
+                tiles.append(&mut self.networks.remove(&other).unwrap().tiles);
+            }
+            for pos in tiles.iter() {
+                self.network_at.insert(*pos, id);
+            }
+            self.networks.get_mut(&id).unwrap().tiles.append(&mut tiles);
+        }
+
+        // Then add up everything plugged into each one
+        for network in self.networks.values_mut() {
+            network.supply = 0;
+            network.demand = 0;
+        }
+        let mut plugged_in = Vec::new();
+        for tile in self.tiles.values() {
+            let door = matches!(tile.kind, TileType::Door(_));
+            let elevator = tile.lift == Some(LiftType::Elevator);
+            if tile.items.is_empty() && !door && !elevator {
+                continue;
+            }
+            let id = match self.network_for(tile.pos) {
+                Some(id) => id,
+                None => continue,
+            };
+            let network = self.networks.get_mut(&id).unwrap();
+            for item in tile.items.iter() {
+                network.supply += item.get_power_output();
+                network.demand += item.get_power_draw();
+            }
+            if door {
+                network.demand += DOOR_DRAW;
+            }
+            if elevator {
+                network.demand += ELEVATOR_DRAW;
+            }
+            if !tile.items.is_empty() {
+                plugged_in.push((tile.pos, id));
+            }
+        }
+
+        // Which says what has power until the next time something changes
+        self.powered = plugged_in
+            .into_iter()
+            .filter(|(_pos, id)| self.networks[id].is_powered())
+            .map(|(pos, _id)| pos)
+            .collect();
+
+        // And heaters and coolers might have come on or gone off with it
+        self.settled_heat.clear();
+    }
+
+    // Which network something on a tile is plugged into, if any
+    // Things on a floor with no conduit plug into the wiring of a wall (or conduit) next to them
+    fn network_for(&self, pos: GridPosition) -> Option<usize> {
+        self.network_at.get(&pos).copied().or_else(|| {
+            self.adjacent(pos)
+                .flatten()
+                .find_map(|tile| self.network_at.get(&tile.pos).copied())
+        })
+    }
+
+    // Get the power network something on a tile would be plugged into, if any
+    pub fn get_network_at(&self, pos: GridPosition) -> Option<&PowerNetwork> {
+        self.network_for(pos).map(|id| &self.networks[&id])
+    }
+
+    pub fn get_networks(&self) -> impl Iterator<Item = &PowerNetwork> {
+        self.networks.values()
+    }
+
+    // Does something on this tile have the power it needs?
+    pub fn is_powered(&self, pos: GridPosition) -> bool {
+        self.get_network_at(pos)
+            .is_some_and(|network| network.is_powered())
+    }
+
+    // Fill everywhere you can walk with fresh air
+    pub fn fill_atmosphere(&mut self) {
+        self.atmosphere = self
+            .tiles
+            .values()
+            .filter(|tile| tile.is_walkable())
+            .map(|tile| (tile.pos, Gas::air()))
+            .collect();
+        self.settled_air.clear();
+    }
+
+    // The air in a tile, if it can hold any
+    pub fn get_gas(&self, pos: GridPosition) -> Option<&Gas> {
+        self.atmosphere.get(&pos)
+    }
+
+    pub fn get_gas_mut(&mut self, pos: GridPosition) -> Option<&mut Gas> {
+        self.unsettle(pos);
+        self.atmosphere.get_mut(&pos)
+    }
+
+    // Let the air spread out between tiles that are open to each other, and rush out wherever a tile is open to space
+    fn update_atmosphere(&mut self, dt: Duration) {
+        self.atmosphere_time += dt.as_secs_f32();
+        while self.atmosphere_time >= ATMOSPHERE_STEP {
+            self.atmosphere_time -= ATMOSPHERE_STEP;
+            self.step_atmosphere(ATMOSPHERE_STEP);
+        }
+    }
+
+    fn step_atmosphere(&mut self, dt: f32) {
+        // Work out every flow first, then apply them all at once, so the order tiles are visited in doesn't matter
+        // Only chunks where the air's still moving get looked at, though that can set it moving in the ones next door
+        let mut flows: Vec<(GridPosition, Option<GridPosition>, Gas)> = Vec::new();
+        for (chunk_pos, chunk) in self.tiles.chunks() {
+            if self.settled_air.contains(&chunk_pos) {
+                continue;
+            }
+            for tile in chunk.tiles() {
+                let gas = match self.atmosphere.get(&tile.pos) {
+                    Some(gas) if tile.lets_air_through() => gas,
+                    _ => continue,
+                };
+
+                for other in self.flows_to(tile.pos) {
+                    // Each pair only once, and from this side if the other's been left alone
+                    let behind = other.pos < tile.pos
+                        && !self.settled_air.contains(&ChunkPos::of(other.pos));
+                    if behind || !other.lets_air_through() {
+                        continue;
+                    }
+                    if let Some(there) = self.atmosphere.get(&other.pos) {
+                        let flow = gas.flow_to(there, DIFFUSION_RATE, dt);
+                        flows.push((tile.pos, Some(other.pos), flow));
+                    }
+                }
+
+                // Anywhere there's no tile at all, or a hole in the wall, is space
+                let open_sides = self.open_sides(tile.pos);
+                if open_sides > 0 {
+                    let vented = gas.flow_to(&Gas::vacuum(), VENT_RATE, dt) * open_sides as f32;
+                    flows.push((tile.pos, None, vented));
+                }
+            }
+        }
+
+        // Chunks where hardly anything moved have settled, until something stirs them up again
+        let mut moving = BTreeSet::new();
+        for (from, to, flow) in flows {
+            let gas = self.atmosphere.get_mut(&from).unwrap();
+            *gas = *gas - flow;
+            if let Some(to) = to {
+                let gas = self.atmosphere.get_mut(&to).unwrap();
+                *gas = *gas + flow;
+            }
+            if flow.largest() > SETTLED_FLOW {
+                moving.insert(ChunkPos::of(from));
+                moving.extend(to.map(ChunkPos::of));
+            }
+        }
+        self.settled_air = self
+            .tiles
+            .chunks()
+            .map(|(chunk_pos, _chunk)| chunk_pos)
+            .filter(|chunk_pos| !moving.contains(chunk_pos))
+            .collect();
+    }
+
+    // How many sides of a tile are open to space, with no tile there or a breach
+    fn open_sides(&self, pos: GridPosition) -> usize {
+        self.adjacent(pos)
+            .filter(|tile| tile.is_none_or(|tile| tile.is_breached()))
+            .count()
+    }
+
+    // Something's changed at a position, so the air and heat around it have to start moving again
+    fn unsettle(&mut self, pos: GridPosition) {
+        let around = Station::neighborhood(pos);
+        for pos in around.iter().chain(&[pos.above(), pos.below()]) {
+            let chunk_pos = ChunkPos::of(*pos);
+            self.settled_air.remove(&chunk_pos);
+            self.settled_heat.remove(&chunk_pos);
+        }
+    }
+
+    // Is this a wall standing between the station and space?
+    pub fn is_hull(&self, pos: GridPosition) -> bool {
+        match self.get_tile(pos) {
+            Some(tile) if matches!(tile.kind, TileType::Wall(_)) && !tile.is_breached() => {
+                self.open_sides(pos) > 0
+            }
+            _ => false,
+        }
+    }
+
+    // Every hole in the hull
+    pub fn get_breaches(&self) -> impl Iterator<Item = GridPosition> + '_ {
+        self.breaches.iter().copied()
+    }
+
+    // Knock some integrity off a wall, and if that's the last of it, open it to space
+    pub fn damage_wall(&mut self, pos: GridPosition, amount: u8, events: &mut EventBus) {
+        let tile = match self.tiles.get_mut(&pos) {
+            Some(tile) if matches!(tile.kind, TileType::Wall(_)) => tile,
+            _ => return,
+        };
+        if tile.damage(amount) {
+            self.breaches.insert(pos);
+            self.set_temperature(pos, SPACE_TEMPERATURE);
+            self.invalidate_mesh(pos);
+            events.publish(SimEvent::HullBreached { pos });
+        }
+    }
+
+    // Roll for micrometeorites hitting the hull. This is separate from `update` since it needs the dice
+    pub fn update_hull(&mut self, dt: Duration, rng: &mut Rand32, events: &mut EventBus) {
+        self.hull_time += dt.as_secs_f32();
+        while self.hull_time >= HULL_STEP {
+            self.hull_time -= HULL_STEP;
+            if rng.rand_float() < METEOR_CHANCE * HULL_STEP {
+                self.meteor_strike(rng, events);
+            }
+        }
+    }
+
+    // Hit a random bit of the hull, on any deck
+    pub fn meteor_strike(&mut self, rng: &mut Rand32, events: &mut EventBus) {
+        let hull: Vec<GridPosition> = self
+            .tiles
+            .keys()
+            .copied()
+            .filter(|pos| self.is_hull(*pos))
+            .collect();
+        if hull.is_empty() {
+            return;
+        }
+
+        let pos = hull[rng.rand_range(0..hull.len() as u32) as usize];
+        let damage = rng.rand_range(METEOR_DAMAGE) as u8;
+        events.publish(SimEvent::MeteorStrike { pos, damage });
+        self.damage_wall(pos, damage, events);
+    }
+
+    // Set a tile alight, if there's a floor there that isn't already burning and enough air to burn in
+    pub fn ignite(&mut self, pos: GridPosition, cause: FireCause, events: &mut EventBus) -> bool {
+        let tile = match self.tiles.get(&pos) {
+            Some(tile) if tile.kind == TileType::Floor => tile,
+            _ => return false,
+        };
+        let has_air = self
+            .atmosphere
+            .get(&pos)
+            .is_some_and(|gas| gas.o2_pressure() >= MIN_FIRE_O2_PRESSURE);
+        if !has_air || self.fires.contains_key(&pos) {
+            return false;
+        }
+
+        let flammable = tile.items.iter().filter(|item| item.is_flammable()).count();
+        let fuel = FLOOR_FUEL + ITEM_FUEL * flammable as f32;
+        self.fires.insert(pos, Fire::new(fuel, cause));
+        events.publish(SimEvent::FireStarted { pos, cause });
+        true
+    }
+
+    pub fn get_fire(&self, pos: GridPosition) -> Option<&Fire> {
+        self.fires.get(&pos)
+    }
+
+    // Every tile that's on fire
+    pub fn get_fires(&self) -> impl Iterator<Item = GridPosition> + '_ {
+        self.fires.keys().copied()
+    }
+
+    // Knock a fire down by `amount` of intensity, putting it out if that's all it had
+    pub fn extinguish(&mut self, pos: GridPosition, amount: f32, events: &mut EventBus) {
+        if let Some(fire) = self.fires.get_mut(&pos) {
+            fire.intensity -= amount;
+            if !fire.is_burning() {
+                self.fires.remove(&pos);
+                events.publish(SimEvent::FireOut { pos });
+            }
+        }
+    }
+
+    // Burn, spread and start fires. This is separate from `update` since it needs the dice
+    pub fn update_fires(&mut self, dt: Duration, rng: &mut Rand32, events: &mut EventBus) {
+        self.fire_time += dt.as_secs_f32();
+        while self.fire_time >= FIRE_STEP {
+            self.fire_time -= FIRE_STEP;
+            self.step_fires(FIRE_STEP, rng, events);
+        }
+    }
+
+    fn step_fires(&mut self, dt: f32, rng: &mut Rand32, events: &mut EventBus) {
+        // Anything running off the wiring might short out
+        let mut faults = Vec::new();
+        for pos in self.powered.iter() {
+            let tile = match self.tiles.get(pos) {
+                Some(tile) => tile,
+                None => continue,
+            };
+            for item in tile.items.iter() {
+                let powered = item.get_power_draw() > 0 || item.get_power_output() > 0;
+                if powered && rng.rand_float() < ELECTRICAL_FAULT_CHANCE * dt {
+                    faults.push(tile.pos);
+                }
+            }
+        }
+        for pos in faults {
+            self.ignite(pos, FireCause::Electrical, events);
+        }
+
+        // Then every fire eats up the air, heats its tile and maybe catches the tiles next to it
+        let mut spreading = BTreeSet::new();
+        let burning: Vec<GridPosition> = self.fires.keys().copied().collect();
+        for pos in burning {
+            let intensity = self.fires[&pos].intensity;
+            let has_air = match self.atmosphere.get_mut(&pos) {
+                Some(gas) if gas.o2_pressure() >= MIN_FIRE_O2_PRESSURE => {
+                    let burnt = (FIRE_O2_RATE * intensity * dt).min(gas.o2);
+                    gas.o2 -= burnt;
+                    gas.co2 += burnt;
+                    true
+                }
+                _ => false,
+            };
+            if let Some(temperature) = self.temperature.get_mut(&pos) {
+                *temperature += FIRE_HEAT_RATE * intensity * dt;
+            }
+            self.unsettle(pos);
+
+            let fire = self.fires.get_mut(&pos).unwrap();
+            if !fire.burn(dt, has_air) {
+                // Anything that was burning with it is gone, unless the fire was smothered first
+                if fire.fuel <= 0.0 {
+                    let Some(tile) = self.tiles.get_mut(&pos) else {
+                        self.fires.remove(&pos);
+                        continue;
+                    };
+                    let (burnt, left): (Vec<Item>, Vec<Item>) = mem::take(&mut tile.items)
+                        .into_iter()
+                        .partition(|item| item.is_flammable());
+                    tile.items = left;
+                    for item in burnt.iter() {
+                        self.items.remove(pos, item);
+                    }
+                    self.rewired.insert(pos);
+                }
+                self.fires.remove(&pos);
+                events.publish(SimEvent::FireOut { pos });
+                continue;
+            }
+
+            for next in self.adjacent(pos).flatten() {
+                if next.kind != TileType::Floor || self.fires.contains_key(&next.pos) {
+                    continue;
+                }
+                let mut chance = SPREAD_CHANCE * intensity * dt;
+                if next.items.iter().any(|item| item.is_flammable()) {
+                    chance *= 3.0;
+                }
+                if rng.rand_float() < chance {
+                    spreading.insert(next.pos);
+                }
+            }
+        }
+        for pos in spreading {
+            self.ignite(pos, FireCause::Spread, events);
+        }
+    }
+
+    // Draw callback, for whichever deck the camera is on
+    // The part of the station the camera can see, in the station's own coordinates
+    fn visible_area(&self, ctx: &Context, camera: &Camera) -> graphics::Rect {
+        let (screen_width, screen_height) = graphics::drawable_size(ctx);
+        let (view_pos, view_size) =
+            camera.viewport(self.pos, Point2::new(screen_width, screen_height));
+        graphics::Rect::new(view_pos.x, view_pos.y, view_size.x, view_size.y)
+    }
+
+    pub fn draw(&self, ctx: &mut Context, camera: &Camera, renderer: Renderer) -> GameResult<()> {
+        let param = DrawParam::default()
+            .dest(self.pos)
+            .offset(camera.pos)
+            .scale(camera.zoom);
+
+        // Only the chunks on screen get drawn
+        let view = self.visible_area(ctx, camera);
+        let visible = |chunk_pos: &ChunkPos| view.overlaps(&Station::chunk_rect(*chunk_pos));
+
+        // The pre-calculated chunks, with the deck below darkened under this one
+        for (z, below) in [(camera.deck - 1, true), (camera.deck, false)] {
+            for (chunk_pos, _chunk) in self.tiles.chunks_on_deck(z) {
+                if !visible(&chunk_pos) {
+                    continue;
+                }
+                match renderer {
+                    Renderer::Mesh => {
+                        if let Some(mesh) = self.meshes.get(&chunk_pos) {
+                            let tint = if below { DECK_BELOW_TINT } else { Color::WHITE };
+                            graphics::draw(ctx, mesh, param.color(tint))?;
+                        }
+                    }
+                    Renderer::Sprites => {
+                        if let Some((lit, darkened)) = self.sprites.get(&chunk_pos) {
+                            graphics::draw(ctx, if below { darkened } else { lit }, param)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Draw items on tiles, batching up the ones there are sprites for
+        let mut item_sprites = match renderer {
+            Renderer::Sprites => self.tileset.as_ref().map(Tileset::batch),
+            Renderer::Mesh => None,
+        };
+        for (_chunk_pos, chunk) in self
+            .tiles
+            .chunks_on_deck(camera.deck)
+            .filter(|(chunk_pos, _chunk)| visible(chunk_pos))
+        {
+            for tile in chunk.tiles() {
+                for item in tile.items.iter() {
+                    match (&mut item_sprites, item.sprite()) {
+                        (Some(batch), Some(part)) => {
+                            batch.add(part.param(Station::tile_corner(tile.pos), Color::WHITE));
+                        }
+                        _ => item.draw(ctx, self.pos, camera)?,
+                    }
+                }
+            }
+        }
+        if let Some(batch) = item_sprites {
+            if !batch.get_sprites().is_empty() {
+                graphics::draw(ctx, &batch, param)?;
+            }
+        }
+
+        // And fires over the top, brighter the more they're raging
+        if !self.fires.keys().any(|pos| pos.z == camera.deck) {
+            return Ok(());
+        }
+        let mb = &mut MeshBuilder::new();
+        for (pos, fire) in self
+            .fires
+            .iter()
+            .filter(|(pos, _fire)| pos.z == camera.deck)
+        {
+            let tile_rect = graphics::Rect::new(
+                (crate::TILE_WIDTH * pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+                (crate::TILE_WIDTH * pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+                crate::TILE_WIDTH,
+                crate::TILE_WIDTH,
+            );
+            let color = Color {
+                a: 0.3 + 0.6 * fire.intensity,
+                ..FIRE_COLOR
+            };
+            mb.rectangle(DrawMode::fill(), tile_rect, color)?;
+        }
+        let mesh = mb.build(ctx)?;
+        graphics::draw(
+            ctx,
+            &mesh,
+            DrawParam::default()
+                .dest(self.pos)
+                .offset(camera.pos)
+                .scale(camera.zoom),
+        )
+    }
+
+    // Tint every tile by how hot it is, for seeing where heat goes
+    pub fn draw_heatmap(&self, ctx: &mut Context, camera: &Camera) -> GameResult<()> {
+        // Only the tiles in chunks on screen, as temperatures change too often to be worth keeping a mesh for
+        let view = self.visible_area(ctx, camera);
+        let on_screen: Vec<(GridPosition, f32)> = self
+            .tiles
+            .chunks_on_deck(camera.deck)
+            .filter(|(chunk_pos, _chunk)| view.overlaps(&Station::chunk_rect(*chunk_pos)))
+            .flat_map(|(_chunk_pos, chunk)| chunk.tiles())
+            .filter_map(|tile| Some((tile.pos, *self.temperature.get(&tile.pos)?)))
+            .collect();
+        if on_screen.is_empty() {
+            return Ok(());
+        }
+
+        let mb = &mut MeshBuilder::new();
+        for (pos, temperature) in on_screen {
+            let tile_rect = graphics::Rect::new(
+                (crate::TILE_WIDTH * pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+                (crate::TILE_WIDTH * pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+                crate::TILE_WIDTH,
+                crate::TILE_WIDTH,
+            );
+            let heat =
+                ((temperature - HEATMAP_COLD) / (HEATMAP_HOT - HEATMAP_COLD)).clamp(0.0, 1.0);
+            let color = Color::new(heat, 0.0, 1.0 - heat, HEATMAP_ALPHA);
+            mb.rectangle(DrawMode::fill(), tile_rect, color)?;
+        }
+        let mesh = mb.build(ctx)?;
+        graphics::draw(
+            ctx,
+            &mesh,
+            DrawParam::default()
+                .dest(self.pos)
+                .offset(camera.pos)
+                .scale(camera.zoom),
+        )
+    }
+
+    // Redden every wall by how damaged it is, for seeing where the hull needs patching
+    pub fn draw_damage(&self, ctx: &mut Context, camera: &Camera) -> GameResult<()> {
+        let damaged: Vec<&Tile> = self
+            .tiles
+            .values()
+            .filter(|tile| tile.pos.z == camera.deck && tile.integrity < MAX_INTEGRITY)
+            .collect();
+        if damaged.is_empty() {
+            return Ok(());
+        }
+
+        let mb = &mut MeshBuilder::new();
+        for tile in damaged {
+            let tile_rect = graphics::Rect::new(
+                (crate::TILE_WIDTH * tile.pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+                (crate::TILE_WIDTH * tile.pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+                crate::TILE_WIDTH,
+                crate::TILE_WIDTH,
+            );
+            let damage = 1.0 - tile.integrity as f32 / MAX_INTEGRITY as f32;
+            let color = Color {
+                a: DAMAGE_ALPHA * damage,
+                ..DAMAGE_COLOR
+            };
+            if tile.is_breached() {
+                mb.rectangle(DrawMode::stroke(2.0), tile_rect, DAMAGE_COLOR)?;
+            } else {
+                mb.rectangle(DrawMode::fill(), tile_rect, color)?;
+            }
+        }
+        let mesh = mb.build(ctx)?;
+        graphics::draw(
+            ctx,
+            &mesh,
+            DrawParam::default()
+                .dest(self.pos)
+                .offset(camera.pos)
+                .scale(camera.zoom),
+        )
+    }
+
+    // Whether the mesh or sprites for a renderer, and the minimap, are up to date
+    // Changing a tile throws away the mesh and sprites for its chunk and the minimap for its deck, so they need building again before drawing
+    pub fn has_mesh(&self, renderer: Renderer) -> bool {
+        let minimap = self
+            .tiles
+            .chunks()
+            .all(|(chunk_pos, _chunk)| self.minimap.contains_key(&chunk_pos.z));
+        minimap
+            && match renderer {
+                Renderer::Mesh => self
+                    .tiles
+                    .chunks()
+                    .all(|(chunk_pos, _chunk)| self.meshes.contains_key(&chunk_pos)),
+                Renderer::Sprites => self
+                    .tiles
+                    .chunks()
+                    .all(|(chunk_pos, _chunk)| self.sprites.contains_key(&chunk_pos)),
+            }
+    }
+
+    // Throw away the mesh and sprites for the chunk a tile is in, and the minimap of its deck, after changing how the tile looks
+    fn invalidate_mesh(&mut self, pos: GridPosition) {
+        let chunk_pos = ChunkPos::of(pos);
+        self.meshes.remove(&chunk_pos);
+        self.sprites.remove(&chunk_pos);
+        self.minimap.remove(&pos.z);
+
+        // If the station's grown or shrunk, every deck's minimap is laid out differently
+        if let Some((min, max)) = self.bounds {
+            let inside = pos.x > min.x && pos.x < max.x && pos.y > min.y && pos.y < max.y;
+            let on_edge =
+                !inside && pos.x >= min.x && pos.x <= max.x && pos.y >= min.y && pos.y <= max.y;
+            if !(inside || (on_edge && self.has_tile(pos))) {
+                self.bounds = None;
+                self.minimap.clear();
+            }
+        }
+    }
+
+    // Create whatever a renderer needs to draw the station that isn't already up to date
+    pub fn build_mesh(&mut self, ctx: &mut Context, renderer: Renderer) -> GameResult<()> {
+        self.bounds = self.get_bounds();
+        if let Some(bounds) = self.bounds {
+            for deck in self.get_decks() {
+                if !self.minimap.contains_key(&deck) {
+                    let layer = minimap::layer(ctx, self, deck, bounds)?;
+                    self.minimap.insert(deck, layer);
+                }
+            }
+        }
+
+        match renderer {
+            Renderer::Mesh => {
+                for (chunk_pos, chunk) in self.tiles.chunks() {
+                    if self.meshes.contains_key(&chunk_pos) {
+                        continue;
+                    }
+                    let mesh = Station::chunk_mesh(chunk)?.build(ctx)?;
+                    self.meshes.insert(chunk_pos, mesh);
+                }
+            }
+            Renderer::Sprites => {
+                if self.tileset.is_none() {
+                    self.tileset = Some(Tileset::new(ctx)?);
+                }
+                let tileset = self.tileset.as_ref().unwrap();
+
+                // Batches can't be tinted when they're drawn, so each chunk gets a darkened copy for under the deck above
+                for (chunk_pos, chunk) in self.tiles.chunks() {
+                    if self.sprites.contains_key(&chunk_pos) {
+                        continue;
+                    }
+                    let mut lit = tileset.batch();
+                    let mut darkened = tileset.batch();
+                    for (lit_param, darkened_param) in Station::chunk_sprites(chunk) {
+                        lit.add(lit_param);
+                        darkened.add(darkened_param);
+                    }
+                    self.sprites.insert(chunk_pos, (lit, darkened));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Every chunk of the station, for drawing it a chunk at a time
+    pub fn get_chunks(&self) -> impl Iterator<Item = (ChunkPos, &Chunk)> {
+        self.tiles.chunks()
+    }
+
+    // The shapes the mesh renderer draws a chunk with, ready to be built into a mesh
+    pub fn chunk_mesh(chunk: &Chunk) -> GameResult<MeshBuilder> {
+        let mut mb = MeshBuilder::new();
+        for tile in chunk.tiles() {
+            Station::build_tile_mesh(&mut mb, tile)?;
+        }
+        Ok(mb)
+    }
+
+    // How each sprite in a chunk is drawn, as it is and darkened for under the deck above
+    pub fn chunk_sprites(chunk: &Chunk) -> Vec<(DrawParam, DrawParam)> {
+        let mut params = Vec::new();
+        for tile in chunk.tiles() {
+            let corner = Station::tile_corner(tile.pos);
+            for part in Station::tile_sprites(tile) {
+                params.push((
+                    part.param(corner, Color::WHITE),
+                    part.param(corner, DECK_BELOW_TINT),
+                ));
+            }
+        }
+        params
+    }
+
+    // The area a chunk is drawn over, relative to the station
+    fn chunk_rect(chunk_pos: ChunkPos) -> graphics::Rect {
+        let corner = Station::tile_corner(GridPosition::new(
+            chunk_pos.x * CHUNK_SIZE,
+            chunk_pos.y * CHUNK_SIZE,
+        ));
+        let size = crate::TILE_WIDTH * CHUNK_SIZE as f32;
+        graphics::Rect::new(corner.x, corner.y, size, size)
+    }
+
+    // Where the top-left corner of a tile is drawn, relative to the station
+    fn tile_corner(pos: GridPosition) -> Point2 {
+        Point2::new(
+            (crate::TILE_WIDTH * pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+            (crate::TILE_WIDTH * pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+        )
+    }
+
+    // The colour a door is drawn in, to show what state it's in
+    fn door_color(state: DoorState) -> Color {
+        match state {
+            DoorState::Open => DOOR_OPEN_COLOR,
+            DoorState::Closed => DOOR_CLOSED_COLOR,
+            DoorState::Locked => DOOR_LOCKED_COLOR,
+            DoorState::Broken => DOOR_BROKEN_COLOR,
+        }
+    }
+
+    // The sprites a tile is drawn with, bottom first, laid out the same way as `build_tile_mesh` draws it
+    fn tile_sprites(tile: &Tile) -> Vec<SpritePart> {
+        if tile.is_breached() {
+            return vec![SpritePart::new(Sprite::HoledPanel)];
+        }
+
+        let floor = SpritePart::new(Sprite::Grating);
+        let wall = SpritePart::new(Sprite::Panel);
+        let top = wall.covering(0.0, 0.0, 1.0, 0.5);
+        let bottom = wall.covering(0.0, 0.5, 1.0, 0.5);
+        let left = wall.covering(0.0, 0.0, 0.5, 1.0);
+        let right = wall.covering(0.5, 0.0, 0.5, 1.0);
+        let across = wall.covering(0.0, 0.25, 1.0, 0.5);
+        let down = wall.covering(0.25, 0.0, 0.5, 1.0);
+
+        let mut parts = match &tile.kind {
+            TileType::Floor => vec![floor],
+            TileType::Wall(wall_direction) => match wall_direction {
+                WallDirection::Full => vec![wall],
+
+                // The corners of the hull are cut off diagonally
+                WallDirection::ExteriorCornerTopLeft => {
+                    vec![SpritePart::new(Sprite::CornerTopLeft)]
+                }
+                WallDirection::ExteriorCornerTopRight => {
+                    vec![SpritePart::new(Sprite::CornerTopRight)]
+                }
+                WallDirection::ExteriorCornerBottomLeft => {
+                    vec![SpritePart::new(Sprite::CornerBottomLeft)]
+                }
+                WallDirection::ExteriorCornerBottomRight => {
+                    vec![SpritePart::new(Sprite::CornerBottomRight)]
+                }
+
+                // Walls along the hull are half wall on the outside, half floor on the inside
+                WallDirection::ExteriorTop => vec![floor, top],
+                WallDirection::ExteriorBottom => vec![floor, bottom],
+                WallDirection::ExteriorLeft => vec![floor, left],
+                WallDirection::ExteriorRight => vec![floor, right],
+
+                // Walls inside run through the middle of the tile, or turn a corner in it
+                WallDirection::InteriorVertical => vec![floor, down],
+                WallDirection::InteriorHorizontal => vec![floor, across],
+                WallDirection::InteriorCross => vec![floor, across, down],
+                WallDirection::InteriorCornerTopLeft => vec![floor, top, left],
+                WallDirection::InteriorCornerTopRight => vec![floor, top, right],
+                WallDirection::InteriorCornerBottomLeft => vec![floor, bottom, left],
+                WallDirection::InteriorCornerBottomRight => vec![floor, bottom, right],
+            },
+            TileType::Door(door) => {
+                // Doors are a floor with a panel across it, coloured by state, and broken ones look it
+                let panel = match door.direction {
+                    WallDirection::InteriorHorizontal => across,
+                    _ => down,
+                };
+                let panel = match door.state {
+                    DoorState::Broken => SpritePart {
+                        sprite: Sprite::ScuffedPanel,
+                        ..panel
+                    },
+                    _ => panel,
+                };
+                vec![floor, panel.tinted(Station::door_color(door.state))]
+            }
+        };
+
+        // Ladders and elevators get steps up to the next deck
+        match tile.lift {
+            Some(LiftType::Ladder) => {
+                parts.push(SpritePart::new(Sprite::StairsLeft).tinted(LIFT_COLOR))
+            }
+            Some(LiftType::Elevator) => {
+                parts.push(SpritePart::new(Sprite::StairsRight).tinted(LIFT_COLOR))
+            }
+            None => (),
+        }
+
+        parts
+    }
+
+    // Add the drawing of one tile to a mesh
+    fn build_tile_mesh(mb: &mut MeshBuilder, tile: &Tile) -> GameResult<()> {
+        let tile_rect = graphics::Rect::new(
+            (crate::TILE_WIDTH * tile.pos.x as f32) - (crate::TILE_WIDTH / 2.0),
+            (crate::TILE_WIDTH * tile.pos.y as f32) - (crate::TILE_WIDTH / 2.0),
+            crate::TILE_WIDTH,
+            crate::TILE_WIDTH,
+        );
+        let center = tile_rect.center();
+
+        if tile.is_breached() {
+            mb.rectangle(DrawMode::fill(), tile_rect, BREACH_COLOR)?;
+            return Ok(());
+        }
+
+        match &tile.kind {
+            TileType::Floor => {
+                // Fill the floor
+                mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?
+            }
+            TileType::Wall(wall_direction) => match wall_direction {
+                WallDirection::ExteriorCornerTopLeft => {
+                    // Fill the bottom-right quarter of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        center.x,
+                        center.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::ExteriorCornerTopRight => {
+                    // Fill the bottom-left quarter of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        center.x + 1.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::ExteriorCornerBottomLeft => {
+                    // Fill the top-right quarter of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        center.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y + 1.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::ExteriorCornerBottomRight => {
+                    // Fill the top-left quarter of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections on the outside of the fill. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y + 1.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        center.x + 1.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::ExteriorTop => {
+                    // Fill the bottom-half of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw the top-half of the tile as wall
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::ExteriorBottom => {
+                    // Fill the top-half of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw the bottom-half of the tile as wall
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y + 1.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::ExteriorLeft => {
+                    // Fill the right-half of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        center.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw the left-half of the tile as wall
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::ExteriorRight => {
+                    // Fill the left-half of the tile as a floor
+                    let floor_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), floor_rect, FLOOR_COLOR)?;
+
+                    // Draw the right-half of the tile as wall
+                    let wall_rect = graphics::Rect::new(
+                        center.x + 1.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::InteriorVertical => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Create a vertical wall centered to the tile
+                    let wall_rect = graphics::Rect::new(
+                        center.x - crate::TILE_WIDTH / 4.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::InteriorHorizontal => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Create a horizontal wall centered to the tile
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y - crate::TILE_WIDTH / 4.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::InteriorCross => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Create a horizontal wall centered to the tile
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y - crate::TILE_WIDTH / 4.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+
+                    // Create a vertical wall centered to the tile
+                    let wall_rect = graphics::Rect::new(
+                        center.x - crate::TILE_WIDTH / 4.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?
+                }
+                WallDirection::InteriorCornerTopLeft => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::InteriorCornerTopRight => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        center.x + 1.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::InteriorCornerBottomLeft => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y + 1.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        tile_rect.x,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::InteriorCornerBottomRight => {
+                    // Fill the floor
+                    mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+
+                    // Draw two "wall" sections. One vertical, one horizontal.
+                    let wall_rect = graphics::Rect::new(
+                        tile_rect.x,
+                        center.y + 1.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect, WALL_COLOR)?;
+                    let wall_rect2 = graphics::Rect::new(
+                        center.x + 1.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0 - 1.0,
+                        crate::TILE_WIDTH,
+                    );
+                    mb.rectangle(DrawMode::fill(), wall_rect2, WALL_COLOR)?
+                }
+                WallDirection::Full => mb.rectangle(DrawMode::fill(), tile_rect, WALL_COLOR)?,
+            },
+            TileType::Door(door) => {
+                // Doors are drawn as a floor with the door across it, coloured by state
+                mb.rectangle(DrawMode::fill(), tile_rect, FLOOR_COLOR)?;
+                let door_rect = match door.direction {
+                    WallDirection::InteriorHorizontal => graphics::Rect::new(
+                        tile_rect.x,
+                        center.y - crate::TILE_WIDTH / 4.0,
+                        crate::TILE_WIDTH,
+                        crate::TILE_WIDTH / 2.0,
+                    ),
+                    _ => graphics::Rect::new(
+                        center.x - crate::TILE_WIDTH / 4.0,
+                        tile_rect.y,
+                        crate::TILE_WIDTH / 2.0,
+                        crate::TILE_WIDTH,
+                    ),
+                };
+                mb.rectangle(DrawMode::fill(), door_rect, Station::door_color(door.state))?
+            }
+        };
+
+        // Ladders get rungs, and elevators a car
+        match tile.lift {
+            Some(LiftType::Ladder) => {
+                for rung in 1..4 {
+                    let y = tile_rect.y + tile_rect.h * rung as f32 / 4.0;
+                    mb.line(
+                        &[
+                            Point2::new(tile_rect.x + 8.0, y),
+                            Point2::new(tile_rect.right() - 8.0, y),
+                        ],
+                        2.0,
+                        LIFT_COLOR,
+                    )?;
+                }
+            }
+            Some(LiftType::Elevator) => {
+                let car = graphics::Rect::new(
+                    tile_rect.x + 4.0,
+                    tile_rect.y + 4.0,
+                    tile_rect.w - 8.0,
+                    tile_rect.h - 8.0,
+                );
+                mb.rectangle(DrawMode::stroke(2.0), car, LIFT_COLOR)?;
+            }
+            None => (),
+        }
+
+        // Draw a line around it to make it a tile
+        mb.rectangle(DrawMode::stroke(1.0), tile_rect, BORDER_COLOR)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ChunkPos, GridPosition, Point2, Problem, RoomType, Station, Tile, TileType, WallDirection,
+        CHUNK_SIZE,
+    };
+    use crate::events::EventBus;
+    use crate::inhabitant::InhabitantType;
+    use crate::item::{
+        get_container_types, ContainerType, FoodType, Item, ItemType, PowerGeneratorType,
+    };
+    use crate::seed::{GameSeed, RngStream};
+    use crate::station::atmosphere::Gas;
+    use crate::station::build::BuildTool;
+    use crate::station::fire::FireCause;
+    use crate::station::generator::CellularAutomaton;
+    use crate::station::heat::{HEATER_SETPOINT, ROOM_TEMPERATURE, SPOIL_TEMPERATURE};
+    use crate::station::hull::MAX_INTEGRITY;
+    use crate::station::tile::{Door, DoorAccess, DoorState, LiftType};
+    use crate::station::tileset::Sprite;
+    use oorandom::Rand32;
+    use std::time::Duration;
+    use strum::IntoEnumIterator;
+
+    // Function to make an empty station, used in tests
+    fn test_station() -> Station {
+        Station::empty(Point2::new(1.0, 1.0))
+    }
+
+    // Function to make a station from a little ASCII map, one row per line, used in tests
+    fn test_station_from(rows: &[&str]) -> Station {
+        Station::from_ascii(Point2::new(1.0, 1.0), &rows.join("\n"), &mut Rand32::new(1)).unwrap()
+    }
+
+    // Function to make a 2x2 floor station surrounded by walls, used in tests
+    fn test_station_full() -> Station {
+        test_station_from(&["####", "#..#", "#..#", "####"])
+    }
+
+    // Check the walls at some positions face the expected ways
+    fn assert_walls(s: &Station, expected: &[((i32, i32), WallDirection)]) {
+        for (pos, direction) in expected {
+            assert_eq!(
+                TileType::Wall(*direction),
+                s.get_tile(GridPosition::from(*pos)).unwrap().kind,
+                "Wall at {:?}",
+                pos
+            );
+        }
+    }
+
+    #[test]
+    fn tile_num_tiles() {
+        let s = test_station();
+        assert_eq!(0, s.num_tiles());
+    }
+
+    #[test]
+    fn add_tile() {
+        let mut s = test_station();
+        let tile = Tile::new(GridPosition::new(1, 1), TileType::Floor);
+        s.add_tile(tile);
+        assert_eq!(1, s.num_tiles());
+    }
+
+    #[test]
+    fn has_tile() {
+        let mut s = test_station();
+        let pos = GridPosition::new(1, 1);
+        let tile = Tile::new(pos, TileType::Floor);
+        s.add_tile(tile);
+        assert!(s.has_tile(pos));
+    }
+
+    #[test]
+    fn get_tile() {
+        let mut s = test_station();
+        let pos = GridPosition::new(1, 1);
+        let tile = Tile::new(pos, TileType::Floor);
+        s.add_tile(tile);
+
+        let tile2 = s.get_tile(pos).unwrap();
+        assert_eq!(pos, tile2.pos);
+        assert_eq!(TileType::Floor, tile2.kind);
+    }
+
+    #[test]
+    fn get_tile_empty() {
+        let mut s = test_station();
+        let pos = GridPosition::new(1, 1);
+        let tile = Tile::new(pos, TileType::Floor);
+        s.add_tile(tile);
+
+        let tile2 = s.get_tile(GridPosition::new(1, 2));
+        assert_eq!(None, tile2);
+    }
+
+    #[test]
+    fn remove_tile() {
+        let mut s = test_station();
+        let pos = GridPosition::new(1, 1);
+        let tile = Tile::new(pos, TileType::Floor);
+        s.add_tile(tile);
+        assert!(s.has_tile(pos));
+        s.remove_tile(pos);
+        assert!(!s.has_tile(pos));
+    }
+
+    #[test]
+    fn get_random_tile() {
+        let s = test_station_full();
+
+        let mut seed: [u8; 8] = [0; 8];
+        getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
+        let mut rng = Rand32::new(u64::from_ne_bytes(seed));
+
+        let floor_tile = s.get_random_tile(TileType::Floor, &mut rng).unwrap();
+        assert_eq!(floor_tile.kind, TileType::Floor, "Finds a floor tile");
+
+        let wall_tile1 = s
+            .get_random_tile(TileType::Wall(WallDirection::ExteriorTop), &mut rng)
+            .unwrap();
+        assert_eq!(
+            wall_tile1.kind,
+            TileType::Wall(WallDirection::ExteriorTop),
+            "Finds an exterior top wall tile"
+        );
+
+        let wall_tile2 = s
+            .get_random_tile(
+                TileType::Wall(WallDirection::ExteriorCornerBottomRight),
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(
+            wall_tile2.kind,
+            TileType::Wall(WallDirection::ExteriorCornerBottomRight),
+            "Finds the bottom-right exterior wall"
+        );
+
+        let door_tile = s.get_random_tile(
+            TileType::Door(Door::new(WallDirection::InteriorVertical)),
+            &mut rng,
+        );
+        assert_eq!(door_tile, None, "Does not find any doors");
+    }
+
+    #[test]
+    fn get_neighbors() {
+        let s = test_station_full();
+
+        // Test neighbors of the top-left corner
+        let neighbors1 = s.get_neighbors(GridPosition::new(0, 0));
+        assert_eq!(neighbors1.len(), 2, "Has two neighbors");
+        assert_eq!(
+            neighbors1[&(0, 1)].kind,
+            TileType::Wall(WallDirection::ExteriorLeft),
+            "Neighbor below is an exterior left wall"
+        );
+        assert_eq!(
+            neighbors1[&(1, 0)].kind,
+            TileType::Wall(WallDirection::ExteriorTop),
+            "Neighbor to the right is an exterior top wall"
+        );
+
+        // Test neighbors in the middle-ish
+        let neighbors2 = s.get_neighbors(GridPosition::new(1, 1));
+        assert_eq!(neighbors2.len(), 4, "Has four neighbors");
+        assert_eq!(
+            neighbors2[&(1, 0)].kind,
+            TileType::Wall(WallDirection::ExteriorTop),
+            "Upper neighbor is an exterior top wall"
+        );
+        assert_eq!(
+            neighbors2[&(2, 1)].kind,
+            TileType::Floor,
+            "Right neighbor is a floor"
+        );
+        assert_eq!(
+            neighbors2[&(1, 2)].kind,
+            TileType::Floor,
+            "Below neighbor is a floor"
+        );
+        assert_eq!(
+            neighbors2[&(0, 1)].kind,
+            TileType::Wall(WallDirection::ExteriorLeft),
+            "Left neighbor is an exterior left wall"
+        );
+    }
+
+    #[test]
+    fn search() {
+        let s = test_station_full();
+        let start = GridPosition::new(1, 1);
+        let target = GridPosition::new(2, 2);
+        let search = s.search(start, target, InhabitantType::Engineer);
+
+        assert_eq!(search.len(), 4, "We can reach 3 tiles plus ourselves");
+        assert_ne!(
+            search[&target],
+            Some(start),
+            "Source of target tile is not start (because that would be a diagonal move)"
+        );
+    }
+
+    #[test]
+    fn path_to() {
+        let mut s = test_station_full();
+        let start = GridPosition::new(1, 1);
+        let target = GridPosition::new(2, 2);
+
+        let path = s.path_to(start, target, InhabitantType::Engineer);
+        assert_eq!(path.len(), 2, "Can path to the target in 2 moves");
+
+        s.add_tile(Tile::new(target, TileType::Wall(WallDirection::Full)));
+        let path = s.path_to(start, target, InhabitantType::Engineer);
+        assert_eq!(path.len(), 0, "Cannnot path to a wall");
+    }
+
+    #[test]
+    fn path_through_doors() {
+        // A 3x3 room with a wall across the middle, and a door at one end of it
+        let mut s = test_station_from(&["#####", "#..S#", "#+#.#", "#...#", "#####"]);
+        let door_pos = GridPosition::new(1, 2);
+        let mut door = Door::new(WallDirection::InteriorHorizontal);
+
+        let start = GridPosition::new(1, 1);
+        let target = GridPosition::new(1, 3);
+        let path = s.path_to(start, target, InhabitantType::Cook);
+        assert_eq!(
+            vec![door_pos, target],
+            path,
+            "Closed doors are quicker than going round"
+        );
+
+        door.state = DoorState::Locked;
+        s.add_tile(Tile::new(door_pos, TileType::Door(door)));
+        let path = s.path_to(start, target, InhabitantType::Cook);
+        assert_eq!(6, path.len(), "Goes the long way round a locked door");
+        assert!(!path.contains(&door_pos));
+
+        door.state = DoorState::Open;
+        door.access = DoorAccess::Only(InhabitantType::Engineer);
+        s.add_tile(Tile::new(door_pos, TileType::Door(door)));
+        let path = s.path_to(start, target, InhabitantType::Cook);
+        assert!(!path.contains(&door_pos), "Cooks aren't allowed through");
+        let path = s.path_to(start, target, InhabitantType::Engineer);
+        assert_eq!(vec![door_pos, target], path, "But engineers are");
+
+        s.set_door_access(door_pos, DoorAccess::Everyone);
+        let path = s.path_to(start, target, InhabitantType::Cook);
+        assert_eq!(
+            vec![door_pos, target],
+            path,
+            "Until it's opened up to everyone"
+        );
+    }
+
+    #[test]
+    fn path_to_item() {
+        // A corridor bending round a wall, so the item that's nearest as the crow flies is the long way round
+        let mut s = test_station_from(&[
+            "#########",
+            "#.#.....#",
+            "#.#####.#",
+            "#.......#",
+            "#########",
+        ]);
+        let mut rng = Rand32::new(1);
+        let food = vec![ItemType::Food(FoodType::EnergyBar)];
+        let start = GridPosition::new(1, 1);
+        let behind = GridPosition::new(3, 1);
+        let below = GridPosition::new(4, 3);
+        s.add_item(behind, Item::new(behind, food[0], &mut rng));
+        s.add_item(below, Item::new(below, food[0], &mut rng));
+
+        assert_eq!(behind, s.find_items(food.clone(), start)[0]);
+        let path = s.path_to_item(start, food.clone(), InhabitantType::Cook);
+        assert_eq!(
+            Some(&below),
+            path.last(),
+            "Goes for the one it can walk to soonest"
+        );
+        assert_eq!(5, path.len());
+
+        s.take_item(below, food.clone());
+        let path = s.path_to_item(start, food.clone(), InhabitantType::Cook);
+        assert_eq!(Some(&behind), path.last(), "Then the long way round");
+
+        s.take_item(behind, food.clone());
+        assert!(s.path_to_item(start, food, InhabitantType::Cook).is_empty());
+    }
+
+    #[test]
+    fn get_bounds() {
+        assert_eq!(None, test_station().get_bounds());
+        let s = test_station_from(&["###", "#.#", "###", "---", "#####", "#...#", "#####"]);
+        assert_eq!(
+            Some((GridPosition::new(0, 0), GridPosition::new(4, 2))),
+            s.get_bounds(),
+            "Big enough for every deck"
+        );
+    }
+
+    #[test]
+    fn path_between_decks() {
+        let s = test_station_from(&["#####", "#.|.#", "#####", "---", "#####", "#.|.#", "#####"]);
+        let start = GridPosition::new(1, 1);
+        let target = GridPosition::on_deck(3, 1, 1);
+        let path = s.path_to(start, target, InhabitantType::Cook);
+        assert_eq!(
+            vec![
+                GridPosition::new(2, 1),
+                GridPosition::on_deck(2, 1, 1),
+                target
+            ],
+            path,
+            "Up the ladder"
+        );
+
+        // Elevators need power at both ends
+        let unpowered =
+            test_station_from(&["#####", "#R^.#", "#####", "---", "#####", "#.^.#", "#####"]);
+        assert!(unpowered
+            .path_to(start, target, InhabitantType::Cook)
+            .is_empty());
+        let powered =
+            test_station_from(&["#####", "#R^.#", "#####", "---", "#####", "#R^.#", "#####"]);
+        assert_eq!(
+            3,
+            powered.path_to(start, target, InhabitantType::Cook).len()
+        );
+        assert_eq!(
+            vec![0, 1],
+            powered.get_decks().into_iter().collect::<Vec<i32>>()
+        );
+    }
+
+    #[test]
+    fn generate_places_doors_between_rooms() {
+        let s = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut GameSeed::new(1).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+
+        let mut num_doors = 0;
+        for tile in s.tiles.values() {
+            if let TileType::Door(door) = tile.kind {
+                num_doors += 1;
+                let (a, b) = match door.direction {
+                    WallDirection::InteriorVertical => ((-1, 0), (1, 0)),
+                    _ => ((0, -1), (0, 1)),
+                };
+                let a = s.get_room_at(tile.pos.offset(a.0, a.1));
+                let b = s.get_room_at(tile.pos.offset(b.0, b.1));
+                assert_ne!(
+                    a.unwrap().id,
+                    b.unwrap().id,
+                    "Doors join two different rooms"
+                );
+            }
+        }
+        assert!(num_doors > 0, "This station has some doors");
+    }
+
+    #[test]
+    fn find_items() {
+        let mut s = test_station_full();
+        let mut rng = Rand32::new(1);
+        let food = vec![ItemType::Food(FoodType::EnergyBar)];
+        let pos = GridPosition::new(1, 1);
+        s.add_item(pos, Item::new(pos, food[0], &mut rng));
+
+        let found = s.find_items(food.clone(), pos);
+        assert_eq!(vec![pos], found, "found one food type");
+
+        let fridge_pos = GridPosition::new(1, 2);
+        let fridge = Item::new(
+            fridge_pos,
+            ItemType::Container(ContainerType::Fridge),
+            &mut rng,
+        );
+        s.add_item(fridge_pos, fridge);
+
+        let found = s.find_items(vec![ItemType::Container(ContainerType::Fridge)], pos);
+        assert_eq!(vec![fridge_pos], found, "found one container type");
+        assert_eq!(
+            6,
+            s.count_items(food.clone()),
+            "found another food type (the fridge comes with food)"
+        );
+        assert_eq!(
+            vec![fridge_pos, pos],
+            s.find_items(food.clone(), GridPosition::new(1, 3)),
+            "Closest first"
+        );
+
+        let taken = s.take_item(pos, food.clone());
+        assert!(taken.is_some());
+        assert_eq!(5, s.count_items(food.clone()), "Taken items are gone");
+        s.clear_items(fridge_pos);
+        assert_eq!(0, s.count_items(food), "And so is anything cleared away");
+    }
+
+    #[test]
+    fn generate_is_reproducible() {
+        let seed = GameSeed::new(1234);
+        let a = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut seed.rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+        let b = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut seed.rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+        assert_eq!(
+            serde_cbor::to_vec(&a).unwrap(),
+            serde_cbor::to_vec(&b).unwrap(),
+            "The same seed makes the same station"
+        );
+
+        let c = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut GameSeed::new(4321).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+        assert_ne!(
+            serde_cbor::to_vec(&a).unwrap(),
+            serde_cbor::to_vec(&c).unwrap(),
+            "A different seed makes a different station"
+        );
+    }
+
+    #[test]
+    fn ascii() {
+        let s = test_station_from(&["#####", "#.F/#", "#####"]);
+        assert_eq!(15, s.num_tiles());
+        assert!(s
+            .get_tile(GridPosition::new(2, 1))
+            .unwrap()
+            .has_item(vec![ItemType::Container(ContainerType::Fridge)]));
+        match s.get_tile(GridPosition::new(3, 1)).unwrap().kind {
+            TileType::Door(door) => assert_eq!(DoorState::Open, door.state),
+            _ => panic!("Should be a door"),
+        }
+        assert!(Station::from_ascii(Point2::zero(), "#?#", &mut Rand32::new(1)).is_err());
+
+        // A generated station survives the trip out and back in
+        let generated = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut GameSeed::new(1234).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+        let text = generated.to_ascii();
+        let s = Station::from_ascii(Point2::zero(), &text, &mut Rand32::new(1)).unwrap();
+        assert_eq!(generated.num_tiles(), s.num_tiles());
+        assert_eq!(generated.get_rooms().count(), s.get_rooms().count());
+        assert_eq!(text, s.to_ascii());
+    }
+
+    #[test]
+    fn atmosphere() {
+        let step = Duration::from_secs_f64(1.0 / 60.0);
+        let mut s = test_station_from(&["#####", "#...#", "#####"]);
+        let left = GridPosition::new(1, 1);
+        let right = GridPosition::new(3, 1);
+        assert_eq!(Some(&Gas::air()), s.get_gas(left));
+        assert_eq!(
+            None,
+            s.get_gas(GridPosition::new(0, 0)),
+            "Walls have no air"
+        );
+
+        // Air evens out in a sealed room, without any getting lost
+        s.get_gas_mut(left).unwrap().o2 = 0.0;
+        let total: f32 = s.atmosphere.values().map(|gas| gas.total()).sum();
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        let after: f32 = s.atmosphere.values().map(|gas| gas.total()).sum();
+        assert!((total - after).abs() < 0.01);
+        let (left_o2, right_o2) = (s.get_gas(left).unwrap().o2, s.get_gas(right).unwrap().o2);
+        assert!((left_o2 - right_o2).abs() < 0.01, "Evened out");
+
+        // New floor starts empty
+        s.add_tile(Tile::new(GridPosition::new(2, 2), TileType::Floor));
+        assert_eq!(Some(&Gas::vacuum()), s.get_gas(GridPosition::new(2, 2)));
+
+        // And knocking a hole in the wall lets it all out
+        s.remove_tile(GridPosition::new(4, 1));
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        assert!(s.get_gas(left).unwrap().pressure() < 1.0);
+    }
+
+    #[test]
+    fn temperature() {
+        let step = Duration::from_secs_f64(1.0 / 60.0);
+        let mut s = test_station_from(&["#####", "#...#", "#####"]);
+        let left = GridPosition::new(1, 1);
+        let right = GridPosition::new(3, 1);
+        assert_eq!(Some(ROOM_TEMPERATURE), s.get_temperature(left));
+
+        // Heat spreads out through the floor, but the hull keeps it in
+        s.set_temperature(left, ROOM_TEMPERATURE + 30.0);
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        let (left_t, right_t) = (
+            s.get_temperature(left).unwrap(),
+            s.get_temperature(right).unwrap(),
+        );
+        assert!((left_t - right_t).abs() < 0.5, "Evened out");
+        assert!(right_t > ROOM_TEMPERATURE + 5.0, "Still warm");
+
+        // Until there's a hole in it, and it all radiates away into space
+        s.remove_tile(GridPosition::new(4, 1));
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        assert!(s.get_temperature(left).unwrap() < 200.0);
+    }
+
+    #[test]
+    fn lift_shafts() {
+        let step = Duration::from_secs_f64(1.0 / 60.0);
+        let pos = GridPosition::new(1, 1);
+        let shaft = |lift: &str| {
+            let mut s = test_station_from(&["###", lift, "###", "---", "###", lift, "###"]);
+            s.get_gas_mut(pos.above()).unwrap().o2 = 0.0;
+            s.set_temperature(pos.above(), ROOM_TEMPERATURE + 30.0);
+            for _ in 0..60 * 60 {
+                s.update(step);
+            }
+            s
+        };
+
+        // Air and heat go up and down a ladder's open hatch
+        let s = shaft("#|#");
+        let (below, above) = (s.get_gas(pos).unwrap(), s.get_gas(pos.above()).unwrap());
+        assert!((below.o2 - above.o2).abs() < 0.01, "Evened out");
+        assert!(s.get_temperature(pos).unwrap() > ROOM_TEMPERATURE + 5.0);
+
+        // But an elevator's car fills its shaft
+        let s = shaft("#^#");
+        assert_eq!(Gas::air().o2, s.get_gas(pos).unwrap().o2);
+        assert_eq!(Some(ROOM_TEMPERATURE), s.get_temperature(pos));
+    }
+
+    #[test]
+    fn heaters() {
+        let step = Duration::from_secs(1);
+        let mut s = test_station_from(&["#####", "#H.R#", "#####"]);
+        let middle = GridPosition::new(2, 1);
+        let cold = ROOM_TEMPERATURE - 20.0;
+        for x in 1..4 {
+            s.set_temperature(GridPosition::new(x, 1), cold);
+        }
+
+        for _ in 0..60 * 10 {
+            s.update(step);
+        }
+        let warmed = s.get_temperature(middle).unwrap();
+        assert!(warmed > HEATER_SETPOINT - 1.0, "Warmed up the room");
+        assert!(warmed <= HEATER_SETPOINT, "But no further");
+
+        // Not without power though
+        s.clear_items(GridPosition::new(3, 1));
+        for x in 1..4 {
+            s.set_temperature(GridPosition::new(x, 1), cold);
+        }
+        for _ in 0..60 {
+            s.update(step);
+        }
+        assert!(s.get_temperature(middle).unwrap() < cold + 1.0);
+    }
+
+    #[test]
+    fn fires() {
+        let step = Duration::from_secs(1);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut s = test_station_from(&["######", "#..F.#", "######"]);
+        let pos = GridPosition::new(1, 1);
+        assert!(!s.ignite(GridPosition::new(0, 0), FireCause::Cooking, &mut events));
+        assert!(s.ignite(pos, FireCause::Cooking, &mut events));
+        assert!(
+            !s.ignite(pos, FireCause::Cooking, &mut events),
+            "Already burning"
+        );
+
+        for _ in 0..10 {
+            s.update_fires(step, &mut rng, &mut events);
+        }
+        assert!(s.get_gas(pos).unwrap().o2 < Gas::air().o2, "Burnt oxygen");
+        assert!(s.get_gas(pos).unwrap().co2 > Gas::air().co2, "Made CO2");
+        assert!(
+            s.get_temperature(pos).unwrap() > ROOM_TEMPERATURE,
+            "Got hot"
+        );
+
+        // Given long enough, it catches whatever's next to it
+        for _ in 0..60 {
+            s.update(step);
+            s.update_fires(step, &mut rng, &mut events);
+        }
+        assert!(s.get_fires().count() > 1, "Spread");
+
+        // Put one out by hand, and starve the rest of air
+        s.extinguish(pos, 1.0, &mut events);
+        assert!(s.get_fire(pos).is_none(), "Put out");
+        let burning: Vec<GridPosition> = s.get_fires().collect();
+        for pos in burning {
+            *s.get_gas_mut(pos).unwrap() = Gas::vacuum();
+        }
+        for _ in 0..10 {
+            s.update_fires(step, &mut rng, &mut events);
+        }
+        assert_eq!(0, s.get_fires().count(), "Smothered");
+        assert!(
+            !s.ignite(pos, FireCause::Cooking, &mut events),
+            "Nothing burns without air"
+        );
+    }
+
+    #[test]
+    fn fires_go_with_their_tile() {
+        let step = Duration::from_secs(1);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut s = test_station_from(&["######", "#....#", "######"]);
+        let removed = GridPosition::new(1, 1);
+        let walled = GridPosition::new(4, 1);
+        assert!(s.ignite(removed, FireCause::Cooking, &mut events));
+        assert!(s.ignite(walled, FireCause::Cooking, &mut events));
+
+        s.remove_tile(removed);
+        assert!(s.get_fire(removed).is_none(), "Nothing left to burn");
+        s.add_tile(Tile::new(walled, TileType::Wall(WallDirection::Full)));
+        assert!(s.get_fire(walled).is_none(), "Walls don't burn");
+
+        // Burning out right where the tile was mustn't fall over
+        for _ in 0..60 {
+            s.update(step);
+            s.update_fires(step, &mut rng, &mut events);
+        }
+    }
+
+    #[test]
+    fn meteors_breach_the_hull() {
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let mut s = test_station_from(&["#####", "#.#.#", "#...#", "#####"]);
+        assert!(s.is_hull(GridPosition::new(0, 1)));
+        assert!(
+            !s.is_hull(GridPosition::new(2, 1)),
+            "Walls with station all round aren't hull"
+        );
+        assert!(!s.is_hull(GridPosition::new(1, 1)), "Only walls are hull");
+
+        // Enough strikes and something gives
+        let pos = loop {
+            s.meteor_strike(&mut rng, &mut events);
+            if let Some(pos) = s.get_breaches().next() {
+                break pos;
+            }
+        };
+        assert!(s.get_tile(pos).unwrap().is_breached());
+        assert!(!s.is_hull(pos), "Nothing left there to hit");
+
+        // The air rushes out through the hole
+        s.fill_atmosphere();
+        let room = GridPosition::new(2, 2);
+        for _ in 0..100 {
+            s.update(Duration::from_millis(100));
+        }
+        assert!(s.get_gas(room).unwrap().pressure() < Gas::air().pressure() / 2.0);
+
+        assert_eq!(
+            Err("Only walls need repairing"),
+            s.can_build(room, BuildTool::Repair)
+        );
+        assert!(s.build(pos, BuildTool::Repair, &mut rng, &mut events));
+        assert_eq!(0, s.get_breaches().count(), "Patched up");
+        assert_eq!(MAX_INTEGRITY, s.get_tile(pos).unwrap().integrity);
+        assert_eq!(
+            Err("Nothing to repair"),
+            s.can_build(pos, BuildTool::Repair)
+        );
+    }
+
+    #[test]
+    fn power_networks() {
+        let mut s = test_station_from(&["####   ####", "#S.#   #F.#", "####   ####"]);
+        let solar = GridPosition::new(1, 1);
+        let fridge = GridPosition::new(8, 1);
+        assert_eq!(
+            2,
+            s.get_networks().count(),
+            "Walls that don't touch aren't wired together"
+        );
+        assert_eq!(500, s.get_network_at(solar).unwrap().supply);
+        assert!(s.is_powered(solar));
+        assert_eq!(200, s.get_network_at(fridge).unwrap().demand);
+        assert!(!s.is_powered(fridge), "Nothing to power the fridge");
+
+        // Run a conduit across to join them up
+        for x in 4..7 {
+            let mut tile = Tile::new(GridPosition::new(x, 1), TileType::Floor);
+            tile.conduit = true;
+            s.add_tile(tile);
+        }
+        s.update_power();
+        assert_eq!(1, s.get_networks().count());
+        assert!(s.is_powered(fridge));
+
+        // Things in the middle of a room need a conduit to plug into
+        let mut s = test_station_from(&["#####", "#...#", "#.F.#", "#...#", "#S..#", "#####"]);
+        assert!(s.get_network_at(GridPosition::new(2, 2)).is_none());
+        s.get_tile_mut(GridPosition::new(2, 1)).unwrap().conduit = true;
+        s.update_power();
+        assert!(s.is_powered(GridPosition::new(2, 2)));
+    }
+
+    #[test]
+    fn build_power() {
+        let mut s = test_station_from(&["#####", "#...#", "#.F.#", "#...#", "#####"]);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let fridge = GridPosition::new(2, 2);
+        let reactor = BuildTool::Generator(PowerGeneratorType::Reactor);
+        assert_eq!(
+            Err("Generators go on floor"),
+            s.can_build(GridPosition::new(0, 0), reactor)
+        );
+        assert!(s.build(GridPosition::new(1, 1), reactor, &mut rng, &mut events));
+        assert_eq!(
+            Err("Already a generator"),
+            s.can_build(GridPosition::new(1, 1), reactor)
+        );
+        s.update_power();
+        assert!(!s.is_powered(fridge), "Nothing to plug into");
+
+        let conduit = GridPosition::new(2, 1);
+        assert!(s.build(conduit, BuildTool::Conduit, &mut rng, &mut events));
+        assert_eq!(
+            Err("Already a conduit"),
+            s.can_build(conduit, BuildTool::Conduit)
+        );
+        s.update_power();
+        assert!(s.is_powered(fridge));
+
+        // Taking the conduit up leaves the floor
+        assert!(s.build(conduit, BuildTool::Remove, &mut rng, &mut events));
+        assert!(!s.get_tile(conduit).unwrap().conduit);
+        assert_eq!(TileType::Floor, s.get_tile(conduit).unwrap().kind);
+    }
+
+    #[test]
+    fn power_networks_follow_changes() {
+        let mut s = test_station_from(&["####   ####", "#S.#   #F.#", "####   ####"]);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+
+        // Whatever gets built or taken away, the networks come out the same as working them out from scratch
+        let changes = [
+            (GridPosition::new(3, 1), BuildTool::Floor),
+            (GridPosition::new(4, 1), BuildTool::Floor),
+            (GridPosition::new(5, 1), BuildTool::Floor),
+            (GridPosition::new(6, 1), BuildTool::Floor),
+            (GridPosition::new(7, 1), BuildTool::Floor),
+            (GridPosition::new(5, 1), BuildTool::Conduit),
+            (GridPosition::new(5, 1), BuildTool::Remove),
+            (GridPosition::new(5, 1), BuildTool::Remove),
+            (
+                GridPosition::new(9, 1),
+                BuildTool::Generator(PowerGeneratorType::Reactor),
+            ),
+            (GridPosition::new(1, 1), BuildTool::Remove),
+            (GridPosition::new(2, 1), BuildTool::Lift(LiftType::Elevator)),
+            (GridPosition::new(2, 1), BuildTool::Remove),
+            (GridPosition::new(6, 1), BuildTool::Wall),
+        ];
+        for (pos, tool) in changes {
+            assert!(
+                s.build(pos, tool, &mut rng, &mut events),
+                "{:?} at {}",
+                tool,
+                pos
+            );
+            s.update_power();
+
+            let mut fresh = s.clone();
+            fresh.networks.clear();
+            fresh.network_at.clear();
+            fresh.rewired = fresh.tiles.keys().copied().collect();
+            fresh.update_power();
+            let networks = |s: &Station| {
+                let mut networks: Vec<_> = s
+                    .get_networks()
+                    .map(|n| (n.tiles.clone(), n.supply, n.demand))
+                    .collect();
+                networks.sort();
+                networks
             };
+            assert_eq!(
+                networks(&fresh),
+                networks(&s),
+                "After {:?} at {}",
+                tool,
+                pos
+            );
+            assert_eq!(fresh.powered, s.powered);
+        }
+    }
 
-            // Draw a line around it to make it a tile
-            mb.rectangle(DrawMode::stroke(1.0), tile_rect, BORDER_COLOR)?;
+    #[test]
+    fn air_settles() {
+        let step = Duration::from_secs_f64(1.0 / 60.0);
+        let mut s = test_station_from(&["#####", "#...#", "#####"]);
+        let left = GridPosition::new(1, 1);
+        let chunk = ChunkPos::of(left);
+        for _ in 0..60 {
+            s.update(step);
         }
+        assert!(
+            s.settled_air.contains(&chunk),
+            "Fresh air has nowhere to go"
+        );
 
-        self.mesh = mb.build(ctx).ok();
+        // Touching the air stirs it up until it's evened out again
+        s.get_gas_mut(left).unwrap().o2 = 0.0;
+        assert!(!s.settled_air.contains(&chunk));
+        for _ in 0..6 {
+            s.update(step);
+        }
+        assert!(!s.settled_air.contains(&chunk));
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        assert!(s.settled_air.contains(&chunk));
 
-        Ok(())
+        // And so does changing the tiles
+        s.remove_tile(GridPosition::new(4, 1));
+        assert!(!s.settled_air.contains(&chunk));
+        for _ in 0..6 {
+            s.update(step);
+        }
+        assert!(s.get_gas(GridPosition::new(3, 1)).unwrap().pressure() < Gas::air().pressure());
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{GridPosition, Point2, Station, Tile, TileType, WallDirection};
-    use crate::item::{ContainerType, FoodType, Item, ItemType};
-    use oorandom::Rand32;
-    use std::collections::HashMap;
+    #[test]
+    fn unpowered_doors_jam() {
+        let mut s = test_station_from(&["#####", "#.+.#", "#####"]);
+        let from = GridPosition::new(1, 1);
+        let door = s.get_tile(GridPosition::new(2, 1)).unwrap().clone();
+        let jammed = s.movement_cost(&from, &door);
 
-    // Function to make an empty station, used in tests
-    fn test_station() -> Station {
-        Station {
-            pos: Point2::new(1.0, 1.0),
-            tiles: HashMap::new(),
-            mesh: None,
+        let solar = Item::new(
+            from,
+            ItemType::Generator(PowerGeneratorType::SolarPanel),
+            &mut Rand32::new(1),
+        );
+        s.add_item(from, solar);
+        s.update_power();
+        assert!(
+            s.movement_cost(&from, &door) < jammed,
+            "Doors open by themselves with power"
+        );
+    }
+
+    #[test]
+    fn fridges_need_power() {
+        let step = Duration::from_secs(1);
+        let fridge = GridPosition::new(1, 1);
+        let solar = GridPosition::new(2, 1);
+        let mut s = test_station_from(&["####", "#FS#", "####"]);
+        let warmest = |s: &Station| {
+            s.get_tile(fridge).unwrap().items[0]
+                .get_items()
+                .iter()
+                .map(|item| item.get_temperature())
+                .fold(0.0, f32::max)
+        };
+
+        for _ in 0..60 * 10 {
+            s.update(step);
         }
+        assert!(warmest(&s) < SPOIL_TEMPERATURE, "Kept cold");
+
+        s.clear_items(solar);
+        for _ in 0..60 * 10 {
+            s.update(step);
+        }
+        assert!(warmest(&s) > SPOIL_TEMPERATURE, "Warmed up without power");
     }
 
-    // Function to make a 4x4 floor station surrounded by walls, used in tests
-    fn test_station_full() -> Station {
-        let mut s = test_station();
+    #[test]
+    fn generate_powers_station() {
+        let s = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut GameSeed::new(1).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
 
-        // Make top wall
-        s.add_tile(Tile::new(
+        let reactors = s.count_items(vec![ItemType::Generator(PowerGeneratorType::Reactor)]);
+        assert_eq!(1, reactors);
+        let fridges = s.find_items(
+            vec![ItemType::Container(ContainerType::Fridge)],
             GridPosition::new(0, 0),
-            TileType::Wall(WallDirection::ExteriorCornerTopLeft),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(1, 0),
-            TileType::Wall(WallDirection::ExteriorTop),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(2, 0),
-            TileType::Wall(WallDirection::ExteriorTop),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(3, 0),
-            TileType::Wall(WallDirection::ExteriorCornerTopRight),
-        ));
+        );
+        assert!(s.is_powered(fridges[0]), "The fridge is plugged in");
+    }
 
-        // Make bottom wall
-        s.add_tile(Tile::new(
-            GridPosition::new(0, 3),
-            TileType::Wall(WallDirection::ExteriorCornerBottomLeft),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(1, 3),
-            TileType::Wall(WallDirection::ExteriorBottom),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(2, 3),
-            TileType::Wall(WallDirection::ExteriorBottom),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(3, 3),
-            TileType::Wall(WallDirection::ExteriorCornerBottomRight),
-        ));
+    #[test]
+    fn atmosphere_doors() {
+        let step = Duration::from_secs_f64(1.0 / 60.0);
+        let mut s = test_station_from(&["#######", "#..+..#", "#######"]);
+        let left = GridPosition::new(1, 1);
+        let right = GridPosition::new(5, 1);
+        *s.get_gas_mut(GridPosition::new(4, 1)).unwrap() = Gas::vacuum();
+        *s.get_gas_mut(right).unwrap() = Gas::vacuum();
 
-        // Left wall
-        s.add_tile(Tile::new(
-            GridPosition::new(0, 1),
-            TileType::Wall(WallDirection::ExteriorLeft),
-        ));
-        s.add_tile(Tile::new(
-            GridPosition::new(0, 2),
-            TileType::Wall(WallDirection::ExteriorLeft),
-        ));
+        for _ in 0..60 * 10 {
+            s.update(step);
+        }
+        assert_eq!(Gas::air(), *s.get_gas(left).unwrap(), "Closed doors hold");
+        assert_eq!(Gas::vacuum(), *s.get_gas(right).unwrap());
+
+        s.set_door_state(GridPosition::new(3, 1), DoorState::Open);
+        for _ in 0..60 * 60 {
+            s.update(step);
+        }
+        let (left, right) = (s.get_gas(left).unwrap(), s.get_gas(right).unwrap());
+        assert!(
+            (left.pressure() - right.pressure()).abs() < 1.0,
+            "Open doors don't"
+        );
+    }
+
+    #[test]
+    fn detect_rooms() {
+        let mut s = test_station_full();
+        assert_eq!(1, s.get_rooms().count(), "The floor is one room");
+        let room = s.get_room_at(GridPosition::new(1, 1)).unwrap();
+        assert_eq!(4, room.area());
+        assert!(
+            s.get_room_at(GridPosition::new(0, 0)).is_none(),
+            "Walls aren't in rooms"
+        );
 
-        // Right wall
+        // Designations stick around when the room changes shape
+        let id = room.id;
+        s.designate_room(id, RoomType::Galley);
         s.add_tile(Tile::new(
-            GridPosition::new(3, 1),
-            TileType::Wall(WallDirection::ExteriorRight),
+            GridPosition::new(2, 1),
+            TileType::Wall(WallDirection::Full),
         ));
+        let room = s.get_room_at(GridPosition::new(1, 1)).unwrap();
+        assert_eq!(id, room.id);
+        assert_eq!(3, room.area());
+        assert_eq!(RoomType::Galley, room.kind);
+
+        // Split it in two with a door
         s.add_tile(Tile::new(
-            GridPosition::new(3, 2),
-            TileType::Wall(WallDirection::ExteriorRight),
+            GridPosition::new(1, 2),
+            TileType::Door(Door::new(WallDirection::Full)),
         ));
+        assert_eq!(2, s.get_rooms().count(), "A door splits rooms");
+        let left = s.get_room_at(GridPosition::new(1, 1)).unwrap();
+        let right = s.get_room_at(GridPosition::new(2, 2)).unwrap();
+        assert_ne!(left.id, right.id);
+        assert_eq!(
+            RoomType::Galley,
+            right.kind,
+            "Both halves are still galleys"
+        );
 
-        // Floors
-        s.add_tile(Tile::new(GridPosition::new(1, 1), TileType::Floor));
+        // Joining them back up gives one room again
         s.add_tile(Tile::new(GridPosition::new(1, 2), TileType::Floor));
-        s.add_tile(Tile::new(GridPosition::new(2, 1), TileType::Floor));
-        s.add_tile(Tile::new(GridPosition::new(2, 2), TileType::Floor));
-
-        s
+        assert_eq!(1, s.get_rooms().count());
+        assert_eq!(
+            1,
+            s.get_rooms_of_type(RoomType::Galley).len(),
+            "And only one galley"
+        );
     }
 
     #[test]
-    fn tile_num_tiles() {
-        let s = test_station();
-        assert_eq!(0, s.num_tiles());
-    }
+    fn split_room_keeps_id_in_bigger_part() {
+        let mut s = test_station_from(&["#######", "#.....#", "#######"]);
+        let id = s.get_room_at(GridPosition::new(1, 1)).unwrap().id;
+        s.designate_room(id, RoomType::Galley);
 
-    #[test]
-    fn add_tile() {
-        let mut s = test_station();
-        let tile = Tile::new(GridPosition::new(1, 1), TileType::Floor);
-        s.add_tile(tile);
-        assert_eq!(1, s.num_tiles());
+        // Cut a sliver off the right, which gets flood filled first
+        s.add_tile(Tile::new(
+            GridPosition::new(4, 1),
+            TileType::Door(Door::new(WallDirection::Full)),
+        ));
+        let small = s.get_room_at(GridPosition::new(5, 1)).unwrap();
+        let big = s.get_room_at(GridPosition::new(2, 1)).unwrap();
+        assert_eq!((1, 3), (small.area(), big.area()));
+        assert_eq!(id, big.id, "The bigger part is still the same room");
+        assert_ne!(id, small.id);
+        assert_eq!(RoomType::Galley, small.kind);
     }
 
     #[test]
-    fn has_tile() {
-        let mut s = test_station();
-        let pos = GridPosition::new(1, 1);
-        let tile = Tile::new(pos, TileType::Floor);
-        s.add_tile(tile);
-        assert!(s.has_tile(pos));
+    fn generate_designates_galley() {
+        let s = Station::new(
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            &mut GameSeed::new(1234).rng(RngStream::Simulation),
+            &mut EventBus::new(),
+        );
+        let galleys = s.get_rooms_of_type(RoomType::Galley);
+        assert_eq!(1, galleys.len(), "The room with the fridge is the galley");
+        assert!(galleys[0]
+            .get_items(&s)
+            .iter()
+            .any(|item| item.get_type() == ItemType::Container(ContainerType::Fridge)));
     }
 
     #[test]
-    fn get_tile() {
-        let mut s = test_station();
-        let pos = GridPosition::new(1, 1);
-        let tile = Tile::new(pos, TileType::Floor);
-        s.add_tile(tile);
+    fn connect_regions() {
+        // Two rooms a little way apart get a corridor between them
+        let mut s = test_station_from(&["####  ####", "#..#  #..#", "#..#  #..#", "####  ####"]);
+        assert_eq!(2, s.walkable_regions().len());
+        assert!(s.connect_regions());
+        assert_eq!(1, s.walkable_regions().len(), "Joined up");
+        assert_eq!(Vec::<Problem>::new(), s.validate());
+        assert!(!s.connect_regions(), "Nothing left to do");
 
-        let tile2 = s.get_tile(pos).unwrap();
-        assert_eq!(pos, tile2.pos);
-        assert_eq!(TileType::Floor, tile2.kind);
-    }
+        // The corridor goes through each room's wall with a door, so the rooms stay rooms
+        for x in [3, 6] {
+            let doors = s
+                .tiles
+                .values()
+                .filter(|tile| tile.pos.x == x && matches!(tile.kind, TileType::Door(_)))
+                .count();
+            assert_eq!(1, doors, "A door at x={}", x);
+        }
+        s.detect_rooms();
+        assert_eq!(3, s.get_rooms().count(), "Two rooms and the corridor");
+        assert_ne!(
+            s.get_room_at(GridPosition::new(1, 1)).unwrap().id,
+            s.get_room_at(GridPosition::new(8, 1)).unwrap().id
+        );
 
-    #[test]
-    fn get_tile_empty() {
-        let mut s = test_station();
-        let pos = GridPosition::new(1, 1);
-        let tile = Tile::new(pos, TileType::Floor);
-        s.add_tile(tile);
+        // Rooms back to back get the one door through both walls
+        let mut s = test_station_from(&["########", "#..##..#", "#..##..#", "########"]);
+        assert!(s.connect_regions());
+        let doors = s
+            .tiles
+            .values()
+            .filter(|tile| matches!(tile.kind, TileType::Door(_)))
+            .count();
+        assert_eq!(1, doors);
+        s.detect_rooms();
+        assert_eq!(2, s.get_rooms().count());
+        assert_eq!(Vec::<Problem>::new(), s.validate());
 
-        let tile2 = s.get_tile(GridPosition::new(1, 2));
-        assert_eq!(None, tile2);
-    }
+        // A row of rooms gets joined up one after another, each to whatever's been joined so far
+        let mut s = test_station_from(&[
+            "####  ####  ####",
+            "#..#  #..#  #..#",
+            "#..#  #..#  #..#",
+            "####  ####  ####",
+        ]);
+        assert_eq!(3, s.walkable_regions().len());
+        assert!(s.connect_regions());
+        assert_eq!(1, s.walkable_regions().len(), "All three joined");
+        assert!(s.has_tile(GridPosition::new(14, 1)), "Even the far end");
+        assert_eq!(Vec::<Problem>::new(), s.validate());
 
-    #[test]
-    fn remove_tile() {
-        let mut s = test_station();
-        let pos = GridPosition::new(1, 1);
-        let tile = Tile::new(pos, TileType::Floor);
-        s.add_tile(tile);
-        assert!(s.has_tile(pos));
-        s.remove_tile(pos);
-        assert!(!s.has_tile(pos));
+        // A room too far away to bother with is dropped, walls and all
+        let mut s = test_station_from(&["####", "#..#", "####"]);
+        s.add_tile(Tile::new(GridPosition::new(30, 1), TileType::Floor));
+        s.wall_in();
+        assert!(s.connect_regions());
+        assert_eq!(1, s.walkable_regions().len());
+        assert!(!s.has_tile(GridPosition::new(30, 1)));
+        assert!(!s.has_tile(GridPosition::new(31, 1)), "Its walls went too");
+        assert_eq!(Vec::<Problem>::new(), s.validate());
     }
 
     #[test]
-    fn get_random_tile() {
-        let s = test_station_full();
-
-        let mut seed: [u8; 8] = [0; 8];
-        getrandom::getrandom(&mut seed[..]).expect("Could not create RNG seed");
-        let mut rng = Rand32::new(u64::from_ne_bytes(seed));
+    fn validate() {
+        let mut s = test_station_from(&["#####", "#...#", "#####", "", "#####", "#...#", "#####"]);
+        assert_eq!(Vec::<Problem>::new(), s.validate(), "Rooms can be cut off");
 
-        let floor_tile = s.get_random_tile(TileType::Floor, &mut rng).unwrap();
-        assert_eq!(floor_tile.kind, TileType::Floor, "Finds a floor tile");
+        let mut rng = Rand32::new(1);
+        let fridge =
+            |pos, rng: &mut Rand32| Item::new(pos, ItemType::Container(ContainerType::Fridge), rng);
+        let big = GridPosition::new(1, 1);
+        s.add_tile(Tile::new(GridPosition::new(4, 1), TileType::Floor));
+        s.add_item(big, fridge(big, &mut rng));
+        let small = GridPosition::new(1, 5);
+        s.add_item(small, fridge(small, &mut rng));
+        let wall = GridPosition::new(0, 0);
+        s.add_item(wall, fridge(wall, &mut rng));
+        s.add_tile(Tile::new(
+            GridPosition::new(9, 9),
+            TileType::Wall(WallDirection::Full),
+        ));
 
-        let wall_tile1 = s
-            .get_random_tile(TileType::Wall(WallDirection::ExteriorTop), &mut rng)
-            .unwrap();
         assert_eq!(
-            wall_tile1.kind,
-            TileType::Wall(WallDirection::ExteriorTop),
-            "Finds an exterior top wall tile"
+            vec![
+                Problem::OrphanWall(GridPosition::new(9, 9)),
+                Problem::OpenToSpace(GridPosition::new(4, 1)),
+                Problem::UnreachableContainer(small),
+                Problem::ItemOnWall(wall),
+            ],
+            s.validate()
         );
+    }
 
-        let wall_tile2 = s
-            .get_random_tile(
-                TileType::Wall(WallDirection::ExteriorCornerBottomRight),
-                &mut rng,
-            )
-            .unwrap();
-        assert_eq!(
-            wall_tile2.kind,
-            TileType::Wall(WallDirection::ExteriorCornerBottomRight),
-            "Finds the bottom-right exterior wall"
+    #[test]
+    fn build() {
+        let mut s = test_station_from(&["####", "#..#", "#..#", "####"]);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let edge = GridPosition::new(3, 1);
+        let space = GridPosition::new(5, 1);
+
+        assert!(
+            s.can_build(space, BuildTool::Floor).is_err(),
+            "Not joined on"
+        );
+        assert!(s.can_build(edge, BuildTool::Door).is_err(), "Nowhere to go");
+        assert!(
+            s.can_build(edge, BuildTool::Remove).is_err(),
+            "Opens up the station"
         );
 
-        let door_tile =
-            s.get_random_tile(TileType::Door(WallDirection::InteriorVertical), &mut rng);
-        assert_eq!(door_tile, None, "Does not find any doors");
+        // Knocking through the wall extends the station, walled in
+        assert!(s.build(edge, BuildTool::Floor, &mut rng, &mut events));
+        assert!(s.has_tile(GridPosition::new(4, 1)));
+        assert_eq!(1, s.get_rooms().count());
+        assert_eq!(Vec::<Problem>::new(), s.validate());
+
+        // Then keep going, and put a door in behind us
+        assert!(s.build(
+            GridPosition::new(4, 1),
+            BuildTool::Floor,
+            &mut rng,
+            &mut events
+        ));
+        assert!(s.build(edge, BuildTool::Wall, &mut rng, &mut events));
+        assert!(s.build(edge, BuildTool::Door, &mut rng, &mut events));
+        assert!(matches!(s.get_tile(edge).unwrap().kind, TileType::Door(_)));
+        assert_eq!(2, s.get_rooms().count());
+        assert_eq!(Vec::<Problem>::new(), s.validate());
+
+        // Furnish it, once
+        let pos = GridPosition::new(1, 1);
+        let locker = BuildTool::Furniture(ContainerType::Locker);
+        assert!(s.build(pos, locker, &mut rng, &mut events));
+        assert!(!s.build(pos, locker, &mut rng, &mut events));
+        assert!(s.get_tile(pos).unwrap().has_item(get_container_types()));
+
+        // Removing takes the furniture first, then the floor, then walls left with nothing to do
+        assert!(s.build(pos, BuildTool::Remove, &mut rng, &mut events));
+        assert!(s.get_tile(pos).unwrap().items.is_empty());
+        let end = GridPosition::new(4, 1);
+        assert!(s.build(end, BuildTool::Remove, &mut rng, &mut events));
+        assert!(matches!(s.get_tile(end).unwrap().kind, TileType::Wall(_)));
+        assert!(!s.has_tile(GridPosition::new(5, 1)), "Wall tidied away");
+        assert_eq!(Vec::<Problem>::new(), s.validate());
     }
 
     #[test]
-    fn get_neighbors() {
-        let s = test_station_full();
-
-        // Test neighbors of the top-left corner
-        let neighbors1 = s.get_neighbors(GridPosition::new(0, 0));
-        assert_eq!(neighbors1.len(), 2, "Has two neighbors");
+    fn build_lifts() {
+        let mut s = test_station_from(&["#####", "#...#", "#####"]);
+        let mut rng = Rand32::new(1);
+        let mut events = EventBus::new();
+        let ladder = BuildTool::Lift(LiftType::Ladder);
+        let pos = GridPosition::new(1, 1);
         assert_eq!(
-            neighbors1[&(0, 1)].kind,
-            TileType::Wall(WallDirection::ExteriorLeft),
-            "Neighbor below is an exterior left wall"
+            Err("Lifts go on floor"),
+            s.can_build(GridPosition::new(0, 0), ladder)
         );
+
+        // The first one starts a new deck, walled in
+        assert!(s.build(pos, ladder, &mut rng, &mut events));
+        assert_eq!(vec![0, 1], s.get_decks().into_iter().collect::<Vec<i32>>());
         assert_eq!(
-            neighbors1[&(1, 0)].kind,
-            TileType::Wall(WallDirection::ExteriorTop),
-            "Neighbor to the right is an exterior top wall"
+            Some(LiftType::Ladder),
+            s.get_tile(pos.above()).unwrap().lift
         );
+        assert_eq!(1, s.get_lift_stops(pos).len());
+        assert_eq!(Vec::<Problem>::new(), s.validate());
+        assert_eq!(Err("Already a lift"), s.can_build(pos, ladder));
 
-        // Test neighbors in the middle-ish
-        let neighbors2 = s.get_neighbors(GridPosition::new(1, 1));
-        assert_eq!(neighbors2.len(), 4, "Has four neighbors");
+        // Which can be built on like any other
+        let next = GridPosition::on_deck(2, 1, 1);
+        assert!(s.build(next, BuildTool::Floor, &mut rng, &mut events));
+        assert!(s.can_build(GridPosition::new(2, 1), ladder).is_ok());
         assert_eq!(
-            neighbors2[&(1, 0)].kind,
-            TileType::Wall(WallDirection::ExteriorTop),
-            "Upper neighbor is an exterior top wall"
+            Err("Lifts need floor on the deck above"),
+            s.can_build(GridPosition::new(3, 1), ladder),
+            "Walled in up there"
         );
+
+        // Taking it out leaves the floor
+        assert!(s.build(pos, BuildTool::Remove, &mut rng, &mut events));
+        assert_eq!(None, s.get_tile(pos).unwrap().lift);
+        assert_eq!(TileType::Floor, s.get_tile(pos).unwrap().kind);
+    }
+
+    #[test]
+    fn chunk_rect() {
+        // Chunks sit edge to edge, lined up with the tiles in them
+        let first = Station::chunk_rect(ChunkPos::of(GridPosition::new(0, 0)));
+        let next = Station::chunk_rect(ChunkPos::of(GridPosition::new(CHUNK_SIZE, 0)));
+        let corner = Station::tile_corner(GridPosition::new(0, 0));
+        assert_eq!((corner.x, corner.y), (first.x, first.y));
+        assert_eq!(first.right(), next.left());
+        assert_eq!(first.top(), next.top());
+    }
+
+    #[test]
+    fn tile_sprites() {
+        let pos = GridPosition::new(0, 0);
+        for direction in WallDirection::iter() {
+            let parts = Station::tile_sprites(&Tile::new(pos, TileType::Wall(direction)));
+            assert!(!parts.is_empty(), "{:?} has sprites", direction);
+            for part in parts {
+                assert!(
+                    part.area.x >= 0.0
+                        && part.area.y >= 0.0
+                        && part.area.right() <= 1.0
+                        && part.area.bottom() <= 1.0,
+                    "{:?} stays inside its tile",
+                    direction
+                );
+            }
+        }
+
+        let floor = Tile::new(pos, TileType::Floor);
+        let sprites = |tile: &Tile| -> Vec<Sprite> {
+            Station::tile_sprites(tile)
+                .iter()
+                .map(|part| part.sprite)
+                .collect()
+        };
+        assert_eq!(vec![Sprite::Grating], sprites(&floor));
+
+        let mut wall = Tile::new(pos, TileType::Wall(WallDirection::ExteriorTop));
+        wall.damage(MAX_INTEGRITY);
+        assert_eq!(vec![Sprite::HoledPanel], sprites(&wall), "Just the hole");
+
+        let mut door = Door::new(WallDirection::InteriorVertical);
+        door.state = DoorState::Broken;
+        let broken = Tile::new(pos, TileType::Door(door));
         assert_eq!(
-            neighbors2[&(2, 1)].kind,
-            TileType::Floor,
-            "Right neighbor is a floor"
+            vec![Sprite::Grating, Sprite::ScuffedPanel],
+            sprites(&broken)
         );
+
+        let mut ladder = floor.clone();
+        ladder.lift = Some(LiftType::Ladder);
         assert_eq!(
-            neighbors2[&(1, 2)].kind,
-            TileType::Floor,
-            "Below neighbor is a floor"
+            Some(&Sprite::StairsLeft),
+            sprites(&ladder).last(),
+            "Steps over the top of the floor"
         );
-        assert_eq!(
-            neighbors2[&(0, 1)].kind,
-            TileType::Wall(WallDirection::ExteriorLeft),
-            "Left neighbor is an exterior left wall"
+    }
+
+    #[test]
+    fn autotile_exterior() {
+        let s = test_station_from(&["####", "#..#", "#..#", "####"]);
+        assert_walls(
+            &s,
+            &[
+                ((0, 0), WallDirection::ExteriorCornerTopLeft),
+                ((3, 0), WallDirection::ExteriorCornerTopRight),
+                ((0, 3), WallDirection::ExteriorCornerBottomLeft),
+                ((3, 3), WallDirection::ExteriorCornerBottomRight),
+                ((1, 0), WallDirection::ExteriorTop),
+                ((2, 0), WallDirection::ExteriorTop),
+                ((1, 3), WallDirection::ExteriorBottom),
+                ((2, 3), WallDirection::ExteriorBottom),
+                ((0, 1), WallDirection::ExteriorLeft),
+                ((0, 2), WallDirection::ExteriorLeft),
+                ((3, 1), WallDirection::ExteriorRight),
+                ((3, 2), WallDirection::ExteriorRight),
+            ],
         );
     }
 
     #[test]
-    fn search() {
-        let s = test_station_full();
-        let start = GridPosition::new(1, 1);
-        let target = GridPosition::new(2, 2);
-        let search = s.search(start, target);
+    fn autotile_interior() {
+        let s = test_station_from(&["#####", "#.#.#", "#####", "#.#.#", "#####"]);
+        assert_walls(
+            &s,
+            &[
+                ((2, 1), WallDirection::InteriorVertical),
+                ((1, 2), WallDirection::InteriorHorizontal),
+                ((2, 2), WallDirection::InteriorCross),
+            ],
+        );
+    }
 
-        assert_eq!(search.len(), 4, "We can reach 3 tiles plus ourselves");
-        assert_ne!(
-            search[&target],
-            Some(start),
-            "Source of target tile is not start (because that would be a diagonal move)"
+    #[test]
+    fn autotile_interior_corners() {
+        // A block of wall in the middle of a room
+        let s = test_station_from(&["######", "#....#", "#.##.#", "#.##.#", "#....#", "######"]);
+        assert_walls(
+            &s,
+            &[
+                ((2, 2), WallDirection::InteriorCornerTopLeft),
+                ((3, 2), WallDirection::InteriorCornerTopRight),
+                ((2, 3), WallDirection::InteriorCornerBottomLeft),
+                ((3, 3), WallDirection::InteriorCornerBottomRight),
+            ],
         );
     }
 
     #[test]
-    fn path_to() {
-        let mut s = test_station_full();
-        let start = GridPosition::new(1, 1);
-        let target = GridPosition::new(2, 2);
+    fn autotile_full() {
+        let s = test_station_from(&["###", "###", "###", " . ", "  #"]);
+        assert_walls(
+            &s,
+            &[
+                ((1, 1), WallDirection::Full), // Nothing but wall around it
+                ((2, 4), WallDirection::Full), // Nothing around it at all
+            ],
+        );
+    }
 
-        let path = s.path_to(start, target);
-        assert_eq!(path.len(), 2, "Can path to the target in 2 moves");
+    #[test]
+    fn autotile_doors() {
+        let s = test_station_from(&["#####", "#.+.#", "#####"]);
+        match s.get_tile(GridPosition::new(2, 1)).unwrap().kind {
+            TileType::Door(door) => assert_eq!(WallDirection::InteriorVertical, door.direction),
+            _ => panic!("Should be a door"),
+        }
 
-        s.add_tile(Tile::new(target, TileType::Wall(WallDirection::Full)));
-        let path = s.path_to(start, target);
-        assert_eq!(path.len(), 0, "Cannnot path to a wall");
+        let s = test_station_from(&["###", "#.#", "#+#", "#.#", "###"]);
+        match s.get_tile(GridPosition::new(1, 2)).unwrap().kind {
+            TileType::Door(door) => assert_eq!(WallDirection::InteriorHorizontal, door.direction),
+            _ => panic!("Should be a door"),
+        }
     }
 
     #[test]
-    fn find_items() {
-        let mut s = test_station_full();
-        let pos = GridPosition::new(1, 1);
-        let tile = s.get_tile_mut(pos).unwrap();
-        tile.add_item(Item::new(pos, ItemType::Food(FoodType::EnergyBar)));
-
-        let found = s.find_items(vec![ItemType::Food(FoodType::EnergyBar)]);
-        assert_eq!(1, found.len(), "found one food type");
+    fn autotile_on_change() {
+        let mut s = test_station_from(&["#####", "#...#", "#####"]);
+        assert_walls(&s, &[((2, 0), WallDirection::ExteriorTop)]);
 
-        let pos = GridPosition::new(1, 2);
-        let tile = s.get_tile_mut(pos).unwrap();
-        let fridge = Item::new(pos, ItemType::Container(ContainerType::Fridge));
-        tile.add_item(fridge);
+        // Splitting the room joins the new wall up to the ones above and below it
+        s.add_tile(Tile::new(
+            GridPosition::new(2, 1),
+            TileType::Wall(WallDirection::Full),
+        ));
+        assert_walls(
+            &s,
+            &[
+                ((2, 0), WallDirection::InteriorCross),
+                ((2, 1), WallDirection::InteriorVertical),
+                ((2, 2), WallDirection::InteriorCross),
+            ],
+        );
 
-        let found = s.find_items(vec![ItemType::Container(ContainerType::Fridge)]);
-        assert_eq!(1, found.len(), "found one container type");
-        let found = s.find_items(vec![ItemType::Food(FoodType::EnergyBar)]);
-        assert_eq!(
-            6,
-            found.len(),
-            "found another food type (the fridge comes with food)"
+        // And putting the floor back puts things back
+        s.add_tile(Tile::new(GridPosition::new(2, 1), TileType::Floor));
+        assert_walls(
+            &s,
+            &[
+                ((2, 0), WallDirection::ExteriorTop),
+                ((2, 2), WallDirection::ExteriorBottom),
+            ],
         );
+
+        // Taking it away completely leaves a wall with space on both sides
+        s.remove_tile(GridPosition::new(2, 1));
+        assert_walls(&s, &[((2, 0), WallDirection::InteriorHorizontal)]);
     }
 }
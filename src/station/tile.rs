@@ -1,8 +1,11 @@
 use super::gridposition::*;
+use super::hull::*;
 use super::station::*;
+use crate::inhabitant::InhabitantType;
 use crate::item::*;
 
 use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 
 use std::hash::{Hash, Hasher};
 
@@ -11,9 +14,12 @@ type Point2 = glam::Vec2;
 // A Tile object, which the Station is made of
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Tile {
-    pub pos: GridPosition, // x,y position of the tile within the station
-    pub kind: TileType,    // what type of square the tile is
-    pub items: Vec<Item>,  // Items that are present on/in the tile
+    pub pos: GridPosition,      // x,y position of the tile within the station
+    pub kind: TileType,         // what type of square the tile is
+    pub items: Vec<Item>,       // Items that are present on/in the tile
+    pub conduit: bool,          // Whether power cables run under the floor
+    pub lift: Option<LiftType>, // A way up and down to the same kind of thing on the decks above and below
+    pub integrity: u8, // How much more damage it can take, and a wall with none left is a hole
 }
 
 // Tiles are equal if they are in the same spot
@@ -31,16 +37,56 @@ impl Hash for Tile {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Debug, Hash, Serialize, Deserialize)]
 pub enum TileType {
     Floor,
     Wall(WallDirection),
-    Door(WallDirection),
+    Door(Door),
+}
+
+// What gets people between decks
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Hash, EnumIter, Serialize, Deserialize)]
+pub enum LiftType {
+    Ladder,   // Slow going, but always there
+    Elevator, // Quick, but only with power
+}
+
+impl LiftType {
+    // How much longer going up or down a deck takes than walking a tile, in the same units as pathfinding costs
+    pub fn extra_cost(&self) -> usize {
+        match self {
+            LiftType::Ladder => 2000,
+            LiftType::Elevator => 0,
+        }
+    }
+}
+
+// A door sits in a wall between two rooms, and may or may not let people through
+#[derive(Copy, Clone, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub struct Door {
+    pub direction: WallDirection,
+    pub state: DoorState,
+    pub access: DoorAccess,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Hash, EnumIter, Serialize, Deserialize)]
+pub enum DoorState {
+    Open,
+    Closed, // Has to be opened on the way through, which slows people down
+    Locked, // Nobody gets through
+    Broken, // Jammed, and has to be forced open, which is even slower
+}
+
+// Who is allowed through a door
+#[derive(Copy, Clone, PartialEq, Debug, Hash, Serialize, Deserialize)]
+pub enum DoorAccess {
+    Everyone,
+    Only(InhabitantType), // Like only Engineers into engineering
 }
 
 // Walls have lots of different possible directions, which indicate how they are drawn
 // Directions like "top-left" indicate that in a square walled room, this is the top-left corner
-#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, PartialEq, Debug, Hash, EnumIter, Serialize, Deserialize)]
 pub enum WallDirection {
     InteriorVertical,
     InteriorHorizontal,
@@ -60,15 +106,119 @@ pub enum WallDirection {
     Full,
 }
 
+impl Door {
+    // A closed door anyone can use
+    pub fn new(direction: WallDirection) -> Door {
+        Door {
+            direction,
+            state: DoorState::Closed,
+            access: DoorAccess::Everyone,
+        }
+    }
+
+    // Can this type of inhabitant get through the door at all?
+    pub fn can_pass(&self, kind: InhabitantType) -> bool {
+        // Ghosts go through everything
+        if kind == InhabitantType::Ghost {
+            return true;
+        }
+
+        let allowed = match self.access {
+            DoorAccess::Everyone => true,
+            DoorAccess::Only(only) => only == kind,
+        };
+        allowed && self.state != DoorState::Locked
+    }
+
+    // The state after this one, for cycling through them in the UI
+    pub fn next_state(&self) -> DoorState {
+        match self.state {
+            DoorState::Open => DoorState::Closed,
+            DoorState::Closed => DoorState::Locked,
+            DoorState::Locked => DoorState::Broken,
+            DoorState::Broken => DoorState::Open,
+        }
+    }
+
+    // Who's let through after these ones, for cycling through them in the UI
+    // Restricting a door to ghosts would be the same as locking it, so that's left to `next_state`
+    pub fn next_access(&self) -> DoorAccess {
+        match self.access {
+            DoorAccess::Everyone => DoorAccess::Only(InhabitantType::Pilot),
+            DoorAccess::Only(kind) => match kind {
+                InhabitantType::Pilot => DoorAccess::Only(InhabitantType::Engineer),
+                InhabitantType::Engineer => DoorAccess::Only(InhabitantType::Scientist),
+                InhabitantType::Scientist => DoorAccess::Only(InhabitantType::Medic),
+                InhabitantType::Medic => DoorAccess::Only(InhabitantType::Soldier),
+                InhabitantType::Soldier => DoorAccess::Only(InhabitantType::Miner),
+                InhabitantType::Miner => DoorAccess::Only(InhabitantType::Cook),
+                InhabitantType::Cook | InhabitantType::Ghost => DoorAccess::Everyone,
+            },
+        }
+    }
+
+    // How much longer it takes to get through the door than over a floor, in the same units as pathfinding costs
+    pub fn extra_cost(&self) -> usize {
+        match self.state {
+            DoorState::Open | DoorState::Locked => 0,
+            DoorState::Closed => 1000,
+            DoorState::Broken => 4000,
+        }
+    }
+}
+
 impl Tile {
     pub fn new(pos: GridPosition, kind: TileType) -> Tile {
         Tile {
             pos,
             kind,
             items: Vec::new(),
+            conduit: false,
+            lift: None,
+            integrity: MAX_INTEGRITY,
+        }
+    }
+
+    // Has the wall been knocked through, leaving a hole to space?
+    pub fn is_breached(&self) -> bool {
+        matches!(self.kind, TileType::Wall(_)) && self.integrity == 0
+    }
+
+    // Knock some integrity off, returning whether that's made a hole
+    pub fn damage(&mut self, amount: u8) -> bool {
+        let was_breached = self.is_breached();
+        self.integrity = self.integrity.saturating_sub(amount);
+        !was_breached && self.is_breached()
+    }
+
+    // Can this type of inhabitant stand on the tile?
+    pub fn is_passable(&self, kind: InhabitantType) -> bool {
+        match self.kind {
+            TileType::Floor => true,
+            TileType::Wall(_) => false,
+            TileType::Door(door) => door.can_pass(kind),
+        }
+    }
+
+    // Could anyone ever walk here, if all the doors were open?
+    pub fn is_walkable(&self) -> bool {
+        !matches!(self.kind, TileType::Wall(_))
+    }
+
+    // Does air get through the tile? Floors and open doors let it, but closed doors keep it in
+    pub fn lets_air_through(&self) -> bool {
+        match self.kind {
+            TileType::Floor => true,
+            TileType::Wall(_) => false,
+            TileType::Door(door) => door.state == DoorState::Open,
         }
     }
 
+    // Can power run through the tile? Walls and doors have wiring in them, floors need a conduit laying
+    pub fn carries_power(&self) -> bool {
+        self.conduit || self.kind != TileType::Floor
+    }
+
     // Add an item to the tile
     pub fn add_item(&mut self, item: Item) {
         self.items.push(item);
@@ -103,6 +253,21 @@ impl Tile {
         None
     }
 
+    // Take the first item of one of these types off the tile, or out of a container on it, if there is one
+    pub fn take_item(&mut self, item_types: Vec<ItemType>) -> Option<Item> {
+        if let Some(index) = self
+            .items
+            .iter()
+            .position(|item| item_types.contains(&item.get_type()))
+        {
+            return Some(self.items.remove(index));
+        }
+
+        self.items
+            .iter_mut()
+            .find_map(|item| item.take_item(&item_types))
+    }
+
     // Given an item uuid, removes it from the tile
     pub fn remove_item(&mut self, id: uuid::Uuid) {
         self.items.retain(|item| item.get_id() != id)
@@ -116,3 +281,82 @@ impl Tile {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Door, DoorAccess, DoorState, Tile, TileType, WallDirection};
+    use crate::inhabitant::InhabitantType;
+    use crate::station::gridposition::GridPosition;
+    use crate::station::hull::MAX_INTEGRITY;
+
+    #[test]
+    fn door_can_pass() {
+        let mut door = Door::new(WallDirection::InteriorVertical);
+        assert!(
+            door.can_pass(InhabitantType::Cook),
+            "Anyone can use a new door"
+        );
+
+        door.access = DoorAccess::Only(InhabitantType::Engineer);
+        assert!(door.can_pass(InhabitantType::Engineer));
+        assert!(
+            !door.can_pass(InhabitantType::Cook),
+            "Cooks stay out of engineering"
+        );
+
+        door.state = DoorState::Locked;
+        assert!(
+            !door.can_pass(InhabitantType::Engineer),
+            "Nobody gets through a locked door"
+        );
+        assert!(
+            door.can_pass(InhabitantType::Ghost),
+            "Except ghosts, who go anywhere"
+        );
+    }
+
+    #[test]
+    fn door_next_access() {
+        let mut door = Door::new(WallDirection::InteriorVertical);
+        let mut seen = Vec::new();
+        loop {
+            door.access = door.next_access();
+            if door.access == DoorAccess::Everyone {
+                break;
+            }
+            assert!(!seen.contains(&door.access), "{:?} twice", door.access);
+            seen.push(door.access);
+        }
+        assert_eq!(7, seen.len(), "Every kind of crew but ghosts");
+        assert!(seen.contains(&DoorAccess::Only(InhabitantType::Engineer)));
+    }
+
+    #[test]
+    fn door_extra_cost() {
+        let mut door = Door::new(WallDirection::InteriorVertical);
+        door.state = DoorState::Open;
+        assert_eq!(0, door.extra_cost(), "Open doors are like floors");
+
+        door.state = DoorState::Closed;
+        let closed = door.extra_cost();
+        door.state = DoorState::Broken;
+        assert!(
+            door.extra_cost() > closed && closed > 0,
+            "Broken doors are slower than closed ones"
+        );
+    }
+
+    #[test]
+    fn wall_damage() {
+        let mut wall = Tile::new(GridPosition::new(0, 0), TileType::Wall(WallDirection::Full));
+        assert!(!wall.damage(MAX_INTEGRITY - 1), "Still holding");
+        assert!(!wall.is_breached());
+        assert!(wall.damage(50), "That's a hole");
+        assert!(wall.is_breached());
+        assert!(!wall.damage(50), "Only holed once");
+
+        let mut floor = Tile::new(GridPosition::new(0, 0), TileType::Floor);
+        floor.damage(MAX_INTEGRITY);
+        assert!(!floor.is_breached(), "Only walls make holes");
+    }
+}
@@ -0,0 +1,604 @@
+use super::ascii::Symbol;
+use super::gridposition::GridPosition;
+use super::station::Station;
+use super::tile::{Door, DoorState, Tile, TileType, WallDirection};
+use crate::inhabitant::InhabitantType;
+use crate::item::{Item, ItemType};
+
+use oorandom::Rand32;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use std::convert::TryFrom;
+use std::fmt;
+
+type Point2 = glam::Vec2;
+
+// Imports stations drawn in the Tiled map editor (https://www.mapeditor.org), saved as JSON with CSV tile data
+//
+// Tiles come from a tileset called "station", numbered from its first tile:
+//   0       floor
+//   1-16    walls, in the order of `WallDirection`
+//   17-20   doors, closed, open, locked and broken
+// Walls and doors get turned to face the right way once they're in, so which one gets drawn only matters in Tiled
+// Later tile layers go on top of earlier ones
+// A layer with an int `deck` property is on that deck rather than the bottom one, so stations with more than one deck
+// have a tile layer and an object layer for each
+//
+// Objects are placed by their type (called class in newer versions of Tiled):
+//   fridge, locker     furniture, which has to be on a floor
+//   reactor, solarpanel, light, heater, cooler, extinguisher
+//                      generators, fixtures and tools, on a floor too
+//   conduit            power cables under a floor
+//   ladder, elevator   a way up or down, to the same kind of lift in the same place on the next deck
+//   spawn              somewhere for the crew to start, with an optional `kind` property like "Engineer"
+
+// Tiled flips and rotates tiles with the top bits of their ids
+const FLIP_FLAGS: u32 = 0xf000_0000;
+
+// The parts of a Tiled map we care about
+#[derive(Deserialize)]
+struct Map {
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    infinite: bool,
+    layers: Vec<Layer>,
+    tilesets: Vec<Tileset>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+enum Layer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        width: usize,
+        #[serde(default)]
+        data: Option<LayerData>,
+        #[serde(default)]
+        properties: Vec<Property>,
+    },
+    #[serde(rename = "objectgroup")]
+    Objects {
+        objects: Vec<Object>,
+        #[serde(default)]
+        properties: Vec<Property>,
+    },
+    #[serde(other)]
+    Other, // Image layers and groups, which have nothing for us
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LayerData {
+    Csv(Vec<u32>),
+    Encoded(IgnoredAny), // Base64, maybe compressed, which we don't read
+}
+
+#[derive(Deserialize)]
+struct Tileset {
+    firstgid: u32,
+    name: Option<String>,   // For tilesets embedded in the map
+    source: Option<String>, // For tilesets in their own file
+}
+
+#[derive(Deserialize)]
+struct Object {
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    gid: Option<u32>, // Only tile objects have one
+    #[serde(default)]
+    properties: Vec<Property>,
+}
+
+#[derive(Deserialize)]
+struct Property {
+    name: String,
+    value: serde_json::Value,
+}
+
+impl Tileset {
+    // Is this our tileset, whether it's embedded or not?
+    fn is_station(&self) -> bool {
+        let name = match (&self.name, &self.source) {
+            (Some(name), _) => name.as_str(),
+            (None, Some(source)) => source
+                .rsplit('/')
+                .next()
+                .and_then(|file| file.split('.').next())
+                .unwrap_or(""),
+            (None, None) => "",
+        };
+        name == "station"
+    }
+}
+
+impl Object {
+    fn kind(&self) -> &str {
+        if self.class.is_empty() {
+            &self.kind
+        } else {
+            &self.class
+        }
+    }
+
+    // The tile under the middle of the object, on the deck its layer is on
+    // Tile objects hang up from their position rather than down like everything else
+    fn pos(&self, map: &Map, deck: i32) -> GridPosition {
+        let top = match self.gid {
+            Some(_) => self.y - self.height,
+            None => self.y,
+        };
+        let x = (self.x + self.width / 2.0) / map.tilewidth;
+        let y = (top + self.height / 2.0) / map.tileheight;
+        GridPosition::on_deck(x.floor() as i32, y.floor() as i32, deck)
+    }
+
+    fn property(&self, name: &str) -> Option<&serde_json::Value> {
+        property(&self.properties, name)
+    }
+}
+
+fn property<'a>(properties: &'a [Property], name: &str) -> Option<&'a serde_json::Value> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+// Which deck a layer is on, from its `deck` property if it has one
+fn deck(properties: &[Property]) -> Result<i32, TiledError> {
+    match property(properties, "deck") {
+        Some(value) => value
+            .as_i64()
+            .and_then(|deck| i32::try_from(deck).ok())
+            .ok_or_else(|| TiledError::BadDeck(value.to_string())),
+        None => Ok(0),
+    }
+}
+
+// Somewhere the map says a crew member should start
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Spawn {
+    pub pos: GridPosition,
+    pub kind: Option<InhabitantType>, // Anyone, if it doesn't say
+}
+
+// Why a map couldn't be imported
+#[derive(Clone, Debug, PartialEq)]
+pub enum TiledError {
+    Json(String),                        // Not a Tiled JSON map at all
+    Unsupported(&'static str),           // Something Tiled can do that we can't read
+    NoStationTileset,                    // None of the tilesets is called "station"
+    UnknownTile(u32, GridPosition),      // A tile id that isn't one of ours
+    UnknownObject(String, GridPosition), // An object type we don't know about
+    BadSpawnKind(String, GridPosition),  // A spawn point for someone who doesn't exist
+    BadDeck(String),                     // A layer's deck that isn't a whole number
+    OffFloor(String, GridPosition), // Furniture, a spawn point or the like that isn't on a floor
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TiledError::Json(error) => write!(f, "Couldn't read map: {}", error),
+            TiledError::Unsupported(what) => write!(f, "Unsupported: {}", what),
+            TiledError::NoStationTileset => write!(f, "No tileset called \"station\""),
+            TiledError::UnknownTile(gid, pos) => write!(f, "Unknown tile id {} at {}", gid, pos),
+            TiledError::UnknownObject(kind, pos) => {
+                write!(f, "Unknown object type \"{}\" at {}", kind, pos)
+            }
+            TiledError::BadSpawnKind(kind, pos) => {
+                write!(f, "Unknown crew kind \"{}\" at {}", kind, pos)
+            }
+            TiledError::BadDeck(deck) => write!(f, "Deck {} isn't a whole number", deck),
+            TiledError::OffFloor(kind, pos) => {
+                write!(f, "The {} at {} isn't on a floor", kind, pos)
+            }
+        }
+    }
+}
+
+// What a tile id in the station tileset stands for
+fn tile_kind(id: u32) -> Option<TileType> {
+    let walls: Vec<WallDirection> = WallDirection::iter().collect();
+    let doors: Vec<DoorState> = DoorState::iter().collect();
+    let id = id as usize;
+
+    if id == 0 {
+        Some(TileType::Floor)
+    } else if id <= walls.len() {
+        Some(TileType::Wall(walls[id - 1]))
+    } else if id <= walls.len() + doors.len() {
+        let mut door = Door::new(WallDirection::Full);
+        door.state = doors[id - walls.len() - 1];
+        Some(TileType::Door(door))
+    } else {
+        None
+    }
+}
+
+// Read a Tiled JSON map into a station and where its crew should start
+// Furniture comes stocked just like in a generated station, which is what the rng is for
+pub fn import(
+    pos: Point2,
+    json: &str,
+    rng: &mut Rand32,
+) -> Result<(Station, Vec<Spawn>), TiledError> {
+    let map: Map =
+        serde_json::from_str(json).map_err(|error| TiledError::Json(error.to_string()))?;
+    if map.infinite {
+        return Err(TiledError::Unsupported("infinite maps"));
+    }
+
+    // Tile ids run on from one tileset to the next
+    let mut firstgids: Vec<u32> = map
+        .tilesets
+        .iter()
+        .map(|tileset| tileset.firstgid)
+        .collect();
+    firstgids.sort_unstable();
+    let firstgid = map
+        .tilesets
+        .iter()
+        .find(|tileset| tileset.is_station())
+        .ok_or(TiledError::NoStationTileset)?
+        .firstgid;
+    let lastgid = firstgids
+        .into_iter()
+        .find(|gid| *gid > firstgid)
+        .unwrap_or(u32::MAX);
+
+    let mut station = Station::empty(pos);
+    for layer in &map.layers {
+        let (width, data, deck) = match layer {
+            Layer::Tiles {
+                width,
+                data,
+                properties,
+            } => (*width, data, deck(properties)?),
+            _ => continue,
+        };
+        let data = match data {
+            Some(LayerData::Csv(data)) => data,
+            Some(LayerData::Encoded(_)) => {
+                return Err(TiledError::Unsupported(
+                    "compressed tile layers, save them as CSV",
+                ))
+            }
+            None => continue,
+        };
+
+        for (i, gid) in data.iter().enumerate() {
+            let gid = gid & !FLIP_FLAGS;
+            if gid == 0 {
+                continue;
+            }
+
+            let pos = GridPosition::on_deck((i % width) as i32, (i / width) as i32, deck);
+            let kind = if gid >= firstgid && gid < lastgid {
+                tile_kind(gid - firstgid)
+            } else {
+                None
+            };
+            match kind {
+                Some(kind) => station.add_tile(Tile::new(pos, kind)),
+                None => return Err(TiledError::UnknownTile(gid, pos)),
+            }
+        }
+    }
+
+    station.fill_atmosphere();
+    station.fill_temperature();
+
+    // Now there's somewhere to put them, add the objects
+    let mut spawns = Vec::new();
+    for layer in &map.layers {
+        let (objects, deck) = match layer {
+            Layer::Objects {
+                objects,
+                properties,
+            } => (objects, deck(properties)?),
+            _ => continue,
+        };
+
+        for object in objects {
+            let pos = object.pos(&map, deck);
+            let kind = object.kind().to_lowercase();
+            let on_floor = station
+                .get_tile(pos)
+                .is_some_and(|tile| tile.kind == TileType::Floor);
+
+            let symbol = Symbol::from_name(&kind);
+            let item = match symbol {
+                Some(Symbol::Furniture(kind)) => Some(ItemType::Container(kind)),
+                Some(Symbol::Generator(kind)) => Some(ItemType::Generator(kind)),
+                Some(Symbol::Fixture(kind)) => Some(ItemType::Fixture(kind)),
+                Some(Symbol::Tool(kind)) => Some(ItemType::Tool(kind)),
+                _ => None,
+            };
+            match (item, symbol) {
+                (Some(item), _) if on_floor => {
+                    station.add_item(pos, Item::new(pos, item, rng));
+                }
+                (None, Some(Symbol::Conduit)) if on_floor => {
+                    if let Some(tile) = station.get_tile_mut(pos) {
+                        tile.conduit = true;
+                    }
+                }
+                (None, Some(Symbol::Lift(lift))) if on_floor => {
+                    if let Some(tile) = station.get_tile_mut(pos) {
+                        tile.lift = Some(lift);
+                    }
+                }
+                _ if kind == "spawn" && on_floor => {
+                    let kind = match object.property("kind") {
+                        Some(value) => Some(
+                            serde_json::from_value::<InhabitantType>(value.clone())
+                                .map_err(|_| TiledError::BadSpawnKind(value.to_string(), pos))?,
+                        ),
+                        None => None,
+                    };
+                    spawns.push(Spawn { pos, kind });
+                }
+                (Some(_), _) | (None, Some(Symbol::Conduit)) | (None, Some(Symbol::Lift(_))) => {
+                    return Err(TiledError::OffFloor(kind, pos))
+                }
+                _ if kind == "spawn" => return Err(TiledError::OffFloor(kind, pos)),
+                _ => return Err(TiledError::UnknownObject(kind, pos)),
+            }
+        }
+    }
+
+    // Wire up the generators and conduits that came in with them
+    station.update_power();
+
+    Ok((station, spawns))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import, tile_kind, Spawn, TiledError};
+    use crate::inhabitant::InhabitantType;
+    use crate::item::{ContainerType, FixtureType, ItemType};
+    use crate::station::gridposition::GridPosition;
+    use crate::station::tile::{DoorState, LiftType, TileType, WallDirection};
+    use oorandom::Rand32;
+
+    type Point2 = glam::Vec2;
+
+    // A little Tiled map: a 4x3 room with the given tiles, and some objects
+    fn test_map(data: &str, objects: &str) -> String {
+        format!(
+            r#"{{
+                "width": 4, "height": 3, "tilewidth": 30, "tileheight": 30, "infinite": false,
+                "tilesets": [{{ "firstgid": 1, "source": "tilesets/other.tsj" }},
+                             {{ "firstgid": 10, "source": "tilesets/station.tsj" }}],
+                "layers": [
+                    {{ "type": "tilelayer", "name": "station", "width": 4, "height": 3, "data": [{}] }},
+                    {{ "type": "objectgroup", "name": "things", "objects": [{}] }}
+                ]
+            }}"#,
+            data, objects
+        )
+    }
+
+    const ROOM: &str = "26,26,26,26, 26,10,10,26, 26,26,26,26";
+
+    #[test]
+    fn tile_ids() {
+        assert_eq!(Some(TileType::Floor), tile_kind(0));
+        assert_eq!(
+            Some(TileType::Wall(WallDirection::InteriorVertical)),
+            tile_kind(1)
+        );
+        assert_eq!(Some(TileType::Wall(WallDirection::Full)), tile_kind(16));
+        match tile_kind(19) {
+            Some(TileType::Door(door)) => assert_eq!(DoorState::Locked, door.state),
+            _ => panic!("Should be a door"),
+        }
+        assert_eq!(None, tile_kind(21));
+    }
+
+    #[test]
+    fn import_map() {
+        let objects = r#"
+            { "type": "fridge", "x": 30, "y": 30, "width": 30, "height": 30 },
+            { "class": "spawn", "x": 75, "y": 45, "properties": [{ "name": "kind", "type": "string", "value": "Cook" }] },
+            { "type": "spawn", "gid": 10, "x": 60, "y": 60, "width": 30, "height": 30 }
+        "#;
+        let (station, spawns) = import(
+            Point2::zero(),
+            &test_map(ROOM, objects),
+            &mut Rand32::new(1),
+        )
+        .unwrap();
+
+        assert_eq!(12, station.num_tiles());
+        assert_eq!(1, station.get_rooms().count());
+        assert_eq!(
+            TileType::Wall(WallDirection::ExteriorCornerTopLeft),
+            station.get_tile(GridPosition::new(0, 0)).unwrap().kind,
+            "Walls face the right way"
+        );
+        assert!(station
+            .get_tile(GridPosition::new(1, 1))
+            .unwrap()
+            .has_item(vec![ItemType::Container(ContainerType::Fridge)]));
+        assert_eq!(
+            vec![
+                Spawn {
+                    pos: GridPosition::new(2, 1),
+                    kind: Some(InhabitantType::Cook)
+                },
+                Spawn {
+                    pos: GridPosition::new(2, 1),
+                    kind: None
+                },
+            ],
+            spawns
+        );
+    }
+
+    #[test]
+    fn import_errors() {
+        let mut rng = Rand32::new(1);
+        assert_eq!(
+            Err(TiledError::UnknownTile(5, GridPosition::new(1, 1))),
+            import(
+                Point2::zero(),
+                &test_map("26,26,26,26, 26,5,10,26, 26,26,26,26", ""),
+                &mut rng
+            )
+            .map(|_| ()),
+            "Tiles from other tilesets aren't ours"
+        );
+        assert_eq!(
+            Err(TiledError::UnknownTile(31, GridPosition::new(2, 1))),
+            import(
+                Point2::zero(),
+                &test_map("26,26,26,26, 26,10,31,26, 26,26,26,26", ""),
+                &mut rng
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(TiledError::UnknownObject(
+                "airlock".to_string(),
+                GridPosition::new(1, 1)
+            )),
+            import(
+                Point2::zero(),
+                &test_map(ROOM, r#"{ "type": "airlock", "x": 40, "y": 40 }"#),
+                &mut rng
+            )
+            .map(|_| ())
+        );
+        assert_eq!(
+            Err(TiledError::OffFloor(
+                "locker".to_string(),
+                GridPosition::new(0, 0)
+            )),
+            import(
+                Point2::zero(),
+                &test_map(ROOM, r#"{ "type": "locker", "x": 10, "y": 10 }"#),
+                &mut rng
+            )
+            .map(|_| ())
+        );
+        assert!(matches!(
+            import(Point2::zero(), "{}", &mut rng),
+            Err(TiledError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn import_resources() {
+        let (station, spawns) = import(
+            Point2::zero(),
+            include_str!("../../resources/stations/outpost.json"),
+            &mut Rand32::new(1),
+        )
+        .unwrap();
+        assert!(station.validate().is_empty());
+        assert!(!spawns.is_empty());
+        assert!(
+            station.get_networks().any(|network| network.is_powered()),
+            "Something to run the lights"
+        );
+    }
+
+    #[test]
+    fn import_decks() {
+        let json = format!(
+            r#"{{
+                "width": 4, "height": 3, "tilewidth": 30, "tileheight": 30,
+                "tilesets": [{{ "firstgid": 10, "source": "station.tsj" }}],
+                "layers": [
+                    {{ "type": "tilelayer", "width": 4, "data": [{room}] }},
+                    {{ "type": "objectgroup", "objects": [{{ "type": "ladder", "x": 30, "y": 30 }}] }},
+                    {{ "type": "tilelayer", "width": 4, "data": [{room}],
+                       "properties": [{{ "name": "deck", "type": "int", "value": 1 }}] }},
+                    {{ "type": "objectgroup", "objects": [{{ "type": "ladder", "x": 30, "y": 30 }}],
+                       "properties": [{{ "name": "deck", "type": "int", "value": 1 }}] }}
+                ]
+            }}"#,
+            room = ROOM
+        );
+        let (station, _spawns) = import(Point2::zero(), &json, &mut Rand32::new(1)).unwrap();
+
+        assert_eq!(24, station.num_tiles());
+        assert_eq!(
+            vec![0, 1],
+            station.get_decks().into_iter().collect::<Vec<i32>>()
+        );
+        let bottom = GridPosition::new(1, 1);
+        assert_eq!(
+            Some(LiftType::Ladder),
+            station.get_tile(bottom).unwrap().lift
+        );
+        assert_eq!(
+            vec![GridPosition::on_deck(1, 1, 1)],
+            station
+                .get_lift_stops(bottom)
+                .iter()
+                .map(|tile| tile.pos)
+                .collect::<Vec<GridPosition>>(),
+            "Up the ladder to the deck above"
+        );
+
+        assert_eq!(
+            Err(TiledError::BadDeck("\"up\"".to_string())),
+            import(
+                Point2::zero(),
+                &json.replace("\"value\": 1", "\"value\": \"up\""),
+                &mut Rand32::new(1),
+            )
+            .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn import_power() {
+        let objects = r#"
+            { "type": "reactor", "x": 30, "y": 30 },
+            { "type": "conduit", "x": 60, "y": 30 },
+            { "type": "light", "x": 60, "y": 30 }
+        "#;
+        let (station, _spawns) = import(
+            Point2::zero(),
+            &test_map(ROOM, objects),
+            &mut Rand32::new(1),
+        )
+        .unwrap();
+
+        let light = GridPosition::new(2, 1);
+        assert!(station.get_tile(light).unwrap().conduit);
+        assert!(station
+            .get_tile(light)
+            .unwrap()
+            .has_item(vec![ItemType::Fixture(FixtureType::Light)]));
+        assert!(station.is_powered(light), "Wired up once it's all in");
+
+        assert_eq!(
+            Err(TiledError::OffFloor(
+                "conduit".to_string(),
+                GridPosition::new(0, 0)
+            )),
+            import(
+                Point2::zero(),
+                &test_map(ROOM, r#"{ "type": "conduit", "x": 10, "y": 10 }"#),
+                &mut Rand32::new(1),
+            )
+            .map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use ggez::graphics::spritebatch::SpriteBatch;
+use ggez::graphics::{Color, DrawParam, FilterMode, Image, Rect};
+use ggez::{Context, GameResult};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use std::fmt;
+
+type Point2 = glam::Vec2;
+
+// The picture all the station's sprites come from, in the resources directory
+pub const TILESET_PATH: &str = "/sprites/StationTileset.png";
+
+// The tileset is a grid of square cells, each with a sprite in the middle and a pixel of space around it
+const ATLAS_COLUMNS: f32 = 4.0;
+const ATLAS_ROWS: f32 = 3.0;
+const CELL_SIZE: f32 = 50.0;
+const SPRITE_SIZE: f32 = 48.0;
+
+// How the station gets drawn
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Renderer {
+    #[default]
+    Sprites, // Pictures from the tileset
+    Mesh, // Flat coloured shapes, which are easier to see what's going on with when debugging
+}
+
+impl Renderer {
+    // The next renderer along, for cycling through them in the UI
+    pub fn next(&self) -> Renderer {
+        match self {
+            Renderer::Sprites => Renderer::Mesh,
+            Renderer::Mesh => Renderer::Sprites,
+        }
+    }
+}
+
+impl fmt::Display for Renderer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// The pictures in the tileset
+// Corners are named for the corner of the station they cut off, so `CornerTopLeft` is a triangle filling the bottom-right
+#[derive(Copy, Clone, PartialEq, Eq, Debug, EnumIter)]
+pub enum Sprite {
+    Panel,             // A plain hull panel
+    HoledPanel,        // A panel with a hole knocked through it
+    ScuffedPanel,      // A panel that's seen better days
+    Grating,           // Deck plating with rows of rivets
+    CornerTopLeft,     // Half a panel, cut diagonally
+    CornerTopRight,    // ...
+    CornerBottomLeft,  // ...
+    CornerBottomRight, // ...
+    StairsLeft,        // Steps going up to the left
+    StairsRight,       // Steps going up to the right
+}
+
+impl Sprite {
+    // Which cell of the tileset the sprite is in, as (column, row)
+    fn cell(&self) -> (f32, f32) {
+        match self {
+            Sprite::Panel => (0.0, 0.0),
+            Sprite::HoledPanel => (1.0, 0.0),
+            Sprite::ScuffedPanel => (2.0, 0.0),
+            Sprite::Grating => (3.0, 0.0),
+            Sprite::CornerTopLeft => (0.0, 1.0),
+            Sprite::CornerTopRight => (1.0, 1.0),
+            Sprite::CornerBottomLeft => (0.0, 2.0),
+            Sprite::CornerBottomRight => (1.0, 2.0),
+            Sprite::StairsLeft => (2.0, 2.0),
+            Sprite::StairsRight => (3.0, 2.0),
+        }
+    }
+
+    // The part of the tileset the sprite covers, as fractions of the whole image like `DrawParam::src` wants
+    pub fn src(&self) -> Rect {
+        let (column, row) = self.cell();
+        let margin = (CELL_SIZE - SPRITE_SIZE) / 2.0;
+        Rect::new(
+            (column * CELL_SIZE + margin) / (ATLAS_COLUMNS * CELL_SIZE),
+            (row * CELL_SIZE + margin) / (ATLAS_ROWS * CELL_SIZE),
+            SPRITE_SIZE / (ATLAS_COLUMNS * CELL_SIZE),
+            SPRITE_SIZE / (ATLAS_ROWS * CELL_SIZE),
+        )
+    }
+}
+
+// One sprite making up part of how something looks
+// `area` is how much of the tile it covers, in fractions of a tile from the top-left corner
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SpritePart {
+    pub sprite: Sprite,
+    pub area: Rect,
+    pub color: Color,
+}
+
+impl SpritePart {
+    // A sprite covering the whole tile, as it comes
+    pub fn new(sprite: Sprite) -> SpritePart {
+        SpritePart {
+            sprite,
+            area: Rect::new(0.0, 0.0, 1.0, 1.0),
+            color: Color::WHITE,
+        }
+    }
+
+    // Squash the sprite into part of the tile
+    pub fn covering(self, x: f32, y: f32, w: f32, h: f32) -> SpritePart {
+        SpritePart {
+            area: Rect::new(x, y, w, h),
+            ..self
+        }
+    }
+
+    // Colour the sprite in, multiplying with what's there
+    pub fn tinted(self, color: Color) -> SpritePart {
+        SpritePart { color, ..self }
+    }
+
+    // How to draw the part on a tile with its top-left corner at `corner`, darkened by `tint`
+    pub fn param(&self, corner: Point2, tint: Color) -> DrawParam {
+        DrawParam::default()
+            .src(self.sprite.src())
+            .dest(corner + Point2::new(self.area.x, self.area.y) * crate::TILE_WIDTH)
+            .scale(Point2::new(self.area.w, self.area.h) * crate::TILE_WIDTH / SPRITE_SIZE)
+            .color(Color::new(
+                self.color.r * tint.r,
+                self.color.g * tint.g,
+                self.color.b * tint.b,
+                self.color.a * tint.a,
+            ))
+    }
+}
+
+// The tileset image, loaded once and shared by every batch drawn from it
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    image: Image,
+}
+
+impl Tileset {
+    pub fn new(ctx: &mut Context) -> GameResult<Tileset> {
+        let mut image = Image::new(ctx, TILESET_PATH)?;
+
+        // Keep the pixels sharp, and stop the space around each sprite bleeding in at the edges
+        image.set_filter(FilterMode::Nearest);
+
+        Ok(Tileset { image })
+    }
+
+    // An empty batch to add sprites to, for drawing them all at once
+    pub fn batch(&self) -> SpriteBatch {
+        SpriteBatch::new(self.image.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Renderer, Sprite};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn sprites_are_in_the_tileset() {
+        let sprites: Vec<Sprite> = Sprite::iter().collect();
+        for (i, sprite) in sprites.iter().enumerate() {
+            let src = sprite.src();
+            assert!(
+                src.x >= 0.0 && src.y >= 0.0 && src.right() <= 1.0 && src.bottom() <= 1.0,
+                "{:?} is inside the image",
+                sprite
+            );
+            for other in sprites[i + 1..].iter() {
+                assert!(
+                    !src.overlaps(&other.src()),
+                    "{:?} and {:?} are different pictures",
+                    sprite,
+                    other
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn renderer_next() {
+        let first = Renderer::default();
+        assert_eq!(Renderer::Sprites, first, "Sprites unless asked otherwise");
+        assert_ne!(first, first.next());
+        assert_eq!(first, first.next().next(), "Cycles back round");
+    }
+}
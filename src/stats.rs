@@ -0,0 +1,187 @@
+use crate::events::{DamageCause, SimEvent};
+use crate::inhabitant::{Inhabitant, InhabitantType};
+use crate::item::ItemType;
+use crate::simulation::{Simulation, TICKS_PER_SECOND};
+
+use std::collections::BTreeMap;
+use std::io;
+
+// What the crew looked like at one point in a run
+#[derive(Clone, PartialEq, Debug)]
+pub struct Sample {
+    pub tick: u64,
+    pub alive: usize,
+    pub avg_hunger: f32,
+    pub avg_thirst: f32,
+    pub deaths: u32,
+    pub items_consumed: u32,
+    pub path_failures: u32,
+}
+
+// Tallies what happens over a run of the simulation, for tuning balance without a window
+// Feed it every event with `record`, and call `sample` every so often for the time series
+#[derive(Default)]
+pub struct RunStats {
+    pub deaths: BTreeMap<DamageCause, u32>,
+    pub items_consumed: BTreeMap<ItemType, u32>,
+    pub path_failures: u32,
+    pub samples: Vec<Sample>,
+}
+
+impl RunStats {
+    pub fn new() -> RunStats {
+        RunStats::default()
+    }
+
+    // Count an event, if it's one we care about
+    pub fn record(&mut self, event: &SimEvent) {
+        match event {
+            SimEvent::Died { cause, .. } => *self.deaths.entry(*cause).or_insert(0) += 1,
+            SimEvent::ItemConsumed { item, .. } => {
+                *self.items_consumed.entry(*item).or_insert(0) += 1
+            }
+            SimEvent::PathFailed { .. } => self.path_failures += 1,
+            _ => (),
+        }
+    }
+
+    // Note down how the living crew are doing right now
+    pub fn sample(&mut self, simulation: &Simulation) {
+        let living: Vec<&Inhabitant> = simulation
+            .inhabitants
+            .iter()
+            .filter(|inhabitant| inhabitant.get_kind() != InhabitantType::Ghost)
+            .collect();
+        let average = |value: fn(&Inhabitant) -> u8| {
+            if living.is_empty() {
+                return 0.0;
+            }
+            living.iter().map(|i| value(i) as f32).sum::<f32>() / living.len() as f32
+        };
+
+        let sample = Sample {
+            tick: simulation.ticks(),
+            alive: living.len(),
+            avg_hunger: average(Inhabitant::get_hunger),
+            avg_thirst: average(Inhabitant::get_thirst),
+            deaths: self.total_deaths(),
+            items_consumed: self.items_consumed.values().sum(),
+            path_failures: self.path_failures,
+        };
+        self.samples.push(sample);
+    }
+
+    pub fn total_deaths(&self) -> u32 {
+        self.deaths.values().sum()
+    }
+
+    // Average hunger of the living crew over every sample taken
+    pub fn avg_hunger(&self) -> f32 {
+        self.average_of(|sample| sample.avg_hunger)
+    }
+
+    // Average thirst of the living crew over every sample taken
+    pub fn avg_thirst(&self) -> f32 {
+        self.average_of(|sample| sample.avg_thirst)
+    }
+
+    fn average_of(&self, value: fn(&Sample) -> f32) -> f32 {
+        let samples: Vec<&Sample> = self.samples.iter().filter(|s| s.alive > 0).collect();
+        if samples.is_empty() {
+            return 0.0;
+        }
+        samples.iter().map(|sample| value(sample)).sum::<f32>() / samples.len() as f32
+    }
+
+    // Fold another run into this one, for totals across many seeds
+    pub fn merge(&mut self, other: &RunStats) {
+        for (cause, count) in &other.deaths {
+            *self.deaths.entry(*cause).or_insert(0) += count;
+        }
+        for (item, count) in &other.items_consumed {
+            *self.items_consumed.entry(*item).or_insert(0) += count;
+        }
+        self.path_failures += other.path_failures;
+        self.samples.extend(other.samples.iter().cloned());
+    }
+
+    // Write the samples out as CSV rows, each starting with `label` (usually the seed)
+    pub fn write_csv<W: io::Write>(&self, writer: &mut W, label: &str) -> io::Result<()> {
+        for sample in &self.samples {
+            writeln!(
+                writer,
+                "{},{},{:.2},{},{:.2},{:.2},{},{},{}",
+                label,
+                sample.tick,
+                sample.tick as f64 / (TICKS_PER_SECOND as f64 * 60.0 * 60.0),
+                sample.alive,
+                sample.avg_hunger,
+                sample.avg_thirst,
+                sample.deaths,
+                sample.items_consumed,
+                sample.path_failures
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// The header row matching `RunStats::write_csv`
+pub const CSV_HEADER: &str =
+    "seed,tick,hours,alive,avg_hunger,avg_thirst,deaths,items_consumed,path_failures";
+
+#[cfg(test)]
+mod tests {
+    use super::RunStats;
+    use crate::events::*;
+    use crate::item::*;
+    use crate::seed::GameSeed;
+    use crate::simulation::Simulation;
+    use crate::station::generator::CellularAutomaton;
+
+    use uuid::Uuid;
+
+    type Point2 = glam::Vec2;
+
+    #[test]
+    fn record_and_sample() {
+        let mut stats = RunStats::new();
+        stats.record(&SimEvent::Died {
+            inhabitant: Uuid::nil(),
+            cause: DamageCause::Starvation,
+        });
+        stats.record(&SimEvent::ItemConsumed {
+            inhabitant: Uuid::nil(),
+            item: ItemType::Drink(DrinkType::Water),
+            from: ItemSource::Inventory,
+        });
+        stats.record(&SimEvent::ItemConsumed {
+            inhabitant: Uuid::nil(),
+            item: ItemType::Drink(DrinkType::Water),
+            from: ItemSource::Tile,
+        });
+        assert_eq!(Some(&1), stats.deaths.get(&DamageCause::Starvation));
+        assert_eq!(
+            Some(&2),
+            stats.items_consumed.get(&ItemType::Drink(DrinkType::Water))
+        );
+
+        let sim = Simulation::new(
+            GameSeed::new(42),
+            Point2::zero(),
+            &CellularAutomaton::default(),
+            3,
+        );
+        stats.sample(&sim);
+        assert_eq!(3, stats.samples[0].alive);
+        assert_eq!(0.0, stats.avg_hunger(), "Nobody is hungry yet");
+        assert_eq!(2, stats.samples[0].items_consumed);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv, "42").unwrap();
+        assert_eq!(
+            "42,0,0.00,3,0.00,0.00,1,2,0\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+}